@@ -0,0 +1,157 @@
+//! 읽기/쓰기 경합에 따른 처리량 비교. `cargo bench --bench contention`
+//!
+//! 멀티 스레드 런타임에서 태스크 수를 바꿔 가며 읽기만, 쓰기만, 섞은(쓰기 1/8) 부하를 정해진
+//! 시간 동안 돌리고 초당 획득 수를 표로 출력한다. 세어 둔 획득 수는 락의 통계 스냅샷과 맞춰 본다.
+//! 이벤트는 만들지 않도록 출력을 끄고 관찰자를 달지 않는다.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+/// 부하 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workload {
+    Read,
+    Write,
+    /// 여덟 번에 한 번 쓰기
+    Mixed,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [Workload::Read, Workload::Write, Workload::Mixed];
+
+    fn label(self) -> &'static str {
+        match self {
+            Workload::Read => "read",
+            Workload::Write => "write",
+            Workload::Mixed => "mixed 1:8",
+        }
+    }
+}
+
+/// 한 번 돌린 결과 (표의 한 줄)
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub workload: Workload,
+    pub tasks: usize,
+    pub reads: u64,
+    pub writes: u64,
+    pub elapsed: Duration,
+    /// 돌린 뒤의 `contention_ratio`
+    pub contention: f64,
+    /// 통계 스냅샷의 획득 수가 태스크들이 센 수와 같은지
+    pub stats_agree: bool,
+}
+
+impl Row {
+    pub fn per_sec(&self) -> f64 {
+        (self.reads + self.writes) as f64 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// `tasks` 개의 태스크가 `duration` 동안 `workload` 를 돌린다.
+pub fn run(
+    runtime: &tokio::runtime::Runtime,
+    workload: Workload,
+    tasks: usize,
+    duration: Duration,
+) -> Row {
+    let lock = Arc::new(TokioRwLockTrace::new(0u64).with_config(LockConfig::new().silent()));
+    let reads = Arc::new(AtomicU64::new(0));
+    let writes = Arc::new(AtomicU64::new(0));
+    let start = Instant::now();
+    let deadline = start + duration;
+
+    runtime.block_on(async {
+        let handles: Vec<_> = (0..tasks)
+            .map(|task| {
+                let (lock, reads, writes) = (lock.clone(), reads.clone(), writes.clone());
+                tokio::spawn(async move {
+                    let mut n = task;
+                    while Instant::now() < deadline {
+                        let write = match workload {
+                            Workload::Read => false,
+                            Workload::Write => true,
+                            Workload::Mixed => n % 8 == 0,
+                        };
+                        // 임계 구역 안에서 한 번 양보해 비동기 작업을 흉내 낸다. 그래야 쥔 채로
+                        // 다른 태스크가 돌면서 경합이 생긴다.
+                        if write {
+                            let mut guard = lock.write().await;
+                            tokio::task::yield_now().await;
+                            *guard += 1;
+                            drop(guard);
+                            writes.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            let guard = lock.read().await;
+                            tokio::task::yield_now().await;
+                            std::hint::black_box(*guard);
+                            drop(guard);
+                            reads.fetch_add(1, Ordering::Relaxed);
+                        }
+                        n += 1;
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    });
+
+    let elapsed = start.elapsed();
+    let (reads, writes) = (
+        reads.load(Ordering::Relaxed),
+        writes.load(Ordering::Relaxed),
+    );
+    let stats = lock.stats();
+    Row {
+        workload,
+        tasks,
+        reads,
+        writes,
+        elapsed,
+        contention: lock.contention_ratio(),
+        stats_agree: stats.reads == reads && stats.writes == writes,
+    }
+}
+
+pub fn print_table(rows: &[Row]) {
+    println!(
+        "{:<10} {:>6} {:>14} {:>10} {:>10} {:>11}",
+        "workload", "tasks", "acquires/s", "reads", "writes", "contention"
+    );
+    for row in rows {
+        println!(
+            "{:<10} {:>6} {:>14.0} {:>10} {:>10} {:>10.1}%{}",
+            row.workload.label(),
+            row.tasks,
+            row.per_sec(),
+            row.reads,
+            row.writes,
+            row.contention * 100.0,
+            if row.stats_agree {
+                ""
+            } else {
+                "  (stats disagree!)"
+            }
+        );
+    }
+}
+
+#[allow(dead_code)]
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut rows = Vec::new();
+    for workload in Workload::ALL {
+        for tasks in [1, 4, 16, 64] {
+            rows.push(run(&runtime, workload, tasks, Duration::from_millis(500)));
+        }
+    }
+    print_table(&rows);
+}
@@ -0,0 +1,67 @@
+//! 계측 비용만 재는 벤치마크. `cargo bench --features dry-run`
+//!
+//! 같은 단일 태스크 루프에서 계측 없는 `tokio::sync::RwLock` 과 `read_dry_run`/`write_dry_run`
+//! 을 비교한다. 출력은 끄고 아무것도 하지 않는 관찰자를 달아 이벤트는 끝까지 만들어진다.
+//! 이벤트가 버려지지 않도록 속도 제한은 사실상 끈다.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tokio::sync::RwLock;
+use tracing_lock::{LockConfig, LockEvent, TokioRwLockTrace};
+
+const ITERATIONS: u32 = 200_000;
+
+async fn measure<F, Fut>(mut op: F) -> Duration
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    // 한 번 돌려서 일회성 초기화를 빼고 잰다.
+    op().await;
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        op().await;
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let raw = RwLock::new(0u64);
+    let traced = TokioRwLockTrace::new(0u64).with_config(
+        LockConfig::new()
+            .name("bench")
+            .silent()
+            .rate_limit(u32::MAX)
+            .observer(|event: &LockEvent| {
+                black_box(event);
+            }),
+    );
+
+    runtime.block_on(async {
+        let raw_read = measure(|| async { drop(black_box(raw.read().await)) }).await;
+        let raw_write = measure(|| async { drop(black_box(raw.write().await)) }).await;
+        let dry_read = measure(|| async { drop(black_box(traced.read_dry_run().await)) }).await;
+        let dry_write = measure(|| async { drop(black_box(traced.write_dry_run().await)) }).await;
+
+        println!(
+            "{:<12} {:>10} {:>12} {:>14}",
+            "op", "raw", "dry-run", "instrumentation"
+        );
+        for (op, raw, dry) in [
+            ("read", raw_read, dry_read),
+            ("write", raw_write, dry_write),
+        ] {
+            println!(
+                "{:<12} {:>10?} {:>12?} {:>14?}",
+                op,
+                raw,
+                dry,
+                dry.saturating_sub(raw)
+            );
+        }
+    });
+}
@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+#[tokio::main]
+async fn main() {
+    // 이벤트는 `debug` 레벨의 `tracing` 이벤트로 나간다.
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let rw_lock = Arc::new(RwLock::new(5));
+    let logging_lock =
+        TokioRwLockTrace::from(rw_lock.clone()).with_config(LockConfig::new().name("demo"));
+
+    let _ = tokio::spawn(async move {
+        {
+            let read_guard = logging_lock.read().await;
+            println!("Read value: {}", *read_guard);
+        }
+
+        {
+            let mut write_guard = logging_lock.write().await;
+            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+            *write_guard += 1;
+            println!("Updated value: {}", *write_guard);
+        }
+    })
+    .await;
+}
@@ -0,0 +1,161 @@
+//! `try_write` 를 지수적으로 늘어나는 간격으로 되풀이하는 획득.
+
+use std::{error::Error, fmt, time::Duration};
+
+use crate::event::{AccessKind, EventKind};
+use crate::guard::LoggingRwLockWriteGuard;
+use crate::lock::TokioRwLockTrace;
+
+pub(crate) const DEFAULT_BASE: Duration = Duration::from_millis(1);
+pub(crate) const DEFAULT_CAP: Duration = Duration::from_millis(100);
+
+/// `write_backoff` 가 정해진 횟수 안에 쓰기 락을 얻지 못함
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contended {
+    /// 실제로 시도한 횟수
+    pub attempts: u32,
+}
+
+impl fmt::Display for Contended {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "write lock still contended after {} attempts",
+            self.attempts
+        )
+    }
+}
+
+impl Error for Contended {}
+
+impl<T> TokioRwLockTrace<T> {
+    /// 대기열에 서지 않고 `try_write` 를 최대 `max_attempts` 번 되풀이한다. (0 이면 한 번)
+    ///
+    /// 실패할 때마다 `Busy` 이벤트를 남기고 `LockConfig::backoff` 로 정한 만큼 쉰다. 쉬는 시간은
+    /// 두 배씩 늘어난다. 성공하면 평소의 획득 이벤트가, 끝내 실패하면 경고 이벤트가 남는다. 기다리는 동안 락을 대기하지 않으므로 `select!` 의 다른 가지(취소 등)에
+    /// 바로 반응할 수 있다.
+    ///
+    /// ```
+    /// use tracing_lock::{Contended, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let reader = lock.read().await;
+    /// assert_eq!(lock.write_backoff(2).await.err(), Some(Contended { attempts: 2 }));
+    /// drop(reader);
+    /// *lock.write_backoff(2).await.unwrap() += 1;
+    /// # }
+    /// ```
+    pub async fn write_backoff(
+        &self,
+        max_attempts: u32,
+    ) -> Result<LoggingRwLockWriteGuard<'_, T>, Contended> {
+        let attempts = max_attempts.max(1);
+        let (base, cap) = self.config.backoff.unwrap_or((DEFAULT_BASE, DEFAULT_CAP));
+        let mut delay = base.min(cap);
+        for attempt in 1..=attempts {
+            let message = if attempt == attempts {
+                format!("attempt {attempt}/{attempts} failed")
+            } else {
+                format!("attempt {attempt}/{attempts} failed, retrying in {delay:?}")
+            };
+            if let Ok(guard) = self.try_write_with((), Some(message)) {
+                return Ok(guard);
+            }
+            if attempt < attempts {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2).min(cap);
+            }
+        }
+        log_call_info!(
+            &self.config,
+            EventKind::Warning,
+            AccessKind::Write,
+            message: Some(format!("write_backoff gave up after {attempts} attempts")),
+        );
+        Err(Contended { attempts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::LockConfig;
+
+    fn busy_messages(events: &CapturingObserver) -> Vec<String> {
+        events
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Busy)
+            .map(|e| e.message.clone().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn succeeds_once_brief_writer_releases() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .backoff(Duration::from_millis(2), Duration::from_millis(8))
+                .observer(events.clone()),
+        );
+        let holder = lock.write().await;
+        let release = async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            drop(holder);
+        };
+
+        let (_, guard) = tokio::join!(release, lock.write_backoff(20));
+        *guard.unwrap() += 1;
+
+        let busy = busy_messages(&events);
+        assert!(!busy.is_empty());
+        assert_eq!(busy[0], "attempt 1/20 failed, retrying in 2ms");
+        // 2ms, 4ms, 8ms, 8ms, ... 로 늘어나다 멈춘다.
+        assert!(busy.iter().skip(3).all(|m| m.ends_with("retrying in 8ms")));
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert!(!kinds.contains(&EventKind::Warning));
+        assert_eq!(lock.stats().writes, 2);
+        assert_eq!(*lock.raw().try_read().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .backoff(Duration::from_millis(1), Duration::from_millis(2))
+                .observer(events.clone()),
+        );
+        let _reader = lock.read().await;
+
+        let err = lock.write_backoff(3).await.err().unwrap();
+        assert_eq!(err, Contended { attempts: 3 });
+        assert_eq!(
+            err.to_string(),
+            "write lock still contended after 3 attempts"
+        );
+        assert_eq!(
+            busy_messages(&events),
+            [
+                "attempt 1/3 failed, retrying in 1ms",
+                "attempt 2/3 failed, retrying in 2ms",
+                "attempt 3/3 failed",
+            ]
+        );
+        let warning = events
+            .events()
+            .iter()
+            .find(|e| e.kind == EventKind::Warning)
+            .and_then(|e| e.message.clone());
+        assert_eq!(
+            warning.as_deref(),
+            Some("write_backoff gave up after 3 attempts")
+        );
+        assert_eq!(lock.stats().writes, 0);
+    }
+}
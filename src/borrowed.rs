@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::config::LockConfig;
+use crate::future::{ReadFuture, WriteFuture};
+use crate::registry::{self, Entry};
+use crate::stats::LockStatsSnapshot;
+
+/// 빌린 `RwLock` 을 계측하는 래퍼. `TokioRwLockTrace::borrow` 로 만든다.
+///
+/// 가드의 수명은 원래 락에 대한 빌림에 묶인다.
+pub struct BorrowedRwLockTrace<'a, T> {
+    inner: &'a RwLock<T>,
+    config: Arc<LockConfig>,
+    entry: Arc<Entry>,
+}
+
+impl<'a, T> BorrowedRwLockTrace<'a, T> {
+    #[track_caller]
+    pub(crate) fn new(inner: &'a RwLock<T>) -> Self {
+        let config = LockConfig::inherited();
+        let entry = registry::register();
+        entry.configure(&config);
+        BorrowedRwLockTrace {
+            inner,
+            config: Arc::new(config),
+            entry,
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        let config = config.overlay(&self.config);
+        self.entry.configure(&config);
+        self.config = Arc::new(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.config.name.as_deref()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.entry.stats.snapshot()
+    }
+
+    #[track_caller]
+    pub fn read(&self) -> ReadFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        ReadFuture::new(self.inner, &self.config, &self.entry, None, ())
+    }
+
+    #[track_caller]
+    pub fn write(&self) -> WriteFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        WriteFuture::new(self.inner, &self.config, &self.entry, None, ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LockConfig, TokioRwLockTrace};
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn wraps_stack_local_lock_by_reference() {
+        let raw = RwLock::new(String::from("a"));
+        {
+            let lock = TokioRwLockTrace::borrow(&raw).with_config(LockConfig::new().name("local"));
+            lock.write().await.push('b');
+            assert_eq!(&*lock.read().await, "ab");
+            assert_eq!((lock.stats().reads, lock.stats().writes), (1, 1));
+        }
+        // 래퍼가 사라진 뒤에도 원래 락은 그대로 사용할 수 있다.
+        assert_eq!(raw.into_inner(), "ab");
+    }
+}
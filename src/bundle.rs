@@ -0,0 +1,156 @@
+//! 여러 락을 전부 잡거나 하나도 잡지 않는 비차단 묶음 획득.
+
+use crate::guard::LoggingRwLockWriteGuard;
+use crate::lock::TokioRwLockTrace;
+
+/// 같은 타입의 락들을 순서대로 `try_write` 한다.
+///
+/// 하나라도 이미 잡혀 있으면 그때까지 잡은 가드를 역순으로 해제하고 `None` 을 돌려준다.
+/// 포기하게 만든 락에는 `Busy` 이벤트가 남는다. 타입이 다른 락은 `try_lock_all!` 을 쓴다.
+///
+/// ```
+/// use tracing_lock::{try_lock_all, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let accounts = [TokioRwLockTrace::new(100), TokioRwLockTrace::new(0)];
+/// if let Some(mut guards) = try_lock_all(&accounts) {
+///     *guards[0] -= 30;
+///     *guards[1] += 30;
+/// }
+/// assert_eq!(*accounts[1].read().await, 30);
+/// # }
+/// ```
+#[track_caller]
+pub fn try_lock_all<'a, T: 'a>(
+    locks: impl IntoIterator<Item = &'a TokioRwLockTrace<T>>,
+) -> Option<Vec<LoggingRwLockWriteGuard<'a, T>>> {
+    let mut guards = Vec::new();
+    for lock in locks {
+        match lock.__try_write_in_bundle() {
+            Some(guard) => guards.push(guard),
+            None => {
+                while let Some(guard) = guards.pop() {
+                    drop(guard);
+                }
+                return None;
+            }
+        }
+    }
+    Some(guards)
+}
+
+/// 타입이 다른 `TokioRwLockTrace` 들을 전부 `try_write` 해서 가드 튜플을 돌려준다.
+///
+/// 하나라도 실패하면 이미 잡은 가드를 해제하고 `None` 이 된다. (`try_lock_all` 참고)
+///
+/// ```
+/// use tracing_lock::{try_lock_all, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let balance = TokioRwLockTrace::new(100_u64);
+/// let history = TokioRwLockTrace::new(Vec::<String>::new());
+/// if let Some((mut balance, mut history)) = try_lock_all!(balance, history) {
+///     *balance -= 30;
+///     history.push("withdraw 30".to_string());
+/// }
+/// assert_eq!(*balance.read().await, 70);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_lock_all {
+    ($($lock:expr),+ $(,)?) => {
+        // `?` 로 빠져나가면 이미 만들어진 튜플 원소(가드)는 역순으로 드롭된다.
+        (|| Some(($($lock.__try_write_in_bundle()?,)+)))()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig, LockEvent, TokioRwLockTrace};
+
+    fn recorded<T>(value: T, name: &str, events: &CapturingObserver) -> TokioRwLockTrace<T> {
+        TokioRwLockTrace::new(value)
+            .with_config(LockConfig::new().name(name).observer(events.clone()))
+    }
+
+    // 생성 이벤트는 첫 획득 순서에 끼어들므로 세지 않는다.
+    fn acquisitions(events: &CapturingObserver) -> Vec<LockEvent> {
+        let mut events = events.events();
+        events.retain(|e| e.kind != EventKind::Created);
+        events
+    }
+
+    #[tokio::test]
+    async fn bundle_backs_off_when_one_lock_is_held() {
+        let events = CapturingObserver::new();
+        let counter = recorded(0_u32, "counter", &events);
+        let label = recorded(String::new(), "label", &events);
+        let flags = recorded(vec![false], "flags", &events);
+
+        {
+            let _held = label.read().await;
+            assert!(try_lock_all!(counter, label, flags).is_none());
+            // 잡았던 `counter` 는 이미 풀렸다.
+            assert!(counter.try_write().is_ok());
+        }
+        let (counter, label, flags) = try_lock_all!(&counter, &label, &flags).unwrap();
+        assert_eq!((*counter, label.as_str(), flags.len()), (0, "", 1));
+
+        let events = acquisitions(&events);
+        let summary: Vec<_> = events
+            .iter()
+            .take(5)
+            .map(|e| (e.kind, e.name.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (EventKind::Acquire, "label"),
+                (EventKind::Acquire, "counter"),
+                (EventKind::Busy, "label"),
+                (EventKind::Release, "counter"),
+                (EventKind::Acquire, "counter"),
+            ]
+        );
+        assert!(events[2]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("try_lock_all"));
+        assert!(!events
+            .iter()
+            .any(|e| e.kind == EventKind::Busy && e.name.as_deref() != Some("label")));
+    }
+
+    #[tokio::test]
+    async fn homogeneous_bundle_releases_in_reverse_order() {
+        let events = CapturingObserver::new();
+        let locks = [
+            recorded(1, "a", &events),
+            recorded(2, "b", &events),
+            recorded(3, "c", &events),
+        ];
+        let _held = locks[2].write().await;
+        assert!(super::try_lock_all(&locks).is_none());
+
+        let names: Vec<_> = acquisitions(&events)
+            .iter()
+            .skip(1)
+            .map(|e| (e.kind, e.name.clone().unwrap()))
+            .collect();
+        let event = |kind, name: &str| (kind, name.to_string());
+        assert_eq!(
+            names,
+            [
+                event(EventKind::Acquire, "a"),
+                event(EventKind::Acquire, "b"),
+                event(EventKind::Busy, "c"),
+                event(EventKind::Release, "b"),
+                event(EventKind::Release, "a"),
+            ]
+        );
+    }
+}
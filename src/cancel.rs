@@ -0,0 +1,107 @@
+//! `CancellationToken` 과 경쟁하는 획득. (`cancel` 기능)
+
+use std::{error::Error, fmt};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::guard::{LoggingRwLockReadGuard, LoggingRwLockWriteGuard};
+use crate::lock::TokioRwLockTrace;
+
+/// 락을 얻기 전에 토큰이 취소됨
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("lock acquisition cancelled by token")
+    }
+}
+
+impl Error for Cancelled {}
+
+impl<T: Send + Sync> TokioRwLockTrace<T> {
+    /// 읽기 락을 기다리다가 `token` 이 먼저 취소되면 `Err(Cancelled)` 를 돌려준다.
+    ///
+    /// 기다리던 중에 취소되면 취소 이벤트가 남고 대기자 집계에서도 빠진다. 락을 바로 얻을 수
+    /// 있으면 토큰이 이미 취소되었어도 락을 얻는다.
+    ///
+    /// ```
+    /// use tokio_util::sync::CancellationToken;
+    /// use tracing_lock::{Cancelled, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let token = CancellationToken::new();
+    /// let writer = lock.write().await;
+    /// token.cancel();
+    /// assert_eq!(lock.read_with_cancel(&token).await.err(), Some(Cancelled));
+    /// # drop(writer);
+    /// # }
+    /// ```
+    pub async fn read_with_cancel(
+        &self,
+        token: &CancellationToken,
+    ) -> Result<LoggingRwLockReadGuard<'_, T>, Cancelled> {
+        tokio::select! {
+            biased;
+            guard = self.read() => Ok(guard),
+            _ = token.cancelled() => Err(Cancelled),
+        }
+    }
+
+    /// 쓰기 락을 기다리다가 `token` 이 먼저 취소되면 `Err(Cancelled)` 를 돌려준다.
+    pub async fn write_with_cancel(
+        &self,
+        token: &CancellationToken,
+    ) -> Result<LoggingRwLockWriteGuard<'_, T>, Cancelled> {
+        tokio::select! {
+            biased;
+            guard = self.write() => Ok(guard),
+            _ = token.cancelled() => Err(Cancelled),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::{AccessKind, EventKind, LockConfig};
+
+    #[tokio::test]
+    async fn token_cancels_reader_waiting_behind_writer() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone())),
+        );
+        let token = CancellationToken::new();
+        let writer = lock.write().await;
+
+        let reader = tokio::spawn({
+            let (lock, token) = (lock.clone(), token.clone());
+            async move { lock.read_with_cancel(&token).await.map(|guard| *guard) }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        token.cancel();
+        assert_eq!(reader.await.unwrap(), Err(Cancelled));
+        assert_eq!(lock.stats().waiters, 0);
+        drop(writer);
+
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| (e.kind, e.access)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (EventKind::Created, AccessKind::Write),
+                (EventKind::Acquire, AccessKind::Write),
+                (EventKind::Cancelled, AccessKind::Read),
+                (EventKind::Release, AccessKind::Write),
+            ]
+        );
+    }
+}
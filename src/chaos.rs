@@ -0,0 +1,99 @@
+//! 테스트 전용 "공정성 퍼저". `chaos` 기능을 켰을 때만 컴파일된다.
+//!
+//! 획득 직전에 시드로 결정되는 의사 난수 지연(`yield_now` 또는 짧은 sleep)을 넣어
+//! 락을 사용하는 코드의 순서 의존 버그를 드러낸다. 같은 시드는 같은 지연 순서를 만든다.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+/// 한 번의 획득 앞에 넣을 지연
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChaosDelay {
+    None,
+    Yield(u32),
+    Sleep(Duration),
+}
+
+impl ChaosDelay {
+    pub(crate) async fn apply(self) {
+        match self {
+            ChaosDelay::None => {}
+            ChaosDelay::Yield(times) => {
+                for _ in 0..times {
+                    tokio::task::yield_now().await;
+                }
+            }
+            ChaosDelay::Sleep(duration) => tokio::time::sleep(duration).await,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Chaos {
+    state: AtomicU64,
+}
+
+impl Chaos {
+    pub(crate) fn new(seed: u64) -> Self {
+        Chaos {
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    pub(crate) fn next_delay(&self) -> ChaosDelay {
+        // splitmix64
+        let mut z = self
+            .state
+            .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+            .wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        match z % 4 {
+            0 => ChaosDelay::None,
+            1 | 2 => ChaosDelay::Yield(1 + (z >> 8) as u32 % 3),
+            _ => ChaosDelay::Sleep(Duration::from_micros(50 + (z >> 8) % 450)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::TokioRwLockTrace;
+
+    fn sequence(seed: u64) -> Vec<ChaosDelay> {
+        let chaos = Chaos::new(seed);
+        (0..32).map(|_| chaos.next_delay()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_delays() {
+        assert_eq!(sequence(7), sequence(7));
+        assert_ne!(sequence(7), sequence(8));
+        assert!(sequence(7).iter().any(|delay| *delay != ChaosDelay::None));
+    }
+
+    #[tokio::test]
+    async fn chaotic_lock_still_excludes_writers() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_chaos(42));
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let lock = lock.clone();
+                tokio::spawn(async move {
+                    for _ in 0..5 {
+                        *lock.write().await += 1;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*lock.read().await, 40);
+    }
+}
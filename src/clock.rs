@@ -0,0 +1,16 @@
+//! 점유 시간을 재는 시계.
+
+use std::time::Instant;
+
+/// 가드의 점유 시간을 잴 때 쓰는 시계. `LockConfig::clock` 으로 바꿀 수 있다.
+///
+/// 구현이 단조롭지 않아도(이전보다 이른 시각을 돌려줘도) 점유 시간은 0 으로 처리된다.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+impl<F: Fn() -> Instant + Send + Sync> Clock for F {
+    fn now(&self) -> Instant {
+        self()
+    }
+}
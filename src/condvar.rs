@@ -0,0 +1,259 @@
+//! 락이 보호하는 값에 대한 조건 변수처럼 쓰는 대기.
+
+use std::future::Future;
+use std::time::Duration;
+
+use crate::event::{AccessKind, EventKind};
+use crate::guard::LoggingRwLockReadGuard;
+use crate::lock::TokioRwLockTrace;
+
+impl<T: Send + Sync> TokioRwLockTrace<T> {
+    /// `pred` 가 참인 동안 기다렸다가, 거짓이 된 값을 쥔 읽기 가드를 돌려준다.
+    ///
+    /// 읽기 락을 쥔 채 `pred` 를 확인하고, 참이면 락을 놓고 다음 쓰기 해제까지 잠든다. 깨어나면
+    /// 다시 확인하므로 거짓 깨어남에도 안전하다. 잠들 때와 깨어날 때 `Waiting`/`Woken` 이벤트가
+    /// 남는다.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let queue = Arc::new(TokioRwLockTrace::new(Vec::<u32>::new()));
+    /// let producer = tokio::spawn({
+    ///     let queue = queue.clone();
+    ///     async move { queue.write().await.push(7) }
+    /// });
+    /// let items = queue.wait_while(|items| items.is_empty()).await;
+    /// assert_eq!(*items, [7]);
+    /// # producer.await.unwrap();
+    /// # }
+    /// ```
+    pub async fn wait_while(&self, pred: impl Fn(&T) -> bool) -> LoggingRwLockReadGuard<'_, T> {
+        loop {
+            // 읽기 락을 놓기 전에 등록해야 그 사이의 쓰기를 놓치지 않는다.
+            let notified = self.entry.written.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let guard = self.read().await;
+                if !pred(&guard) {
+                    return guard;
+                }
+            }
+            log_call_info!(&self.config, EventKind::Waiting, AccessKind::Read);
+            notified.await;
+            log_call_info!(&self.config, EventKind::Woken, AccessKind::Read);
+        }
+    }
+
+    /// 진행 중이거나 기다리는 쓰기가 끝나기를 기다렸다가 읽기 가드를 돌려준다. 돌려받은 가드는
+    /// 그 쓰기가 반영된 값을 본다.
+    ///
+    /// 쓰기 락을 누가 쥐고 있지도, 기다리고 있지도 않으면 기다리지 않고 바로 읽기 락을 잡는다.
+    /// 기다렸다면 `Waiting`/`Woken` 이벤트가 남고, `Woken` 의 `waited` 에 기다린 시간이 담긴다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let version = TokioRwLockTrace::new(1);
+    /// // 쓰기가 없으면 바로 읽는다.
+    /// assert_eq!(*version.read_after_next_write().await, 1);
+    /// # }
+    /// ```
+    pub async fn read_after_next_write(&self) -> LoggingRwLockReadGuard<'_, T> {
+        let notified = self.entry.written.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        // tokio 의 `RwLock` 은 공정하므로 writer 가 쥐고 있거나 기다리는 동안 `try_read` 가 실패한다.
+        let pending = self.entry.stats.writer_waited().is_some() || self.inner.try_read().is_err();
+        if pending {
+            log_call_info!(&self.config, EventKind::Waiting, AccessKind::Read);
+            let started = self.config.timed().then(|| self.config.now());
+            notified.await;
+            log_call_info!(
+                &self.config,
+                EventKind::Woken,
+                AccessKind::Read,
+                waited: started.map(|started| self.config.now().saturating_duration_since(started)),
+            );
+        }
+        self.read().await
+    }
+
+    /// 마지막 쓰기가 `max_age` 안이면 바로 읽기 가드를 돌려주고, 아니면 쓰기 락을 잡고
+    /// `refresh` 로 값을 새로 채운 뒤 그 가드를 읽기로 바꿔 돌려준다.
+    ///
+    /// 쓰기 락을 잡은 뒤 다시 확인하므로, 여러 태스크가 함께 오래된 값을 보더라도 `refresh` 는 한
+    /// 번만 돈다. 쓰기가 한 번도 없었던 락(생성 때의 값만 있는 락)은 오래된 것으로 본다. 빠른 경로는
+    /// 보통의 읽기 이벤트를, 갱신 경로는 쓰기 획득과 읽기로의 전이 이벤트를 남긴다.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let rates = TokioRwLockTrace::new(0.0);
+    /// let rate = rates
+    ///     .read_fresh(Duration::from_secs(60), || async { 1.25 })
+    ///     .await;
+    /// assert_eq!(*rate, 1.25);
+    /// # }
+    /// ```
+    pub async fn read_fresh<F, Fut>(
+        &self,
+        max_age: Duration,
+        refresh: F,
+    ) -> LoggingRwLockReadGuard<'_, T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let fresh = || {
+            self.entry
+                .stats
+                .since_last_write()
+                .is_some_and(|age| age <= max_age)
+        };
+        {
+            let guard = self.read().await;
+            if fresh() {
+                return guard;
+            }
+        }
+        let mut guard = self.write().await;
+        if !fresh() {
+            *guard = refresh().await;
+        }
+        guard.downgrade()
+    }
+
+    /// 마지막으로 쓰기 가드를 놓은(또는 읽기로 바꾼) 뒤 지난 시간. 쓰기가 없었으면 `None`.
+    pub fn since_last_write(&self) -> Option<Duration> {
+        self.entry.stats.since_last_write()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig, TokioRwLockTrace};
+
+    #[tokio::test]
+    async fn waiter_blocks_until_writer_flips_flag() {
+        let events = CapturingObserver::new();
+        let ready = Arc::new(
+            TokioRwLockTrace::new(false).with_config(LockConfig::new().observer(events.clone())),
+        );
+
+        let waiter = tokio::spawn({
+            let ready = ready.clone();
+            async move { *ready.wait_while(|ready| !ready).await }
+        });
+        while !events.events().iter().any(|e| e.kind == EventKind::Waiting) {
+            tokio::task::yield_now().await;
+        }
+        // 조건과 상관없는 쓰기는 깨우기만 하고 다시 잠들게 한다.
+        drop(ready.write().await);
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!waiter.is_finished());
+
+        *ready.write().await = true;
+        assert!(waiter.await.unwrap());
+
+        let events = events.events();
+        let count = |kind| events.iter().filter(|e| e.kind == kind).count();
+        assert_eq!((count(EventKind::Waiting), count(EventKind::Woken)), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn reader_sees_value_of_pending_write() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(1)
+                .with_config(LockConfig::new().silent().observer(events.clone())),
+        );
+
+        let mut writer = lock.write().await;
+        let reader = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.read_after_next_write().await }
+        });
+        while !events.events().iter().any(|e| e.kind == EventKind::Waiting) {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        *writer = 2;
+        drop(writer);
+        assert_eq!(reader.await.unwrap(), 2);
+
+        let events = events.events();
+        let woken = events.iter().find(|e| e.kind == EventKind::Woken).unwrap();
+        assert!(woken.waited.unwrap() >= std::time::Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn reads_immediately_without_pending_write() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(1)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        let other = lock.read().await;
+        assert_eq!(*lock.read_after_next_write().await, 1);
+        drop(other);
+
+        let events = events.events();
+        assert!(!events.iter().any(|e| e.kind == EventKind::Waiting));
+    }
+
+    #[tokio::test]
+    async fn stale_value_is_refreshed_once() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent()));
+        let refreshes = Arc::new(AtomicUsize::new(0));
+        let max_age = Duration::from_secs(3600);
+        assert_eq!(lock.since_last_write(), None);
+
+        // 아직 쓴 적이 없으므로 오래된 값이다. 함께 들어온 태스크들 중 하나만 갱신한다.
+        let held = lock.read().await;
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (lock, refreshes) = (lock.clone(), refreshes.clone());
+                tokio::spawn(async move {
+                    let value = lock
+                        .read_fresh(max_age, || async move {
+                            refreshes.fetch_add(1, Ordering::SeqCst);
+                            tokio::task::yield_now().await;
+                            42
+                        })
+                        .await;
+                    *value
+                })
+            })
+            .collect();
+        tokio::task::yield_now().await;
+        drop(held);
+        for reader in readers {
+            assert_eq!(reader.await.unwrap(), 42);
+        }
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+        assert!(lock.since_last_write().is_some_and(|age| age <= max_age));
+
+        // 방금 썼으므로 갱신하지 않는다.
+        let value = lock
+            .read_fresh(max_age, || async { unreachable!("value is fresh") })
+            .await;
+        assert_eq!(*value, 42);
+        drop(value);
+        assert_eq!(lock.stats().writes, 1);
+
+        // `max_age` 가 0 이면 늘 오래된 값이다.
+        assert_eq!(*lock.read_fresh(Duration::ZERO, || async { 7 }).await, 7);
+    }
+}
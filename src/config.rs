@@ -0,0 +1,547 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::clock::Clock;
+use crate::event::{AccessKind, LockObserver, OutputFormat};
+
+pub(crate) type ReleaseHook =
+    Arc<dyn Fn(AccessKind) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// 락 단위 설정.
+///
+/// ```
+/// use tracing_lock::{LockConfig, TokioRwLockTrace};
+///
+/// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("counter"));
+/// assert_eq!(lock.name(), Some("counter"));
+/// ```
+#[derive(Clone, Default)]
+pub struct LockConfig {
+    pub(crate) name: Option<String>,
+    pub(crate) observer: Option<Arc<dyn LockObserver>>,
+    pub(crate) reader_blocking_grace: Option<Duration>,
+    // `escalate_writer_starvation`: writer 가 이만큼 기다리면 `Starvation` 을 남긴다.
+    pub(crate) starvation_escalation: Option<Duration>,
+    pub(crate) min_logged_hold: Option<Duration>,
+    pub(crate) silent: bool,
+    pub(crate) format: OutputFormat,
+    // `with_timing(false)`: 시계를 읽지 않고 획득 이벤트만 남긴다.
+    pub(crate) untimed: bool,
+    pub(crate) clock: Option<Arc<dyn Clock>>,
+    // `write_backoff` 의 (첫 지연, 최대 지연)
+    pub(crate) backoff: Option<(Duration, Duration)>,
+    // `release_async` 가 가드를 놓기 전에 차례로 기다린다.
+    pub(crate) release_hooks: Vec<ReleaseHook>,
+    // `with_strict_fifo`: 모든 획득이 지나가는 차례 대기열
+    pub(crate) fifo: Option<Arc<crate::future::Fifo>>,
+    // `with_group` 으로 붙인 논리적 묶음 이름
+    pub(crate) group: Option<String>,
+    // `warn_contention_ratio` 의 문턱
+    pub(crate) contention_threshold: Option<f64>,
+    // `quiet_peeks`: `peek` 이 이벤트를 남기지 않는다.
+    pub(crate) quiet_peeks: bool,
+    // `record_stack_depth`: 획득 지점의 `enter_frame` 깊이를 남긴다.
+    pub(crate) stack_depth: bool,
+    // `tag_early_exits`: 끝을 표시하지 않은 드롭을 조기 종료로 본다.
+    pub(crate) tag_early_exits: bool,
+    // `rate_limit`/`no_rate_limit`: 출력의 초당 한도
+    pub(crate) rate_limit: crate::rate_limit::RateLimit,
+    // `sample_stats`: 통계 갱신을 n 번에 한 번만 한다.
+    pub(crate) stats_sample: Option<u32>,
+    // `track_sites`: 이름과 호출 위치별 통계를 모은다.
+    pub(crate) track_sites: bool,
+    // `warn_if_wait_exceeds`/`warn_if_held_exceeds`: 감시 스레드가 그 자리에서 경고하는 문턱
+    pub(crate) wait_watchdog: Option<Duration>,
+    pub(crate) held_watchdog: Option<Duration>,
+    // `on_watchdog`: 감시 스레드의 경고를 따로 받는 콜백
+    pub(crate) watchdog_observer: Option<Arc<dyn LockObserver>>,
+    #[cfg(feature = "chaos")]
+    pub(crate) chaos: Option<Arc<crate::chaos::Chaos>>,
+    // 빌더로 직접 정한 필드(`field` 의 비트). `with_config` 는 이 필드만 락의 설정에 덮어쓴다.
+    pub(crate) explicit: u32,
+}
+
+// `LockConfig::explicit` 의 비트
+mod field {
+    pub(super) const NAME: u32 = 1 << 0;
+    pub(super) const OBSERVER: u32 = 1 << 1;
+    pub(super) const WATCHDOG_OBSERVER: u32 = 1 << 2;
+    pub(super) const SILENT: u32 = 1 << 3;
+    pub(super) const FORMAT: u32 = 1 << 4;
+    pub(super) const CLOCK: u32 = 1 << 5;
+    pub(super) const READER_BLOCKING_GRACE: u32 = 1 << 6;
+    pub(super) const STARVATION_ESCALATION: u32 = 1 << 7;
+    pub(super) const WAIT_WATCHDOG: u32 = 1 << 8;
+    pub(super) const HELD_WATCHDOG: u32 = 1 << 9;
+    pub(super) const RELEASE_HOOKS: u32 = 1 << 10;
+    pub(super) const CONTENTION_THRESHOLD: u32 = 1 << 11;
+    pub(super) const QUIET_PEEKS: u32 = 1 << 12;
+    pub(super) const STACK_DEPTH: u32 = 1 << 13;
+    pub(super) const TAG_EARLY_EXITS: u32 = 1 << 14;
+    pub(super) const STATS_SAMPLE: u32 = 1 << 15;
+    pub(super) const TRACK_SITES: u32 = 1 << 16;
+    pub(super) const RATE_LIMIT: u32 = 1 << 17;
+    pub(super) const BACKOFF: u32 = 1 << 18;
+}
+
+static HAS_DEFAULT_CONFIG: AtomicBool = AtomicBool::new(false);
+static DEFAULT_CONFIG: RwLock<Option<LockConfig>> = RwLock::new(None);
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// 이후에 만드는 락과 `LockConfig::inherited()` 가 물려받을 프로세스 전역 기본 설정.
+///
+/// 락은 만들어질 때 그 시점의 기본값을 복사해 가지므로, 나중에 기본값을 바꿔도 이미 만든 락은
+/// 그대로다. `with_config` 는 넘긴 설정에서 빌더로 직접 정한 값만 덮어쓰므로, 아래처럼 이름만
+/// 붙여도 나머지는 기본값을 그대로 물려받는다. 물려받은 관찰자를 떼려면 `without_observers` 를 쓴다.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+/// use tracing_lock::{LockConfig, LockEvent, TokioRwLockTrace};
+///
+/// static SEEN: AtomicUsize = AtomicUsize::new(0);
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// tracing_lock::set_default_config(
+///     LockConfig::new()
+///         .warn_reader_blocking_writer(Duration::from_millis(100))
+///         .observer(|_: &LockEvent| {
+///             SEEN.fetch_add(1, Ordering::Relaxed);
+///         }),
+/// );
+/// // 이름만 붙여도 위 관찰자를 물려받는다.
+/// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs"));
+/// *lock.write().await += 1;
+/// assert!(SEEN.load(Ordering::Relaxed) > 0);
+/// # }
+/// ```
+pub fn set_default_config(config: LockConfig) {
+    *DEFAULT_CONFIG
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(config);
+    HAS_DEFAULT_CONFIG.store(true, Ordering::Release);
+}
+
+/// 프로세스 전체의 계측을 켜고 끈다. 기본값은 켜짐.
+///
+/// 꺼져 있는 동안에는 어떤 락도 이벤트를 만들지 않고 시계를 읽지 않는다. 이미 쥔 가드의 해제도
+/// 마찬가지다. 획득 수 같은 통계 카운터와 따로 켠 기록(`deadlock`, `timeline`, `track_sites`)은
+/// 그대로 움직인다. 재배포 없이 살아 있는 프로세스에서 켰다 껐다 할 때 쓰며, 계측 비용을 아예
+/// 없애려면 `trace` 기능을 끈다.
+///
+/// ```
+/// use tracing_lock::{LockConfig, LockEvent, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// tracing_lock::set_enabled(false);
+/// let lock = TokioRwLockTrace::new(0)
+///     .with_config(LockConfig::new().observer(|_: &LockEvent| unreachable!()));
+/// *lock.write().await += 1;
+/// assert_eq!(lock.stats().writes, 1);
+/// # }
+/// ```
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub(crate) fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+impl LockConfig {
+    /// 아무것도 켜지 않은 설정. `LockConfig::default()` 와 같고 전역 기본 설정과 상관없다.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `set_default_config` 로 정한 기본 설정의 복사본. 기본값이 없으면 `LockConfig::new()`.
+    ///
+    /// 락은 만들어질 때 이 설정으로 시작한다. 물려받은 관찰자를 떼려면 `without_observers` 를 쓴다.
+    pub fn inherited() -> Self {
+        if !HAS_DEFAULT_CONFIG.load(Ordering::Acquire) {
+            return Self::default();
+        }
+        DEFAULT_CONFIG
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+            .unwrap_or_default()
+    }
+
+    /// 로그에 표시될 락 이름
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self.explicit |= field::NAME;
+        self
+    }
+
+    /// 획득/해제 이벤트를 함께 받을 관찰자
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, LockEvent};
+    ///
+    /// let config = LockConfig::new().observer(|event: &LockEvent| eprintln!("{event}"));
+    /// # let _ = config;
+    /// ```
+    pub fn observer(mut self, observer: impl LockObserver + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self.explicit |= field::OBSERVER;
+        self
+    }
+
+    /// `observer` 와 `on_watchdog` 로 단 관찰자를 모두 뗀다. `inherited` 로 물려받은 것을 지울 때 쓴다.
+    pub fn without_observers(mut self) -> Self {
+        self.observer = None;
+        self.watchdog_observer = None;
+        self.explicit |= field::OBSERVER | field::WATCHDOG_OBSERVER;
+        self
+    }
+
+    /// 이벤트를 출력 백엔드(`tracing`, `log`, stdout)로 내보내지 않는다. 관찰자는 계속 받는다.
+    ///
+    /// 관찰자도 없으면 획득/해제 때 이벤트를 아예 만들지 않으므로 메시지 할당이 없다. 통계는
+    /// 그대로 집계된다.
+    pub fn silent(mut self) -> Self {
+        self.silent = true;
+        self.explicit |= field::SILENT;
+        self
+    }
+
+    /// stdout(또는 `log` 백엔드)으로 내보낼 이벤트의 형식. 기본값은 `OutputFormat::Plain`.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, OutputFormat, TokioRwLockTrace};
+    ///
+    /// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().format(OutputFormat::Pretty));
+    /// # let _ = lock;
+    /// ```
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self.explicit |= field::FORMAT;
+        self
+    }
+
+    /// 점유 시간을 잴 시계. 기본값은 `Instant::now`.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self.explicit |= field::CLOCK;
+        self
+    }
+
+    /// `self` 에서 빌더로 직접 정한 값만 `base` 위에 덮어쓴 설정. `on_release_async` 로 더한 훅은
+    /// `base` 의 훅 뒤에 붙는다.
+    ///
+    /// `TokioRwLockTrace` 의 빌더(`with_group`, `with_timing` 등)로만 정하는 값은 `LockConfig` 의
+    /// 빌더가 없으므로 언제나 `base` 의 것이 남는다.
+    pub(crate) fn overlay(self, base: &LockConfig) -> LockConfig {
+        let mut merged = base.clone();
+        macro_rules! take {
+            ($($bit:ident => $field:ident),* $(,)?) => {
+                $(
+                    if self.explicit & field::$bit != 0 {
+                        merged.$field = self.$field;
+                    }
+                )*
+            };
+        }
+        take!(
+            NAME => name,
+            OBSERVER => observer,
+            WATCHDOG_OBSERVER => watchdog_observer,
+            SILENT => silent,
+            FORMAT => format,
+            CLOCK => clock,
+            READER_BLOCKING_GRACE => reader_blocking_grace,
+            STARVATION_ESCALATION => starvation_escalation,
+            WAIT_WATCHDOG => wait_watchdog,
+            HELD_WATCHDOG => held_watchdog,
+            CONTENTION_THRESHOLD => contention_threshold,
+            QUIET_PEEKS => quiet_peeks,
+            STACK_DEPTH => stack_depth,
+            TAG_EARLY_EXITS => tag_early_exits,
+            STATS_SAMPLE => stats_sample,
+            TRACK_SITES => track_sites,
+            RATE_LIMIT => rate_limit,
+            BACKOFF => backoff,
+        );
+        merged.release_hooks.extend(self.release_hooks);
+        merged.explicit |= self.explicit;
+        merged
+    }
+
+    /// 점유/대기 시간을 재는지. `with_timing(false)` 이거나 `set_enabled(false)` 면 재지 않는다.
+    pub(crate) fn timed(&self) -> bool {
+        !self.untimed && enabled()
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => Instant::now(),
+        }
+    }
+
+    /// writer 가 기다리는 동안 `grace` 보다 오래 읽기 락을 붙잡고 있던 reader 가
+    /// 해제될 때 경고를 남긴다.
+    pub fn warn_reader_blocking_writer(mut self, grace: Duration) -> Self {
+        self.reader_blocking_grace = Some(grace);
+        self.explicit |= field::READER_BLOCKING_GRACE;
+        self
+    }
+
+    /// writer 가 `after` 넘게 기다리면 그 자리에서 `Starvation` 이벤트(`log` 백엔드에서는 `error`)를
+    /// 남긴다. 메시지에는 그 순간 읽기 락을 쥐고 있던 `read()` 호출 위치들이 담긴다.
+    ///
+    /// `warn_reader_blocking_writer` 는 reader 가 놓을 때에야 남지만, 이쪽은 reader 가 끝내 놓지
+    /// 않아도 writer 쪽 타이머로 남는다. 기다리는 writer 하나당 한 번이다. 보유자를 기록해야 하므로
+    /// 이 설정의 가드는 늘 보유자 목록에 오르며, tokio 런타임의 시간 드라이버가 있어야 한다.
+    pub fn escalate_writer_starvation(mut self, after: Duration) -> Self {
+        self.starvation_escalation = Some(after);
+        self.explicit |= field::STARVATION_ESCALATION;
+        self
+    }
+
+    /// 획득을 `threshold` 넘게 기다리면, 얻을 때까지 기다리지 않고 그 자리에서 경고(`Warning`)를
+    /// 남긴다. 이벤트의 `location` 은 `read()`/`write()` 호출 위치, `thread` 는 기다리는 쪽,
+    /// `waited` 는 그때까지 기다린 시간이다. 기다리는 획득 하나당 한 번이다.
+    ///
+    /// 프로세스에 하나 있는 감시 스레드가 문턱에 맞춰 깨어나 살펴보므로 런타임이 멈춰 있어도
+    /// 알린다. 기다려야 했던 획득만 대기자 목록에 잠시 오른다. `warn_reader_blocking_writer` 나
+    /// `escalate_writer_starvation` 과 달리 읽기/쓰기를 가리지 않는다.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// let lock = TokioRwLockTrace::new(0).with_config(
+    ///     LockConfig::new()
+    ///         .warn_if_wait_exceeds(Duration::from_millis(100))
+    ///         .warn_if_held_exceeds(Duration::from_secs(1)),
+    /// );
+    /// # drop(lock);
+    /// ```
+    pub fn warn_if_wait_exceeds(mut self, threshold: Duration) -> Self {
+        self.wait_watchdog = Some(threshold);
+        self.explicit |= field::WAIT_WATCHDOG;
+        self
+    }
+
+    /// 가드를 `threshold` 넘게 쥐고 있으면, 드롭되기 전에 그 자리에서 경고(`Warning`)를 남긴다.
+    /// `acquired_at` 과 `thread` 는 가드를 얻은 호출 위치와 스레드, `held` 는 그때까지 쥔 시간이다.
+    ///
+    /// `warn_if_wait_exceeds` 와 같은 감시 스레드가 보며 가드 하나당 한 번이다. 이 설정의 가드는 늘
+    /// 보유자 목록에 오른다.
+    pub fn warn_if_held_exceeds(mut self, threshold: Duration) -> Self {
+        self.held_watchdog = Some(threshold);
+        self.explicit |= field::HELD_WATCHDOG;
+        self
+    }
+
+    /// `warn_if_wait_exceeds`/`warn_if_held_exceeds` 의 경고를 `observer` 와 따로 받는다.
+    ///
+    /// 알림을 보내거나 덤프를 남기는 데 쓴다. `silent` 이어도 불린다. 감시 스레드에서 불리므로 오래
+    /// 막지 말 것.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tracing_lock::{LockConfig, LockEvent};
+    ///
+    /// let config = LockConfig::new()
+    ///     .warn_if_held_exceeds(Duration::from_secs(1))
+    ///     .on_watchdog(|event: &LockEvent| eprintln!("lock watchdog: {event}"));
+    /// # let _ = config;
+    /// ```
+    pub fn on_watchdog(mut self, callback: impl LockObserver + 'static) -> Self {
+        self.watchdog_observer = Some(Arc::new(callback));
+        self.explicit |= field::WATCHDOG_OBSERVER;
+        self
+    }
+
+    /// 가드를 `release_async` 로 놓을 때 락을 풀기 전에 기다릴 비동기 정리 작업을 더한다.
+    ///
+    /// 등록한 순서대로 하나씩 기다린다. `Drop` 은 동기이므로 그냥 드롭한 가드는 이 훅을 건너뛴다.
+    ///
+    /// ```
+    /// use tokio::sync::mpsc;
+    /// use tracing_lock::{AccessKind, LockConfig};
+    ///
+    /// let (tx, _rx) = mpsc::channel(8);
+    /// let config = LockConfig::new().on_release_async(move |access: AccessKind| {
+    ///     let tx = tx.clone();
+    ///     async move {
+    ///         let _ = tx.send(access).await;
+    ///     }
+    /// });
+    /// # let _ = config;
+    /// ```
+    pub fn on_release_async<F, Fut>(mut self, hook: F) -> Self
+    where
+        F: Fn(AccessKind) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.release_hooks
+            .push(Arc::new(move |access| Box::pin(hook(access))));
+        self.explicit |= field::RELEASE_HOOKS;
+        self
+    }
+
+    /// 최근 획득 중 기다려야 했던 비율(`contention_ratio`)이 `threshold` 를 넘으면 경고를 남긴다.
+    ///
+    /// 넘은 상태가 이어지는 동안은 한 번만 남기고, 내려갔다가 다시 넘으면 또 남긴다. 최근 창에
+    /// 획득이 10번 미만이면 판단하지 않는다.
+    pub fn warn_contention_ratio(mut self, threshold: f64) -> Self {
+        self.contention_threshold = Some(threshold);
+        self.explicit |= field::CONTENTION_THRESHOLD;
+        self
+    }
+
+    /// `TokioRwLockTrace::peek` 이 `Peek` 이벤트도 남기지 않게 한다. 통계는 그대로 센다.
+    pub fn quiet_peeks(mut self) -> Self {
+        self.quiet_peeks = true;
+        self.explicit |= field::QUIET_PEEKS;
+        self
+    }
+
+    /// 획득 이벤트의 `stack_depth` 에 락을 얻은 지점의 호출 깊이를 남긴다.
+    ///
+    /// 예상보다 깊은 호출 스택에서 잡히는 락을 찾는 진단용이다. 깊이는 `enter_frame` 으로 표시한
+    /// 함수만 세는 스레드별 카운터라 획득마다 드는 비용은 스레드 로컬 읽기 하나다.
+    pub fn record_stack_depth(mut self) -> Self {
+        self.stack_depth = true;
+        self.explicit |= field::STACK_DEPTH;
+        self
+    }
+
+    /// 오류 경로에서 버려진 임계 구역을 해제 이벤트의 `reason` 으로 구분한다.
+    ///
+    /// 가드에서 `complete()` 를 부르지 않고 드롭되면 `ReleaseReason::EarlyExit`, 패닉으로 풀리며
+    /// 드롭되면 `ReleaseReason::Unwound` 가 된다. `release()` 는 그대로 `Explicit` 이다.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(Vec::new()).with_config(LockConfig::new().tag_early_exits());
+    /// let mut items = lock.write().await;
+    /// items.push(1);
+    /// items.complete(); // 여기까지 왔으면 정상 종료
+    /// # }
+    /// ```
+    pub fn tag_early_exits(mut self) -> Self {
+        self.tag_early_exits = true;
+        self.explicit |= field::TAG_EARLY_EXITS;
+        self
+    }
+
+    /// 누적 통계(획득 수, 점유 시간)를 평균 `every` 번에 한 번만 갱신하고 `every` 배로 센다.
+    ///
+    /// 초당 수백만 번 잡히는 락에서 통계 카운터의 캐시 라인 경합이 병목으로 측정됐을 때만 쓴다.
+    /// 어느 획득을 셀지는 스레드마다 의사 난수로 정하므로 값은 추정치가 되며, 스냅샷의
+    /// `sample_every` 로 표시된다. 현재 reader/대기자 수 같은 게이지와 이벤트 로그는 그대로
+    /// 정확하다. `every` 가 0 이면 패닉한다.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().sample_stats(16));
+    /// assert_eq!(lock.stats().sample_every, Some(16));
+    /// ```
+    pub fn sample_stats(mut self, every: u32) -> Self {
+        assert!(every > 0, "stats sample rate must be at least 1");
+        self.stats_sample = (every > 1).then_some(every);
+        self.explicit |= field::STATS_SAMPLE;
+        self
+    }
+
+    /// 락 이름과 `read()`/`write()` 호출 위치마다 획득 수, 대기/점유 시간 히스토그램, 지금 쥔 가드
+    /// 수를 모아 `sites::snapshot()` 으로 보여 준다.
+    ///
+    /// 같은 이름의 락들은 호출 위치별로 합쳐지므로, 어느 락이 아니라 코드의 어느 지점이 붐비는지를
+    /// 볼 수 있다. 획득마다 원자 연산 몇 개와 락별 위치 목록 조회가 더 들며, 처음 보는 위치에서만
+    /// 전역 목록을 잠근다. `prometheus`/`metrics` 기능이 켜져 있으면 같은 값을 `site` 레이블과 함께
+    /// 내보낸다.
+    pub fn track_sites(mut self) -> Self {
+        self.track_sites = true;
+        self.explicit |= field::TRACK_SITES;
+        self
+    }
+
+    /// 이 설정으로 출력(stdout, `log`, `tracing`)에 내보내는 이벤트를 초당 `events_per_sec` 개로
+    /// 제한한다. 기본값은 초당 10,000 개.
+    ///
+    /// 한도를 넘는 이벤트는 출력하지 않고 "suppressed N events" 경고 하나로 요약한다. 요약은 한도가
+    /// 다시 찬 뒤 첫 이벤트 앞에, 그런 이벤트가 없으면 1초 안에 나가며 관찰자도 받는다. 관찰자와
+    /// 통계는 버린 이벤트까지 모두 받는다. 이 설정을 복제한 락들은 한도를 함께 쓴다.
+    /// `events_per_sec` 가 0 이면 패닉한다.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().rate_limit(1_000));
+    /// # drop(lock);
+    /// ```
+    pub fn rate_limit(mut self, events_per_sec: u32) -> Self {
+        assert!(events_per_sec > 0, "event rate limit must be at least 1");
+        self.rate_limit.0 = Some(Arc::new(crate::rate_limit::RateLimiter::new(
+            events_per_sec,
+        )));
+        self.explicit |= field::RATE_LIMIT;
+        self
+    }
+
+    /// 출력 한도를 없앤다. 모든 이벤트를 출력으로 내보낸다.
+    pub fn no_rate_limit(mut self) -> Self {
+        self.rate_limit.0 = None;
+        self.explicit |= field::RATE_LIMIT;
+        self
+    }
+
+    /// `write_backoff` 가 실패한 시도 사이에 쉬는 시간. `base` 에서 시작해 두 배씩 늘리되
+    /// `cap` 을 넘지 않는다. 기본값은 1ms 에서 100ms 까지.
+    pub fn backoff(mut self, base: Duration, cap: Duration) -> Self {
+        self.backoff = Some((base, cap));
+        self.explicit |= field::BACKOFF;
+        self
+    }
+}
+
+impl fmt::Debug for LockConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockConfig")
+            .field("name", &self.name)
+            .field("observer", &self.observer.is_some())
+            .field("reader_blocking_grace", &self.reader_blocking_grace)
+            .field("starvation_escalation", &self.starvation_escalation)
+            .field("min_logged_hold", &self.min_logged_hold)
+            .field("silent", &self.silent)
+            .field("format", &self.format)
+            .field("untimed", &self.untimed)
+            .field("clock", &self.clock.is_some())
+            .field("backoff", &self.backoff)
+            .field("release_hooks", &self.release_hooks.len())
+            .field("strict_fifo", &self.fifo.is_some())
+            .field("group", &self.group)
+            .field("contention_threshold", &self.contention_threshold)
+            .field("quiet_peeks", &self.quiet_peeks)
+            .field("stack_depth", &self.stack_depth)
+            .field("tag_early_exits", &self.tag_early_exits)
+            .field("rate_limit", &self.rate_limit)
+            .field("stats_sample", &self.stats_sample)
+            .field("track_sites", &self.track_sites)
+            .field("wait_watchdog", &self.wait_watchdog)
+            .field("held_watchdog", &self.held_watchdog)
+            .field("watchdog_observer", &self.watchdog_observer.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,84 @@
+//! `tokio-console` 의 리소스 뷰에 락을 보여 주는 `tracing` 계측. (`tokio-console` 기능)
+//!
+//! tokio 가 `tokio_unstable` 에서 자체 `RwLock` 에 붙이는 것과 같은 span 이름, target, 필드를
+//! 쓴다. 락마다 `runtime.resource` span 이 하나 있고, 획득/해제는 그 span 을 부모로 하는
+//! `runtime::resource::state_update` 이벤트로 `write_locked`/`current_readers` 를 갱신한다.
+
+use std::panic::Location;
+
+use tracing::Span;
+
+use crate::event::AccessKind;
+use crate::registry::Entry;
+
+/// 락을 만든 위치로 리소스 span 을 연다. 구독자가 없으면 비활성 span 이다.
+pub(crate) fn resource_span(location: &'static Location<'static>) -> Span {
+    let span = tracing::trace_span!(
+        target: "runtime::resource",
+        parent: None,
+        "runtime.resource",
+        concrete_type = "TokioRwLockTrace",
+        kind = "Sync",
+        loc.file = location.file(),
+        loc.line = location.line(),
+        loc.col = location.column(),
+        lock.name = tracing::field::Empty,
+    );
+    tracing::trace!(
+        target: "runtime::resource::state_update",
+        parent: &span,
+        write_locked = false,
+    );
+    tracing::trace!(
+        target: "runtime::resource::state_update",
+        parent: &span,
+        current_readers = 0,
+    );
+    span
+}
+
+pub(crate) fn set_name(entry: &Entry, name: Option<&str>) {
+    if let Some(name) = name {
+        entry.resource.record("lock.name", name);
+    }
+}
+
+pub(crate) fn acquired(entry: &Entry, access: AccessKind) {
+    match access {
+        AccessKind::Read => tracing::trace!(
+            target: "runtime::resource::state_update",
+            parent: &entry.resource,
+            current_readers = 1,
+            current_readers.op = "add",
+        ),
+        AccessKind::Write => tracing::trace!(
+            target: "runtime::resource::state_update",
+            parent: &entry.resource,
+            write_locked = true,
+            write_locked.op = "override",
+        ),
+    }
+}
+
+pub(crate) fn released(entry: &Entry, access: AccessKind) {
+    match access {
+        AccessKind::Read => tracing::trace!(
+            target: "runtime::resource::state_update",
+            parent: &entry.resource,
+            current_readers = 1,
+            current_readers.op = "sub",
+        ),
+        AccessKind::Write => tracing::trace!(
+            target: "runtime::resource::state_update",
+            parent: &entry.resource,
+            write_locked = false,
+            write_locked.op = "override",
+        ),
+    }
+}
+
+/// 쓰기 가드가 읽기 가드로 바뀜: writer 하나가 빠지고 reader 하나가 는다.
+pub(crate) fn downgraded(entry: &Entry) {
+    released(entry, AccessKind::Write);
+    acquired(entry, AccessKind::Read);
+}
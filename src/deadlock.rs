@@ -0,0 +1,353 @@
+//! 여러 락에 걸친 교착을 찾는 대기 그래프. (`set_enabled`)
+//!
+//! 켜져 있는 동안 태스크(tokio 밖에서는 스레드)마다 쥔 가드와 기다리는 획득을 전역 그래프에 올린다.
+//! 기다리는 쪽에서 막고 있는 쪽으로 간선을 긋고, 새로 기다리기 시작할 때 그 대기에서 출발해 자기
+//! 자신으로 돌아오는 순환을 찾는다. 순환은 마지막 대기가 더해질 때만 닫히므로 검사도 그때 한 번이다.
+//!
+//! 간선은 다음 두 경우에 생긴다.
+//!
+//! - 기다리는 획득과 충돌하는 가드를 다른 태스크(또는 자기 자신)가 쥐고 있다. 읽기끼리는 충돌하지
+//!   않는다.
+//! - 읽기를 기다리는데 같은 락의 쓰기가 먼저 줄을 서 있다. tokio `RwLock` 은 먼저 선 writer 가 얻기
+//!   전에는 새 reader 를 들이지 않으므로, 읽기 가드를 쥔 채로 같은 락을 다시 읽으면 교착이 된다.
+//!
+//! 찾은 순환은 `on_deadlock` 콜백과, 마지막으로 기다리기 시작한 락의 설정으로 `Deadlock` 이벤트에
+//! 담긴다. `timeout` 이나 `select!` 로 감싸 결국 포기할 대기도 순환을 이루면 알린다. 세마포어는
+//! 허가 수를 알 수 없으므로 간선을 만들지 않는다.
+//!
+//! ```
+//! use tracing_lock::{deadlock, TracedMutex};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! deadlock::set_enabled(true);
+//! deadlock::on_deadlock(|report| eprintln!("{report}"));
+//!
+//! let mutex = TracedMutex::new(0);
+//! let _guard = mutex.lock().await;
+//! // 같은 태스크가 쥔 락을 다시 기다리므로 보고된다.
+//! let relock = tokio::time::timeout(std::time::Duration::from_millis(1), mutex.lock()).await;
+//! assert!(relock.is_err());
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    num::NonZeroU64,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
+};
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::task::TaskKey;
+
+type Callback = Arc<dyn Fn(&DeadlockReport) + Send + Sync>;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static GRAPH: Mutex<Option<Graph>> = Mutex::new(None);
+static CALLBACK: RwLock<Option<Callback>> = RwLock::new(None);
+// 가드와 대기를 구분하는 일련번호. 같은 락의 대기끼리는 먼저 선 순서로도 쓴다.
+static NEXT_TOKEN: AtomicU64 = AtomicU64::new(1);
+
+/// 교착 검사를 켜고 끈다. 기본값은 꺼짐.
+///
+/// 켜져 있는 동안 획득, 대기, 해제마다 전역 뮤텍스 하나를 거치므로 운영 중 상시로 켜 두기보다는
+/// 멈춤을 재현할 때 쓴다. 켜져 있는 동안 얻은 가드와 시작한 대기만 그래프에 오른다.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 순환을 찾을 때마다 부를 콜백을 정한다. 이전 콜백은 바뀐다.
+///
+/// 콜백은 순환을 닫은 대기를 시작한 태스크에서, 그래프를 놓은 뒤에 불린다.
+pub fn on_deadlock(callback: impl Fn(&DeadlockReport) + Send + Sync + 'static) {
+    *CALLBACK.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(callback));
+}
+
+/// 찾은 순환 하나. 간선은 순환을 닫은 대기부터 차례로 이어진다.
+#[derive(Debug, Clone)]
+pub struct DeadlockReport {
+    pub edges: Vec<WaitEdge>,
+}
+
+/// 대기 그래프의 간선 하나: `waiter` 가 `lock` 을 기다리는데 `holder` 가 막고 있다.
+#[derive(Debug, Clone)]
+pub struct WaitEdge {
+    /// 기다리는 태스크. `task 7` 또는 `ThreadId(3)` 꼴이다.
+    pub waiter: String,
+    pub lock_id: u64,
+    pub lock: Option<String>,
+    pub access: AccessKind,
+    /// 기다리기 시작한 호출 위치
+    pub waiting_at: &'static Location<'static>,
+    /// 막고 있는 태스크
+    pub holder: String,
+    pub held_access: AccessKind,
+    /// 막는 쪽이 가드를 얻은 호출 위치. `queued` 면 막는 쪽이 기다리기 시작한 위치다.
+    pub held_at: &'static Location<'static>,
+    /// 막는 쪽이 가드를 쥔 것이 아니라 먼저 줄을 선 쓰기 대기다.
+    pub queued: bool,
+}
+
+impl fmt::Display for DeadlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "deadlock across {} wait(s)", self.edges.len())?;
+        for edge in &self.edges {
+            write!(f, "; {edge}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for WaitEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |access| match access {
+            AccessKind::Read => "read",
+            AccessKind::Write => "write",
+        };
+        write!(f, "{} waits to {} lock", self.waiter, name(self.access))?;
+        match &self.lock {
+            Some(lock) => write!(f, " '{}'", lock)?,
+            None => write!(f, " #{}", self.lock_id)?,
+        }
+        write!(
+            f,
+            " at {}:{}, ",
+            self.waiting_at.file(),
+            self.waiting_at.line()
+        )?;
+        let how = if self.queued { "queued" } else { "held" };
+        write!(
+            f,
+            "{} for {} by {} at {}:{}",
+            how,
+            name(self.held_access),
+            self.holder,
+            self.held_at.file(),
+            self.held_at.line()
+        )
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    // 가드 토큰으로 찾는다. 가드 안의 토큰을 8바이트로 두기 위해서다.
+    holds: HashMap<NonZeroU64, Hold>,
+    waits: HashMap<TaskKey, Vec<Wait>>,
+}
+
+struct Hold {
+    lock_id: u64,
+    key: TaskKey,
+    access: AccessKind,
+    location: &'static Location<'static>,
+}
+
+struct Wait {
+    token: NonZeroU64,
+    lock_id: u64,
+    name: Option<String>,
+    access: AccessKind,
+    location: &'static Location<'static>,
+}
+
+fn graph() -> std::sync::MutexGuard<'static, Option<Graph>> {
+    GRAPH.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn next_token() -> NonZeroU64 {
+    NonZeroU64::new(NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)).expect("token counter overflowed")
+}
+
+fn describe(key: TaskKey) -> String {
+    match key {
+        TaskKey::Task(id) => format!("task {}", id),
+        TaskKey::Thread(id) => format!("{:?}", id),
+    }
+}
+
+/// 그래프에 올라간 가드 하나. 드롭되면 내린다.
+pub(crate) struct HoldToken(NonZeroU64);
+
+/// 검사가 켜져 있으면 현재 태스크가 `lock_id` 의 가드를 쥐었다고 올린다.
+pub(crate) fn held(
+    lock_id: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+) -> Option<HoldToken> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let token = next_token();
+    graph().get_or_insert_with(Graph::default).holds.insert(
+        token,
+        Hold {
+            lock_id,
+            key: TaskKey::current(),
+            access,
+            location,
+        },
+    );
+    Some(HoldToken(token))
+}
+
+impl HoldToken {
+    /// 해제 없이 접근 종류가 바뀐 가드를 고친다. (`downgrade`)
+    pub(crate) fn set_access(&self, access: AccessKind) {
+        let mut graph = graph();
+        if let Some(hold) = graph
+            .as_mut()
+            .and_then(|graph| graph.holds.get_mut(&self.0))
+        {
+            hold.access = access;
+        }
+    }
+}
+
+impl Drop for HoldToken {
+    fn drop(&mut self) {
+        if let Some(graph) = graph().as_mut() {
+            graph.holds.remove(&self.0);
+        }
+    }
+}
+
+/// 그래프에 올라간 대기 하나. 드롭되면 내린다.
+pub(crate) struct WaitToken {
+    key: TaskKey,
+    token: NonZeroU64,
+}
+
+/// 검사가 켜져 있으면 현재 태스크가 `lock_id` 를 기다리기 시작했다고 올리고, 이 대기로 닫힌 순환을
+/// 알린다.
+pub(crate) fn waiting(
+    config: &LockConfig,
+    lock_id: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+) -> Option<WaitToken> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    let key = TaskKey::current();
+    let token = next_token();
+    let report = {
+        let mut graph = graph();
+        let graph = graph.get_or_insert_with(Graph::default);
+        let wait = Wait {
+            token,
+            lock_id,
+            name: config.name.clone(),
+            access,
+            location,
+        };
+        let mut edges = Vec::new();
+        let found = graph.search(key, key, &wait, &mut HashSet::new(), &mut edges);
+        graph.waits.entry(key).or_default().push(wait);
+        found.then_some(DeadlockReport { edges })
+    };
+    if let Some(report) = report {
+        alert(config, access, location, &report);
+    }
+    Some(WaitToken { key, token })
+}
+
+impl Drop for WaitToken {
+    fn drop(&mut self) {
+        let mut graph = graph();
+        let Some(graph) = graph.as_mut() else {
+            return;
+        };
+        if let Some(waits) = graph.waits.get_mut(&self.key) {
+            waits.retain(|wait| wait.token != self.token);
+            if waits.is_empty() {
+                graph.waits.remove(&self.key);
+            }
+        }
+    }
+}
+
+impl Graph {
+    // `waiter` 의 `wait` 에서 출발해 `origin` 으로 돌아오는 길을 찾는다. 찾으면 `edges` 에 그 길이
+    // 남는다.
+    fn search(
+        &self,
+        origin: TaskKey,
+        waiter: TaskKey,
+        wait: &Wait,
+        visited: &mut HashSet<TaskKey>,
+        edges: &mut Vec<WaitEdge>,
+    ) -> bool {
+        let held = self
+            .holds
+            .values()
+            .filter(|hold| hold.lock_id == wait.lock_id)
+            .filter(|hold| wait.access == AccessKind::Write || hold.access == AccessKind::Write)
+            .map(|hold| (hold.key, hold.access, hold.location, false));
+        // 읽기는 먼저 줄을 선 쓰기 뒤에서 기다린다.
+        let queued = self
+            .waits
+            .iter()
+            .filter(|(key, _)| wait.access == AccessKind::Read && **key != waiter)
+            .flat_map(|(key, waits)| waits.iter().map(move |queued| (*key, queued)))
+            .filter(|(_, queued)| {
+                queued.lock_id == wait.lock_id
+                    && queued.access == AccessKind::Write
+                    && queued.token < wait.token
+            })
+            .map(|(key, queued)| (key, queued.access, queued.location, true));
+        for (holder, held_access, held_at, queued) in held.chain(queued) {
+            edges.push(WaitEdge {
+                waiter: describe(waiter),
+                lock_id: wait.lock_id,
+                lock: wait.name.clone(),
+                access: wait.access,
+                waiting_at: wait.location,
+                holder: describe(holder),
+                held_access,
+                held_at,
+                queued,
+            });
+            if holder == origin {
+                return true;
+            }
+            if visited.insert(holder) {
+                let next = self.waits.get(&holder).into_iter().flatten();
+                for next in next {
+                    if self.search(origin, holder, next, visited, edges) {
+                        return true;
+                    }
+                }
+            }
+            edges.pop();
+        }
+        false
+    }
+}
+
+fn alert(
+    config: &LockConfig,
+    access: AccessKind,
+    location: &'static Location<'static>,
+    report: &DeadlockReport,
+) {
+    let callback = CALLBACK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    if let Some(callback) = callback {
+        callback(report);
+    }
+    log_call_info!(
+        config,
+        EventKind::Deadlock,
+        access,
+        location: location,
+        message: Some(report.to_string()),
+    );
+}
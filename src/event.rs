@@ -0,0 +1,623 @@
+use std::{
+    fmt,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::config::LockConfig;
+
+/// 읽기/쓰기 구분
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// 이벤트 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    /// 락을 획득함
+    Acquire,
+    /// 가드가 드롭되어 락을 해제함
+    Release,
+    /// 획득을 기다리던 future 가 드롭됨
+    Cancelled,
+    /// 지연 초기화된 락이 처음 만들어짐
+    Initialized,
+    /// 진단 경고. 내용은 `LockEvent::message` 에 담긴다.
+    Warning,
+    /// `try_write` 가 이미 잡힌 락을 만나 기다리지 않고 포기함
+    Busy,
+    /// `wait_while` 이 조건이 바뀌기를 기다리기 시작함
+    Waiting,
+    /// `wait_while` 이 쓰기 해제로 깨어남
+    Woken,
+    /// 락이 만들어짐. 위치와 시각은 생성 시점이지만, 설정이 정해진 뒤(첫 획득이나 드롭)에 내보낸다.
+    Created,
+    /// 가드가 해제 없이 다른 접근 종류로 바뀜 (예: `downgrade`). 이전 종류는 `previous` 에 담긴다.
+    Transition,
+    /// 락의 마지막 핸들이 드롭됨. 누적 획득 수는 `message` 에 담긴다.
+    Destroyed,
+    /// 락의 사용 규칙을 어김 (예: `OneShotWriteLock` 을 두 번 씀). 내용은 `message` 에 담긴다.
+    Misuse,
+    /// writer 가 `LockConfig::escalate_writer_starvation` 의 시간을 넘게 기다림. `message` 에
+    /// 그때 읽기 락을 쥐고 있던 호출 위치들이 담긴다.
+    Starvation,
+    /// `peek` 으로 잠깐 읽고 놓음. 획득/해제 쌍 대신 이것 하나가 남고 `held` 에 점유 시간이 담긴다.
+    Peek,
+    /// `deadlock::set_enabled` 의 대기 그래프에서 순환을 찾음. 위치는 순환을 닫은 대기이고, 간선마다의
+    /// 호출 위치가 `message` 에 담긴다.
+    Deadlock,
+}
+
+/// 해제 이벤트가 어떻게 일어났는지
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReleaseReason {
+    /// 가드가 스코프를 벗어나거나 `drop` 됨
+    Dropped,
+    /// `release()` 로 명시적으로 해제함
+    Explicit,
+    /// 패닉으로 스택이 풀리면서 드롭됨 (`LockConfig::tag_early_exits`)
+    Unwound,
+    /// `complete()` 로 끝을 표시하기 전에 드롭됨. 대개 `?` 나 `return` 으로 빠져나간 경로다.
+    /// (`LockConfig::tag_early_exits`)
+    EarlyExit,
+}
+
+/// 이벤트를 stdout(또는 `log` 백엔드)으로 내보낼 때의 형식. `LockConfig::format` 으로 고른다.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// `LockEvent` 의 `Display` 그대로 한 줄
+    #[default]
+    Plain,
+    /// 로컬 개발용. 열을 맞추고, stdout 이 터미널이면 획득은 초록, 해제는 파랑, 경고는 노랑으로
+    /// 칠한다. 파일이나 파이프, `log` 백엔드로는 색 코드를 내보내지 않는다.
+    Pretty,
+}
+
+/// 락 획득/해제 시 발생하는 이벤트.
+#[derive(Debug, Clone)]
+pub struct LockEvent {
+    /// 프로세스 전역에서 내보낸 순서. 1 부터 단조 증가하며 내보낼 때 매겨진다.
+    ///
+    /// 출력 버퍼링이나 스레드 스케줄링 때문에 로그 순서가 뒤섞여도 이 값으로 정렬하면 된다.
+    pub seq: u64,
+    pub kind: EventKind,
+    pub access: AccessKind,
+    pub name: Option<String>,
+    pub location: &'static Location<'static>,
+    pub thread: String,
+    /// 이벤트가 발생한 절대 시각
+    pub timestamp: SystemTime,
+    /// 해제 이벤트의 점유 시간
+    pub held: Option<Duration>,
+    /// `write_ctx`/`read_ctx` 로 붙인 사용자 컨텍스트
+    pub context: Option<String>,
+    /// 경고 등 부가 메시지
+    pub message: Option<String>,
+    /// 태스크가 보유한 가드 수. 해제 이벤트에서는 해제 후 남은 수. (`set_nesting_depth`)
+    pub depth: Option<usize>,
+    /// 획득 이벤트에서, 처음 poll 된 뒤 락을 얻기까지 걸린 시간
+    pub waited: Option<Duration>,
+    /// `waited` 중 락이 아니라 런타임이 태스크를 돌리지 못해서 지연된 시간(근사치)
+    pub scheduler_delay: Option<Duration>,
+    /// 해제 이벤트의 해제 방식
+    pub reason: Option<ReleaseReason>,
+    /// 해제 이벤트에서, 이 가드를 얻은 `read()`/`write()` 호출 위치.
+    ///
+    /// 가드가 여러 함수를 거쳐 옮겨져도 획득 하나에 해제 하나이며, 이 위치로 짝을 찾는다.
+    pub acquired_at: Option<&'static Location<'static>>,
+    /// 획득/해제 이벤트를 만들 때의 OpenTelemetry trace id (16진수, `otel` 기능)
+    pub trace_id: Option<String>,
+    /// `trace_id` 와 함께 기록된 span id (16진수, `otel` 기능)
+    pub span_id: Option<String>,
+    /// 전이 이벤트에서, 바뀌기 전의 접근 종류. `access` 가 바뀐 뒤의 종류다.
+    pub previous: Option<AccessKind>,
+    /// `write_prio` 로 붙인 우선순위. 진단용이며 스케줄링에는 영향이 없다.
+    pub priority: Option<u8>,
+    /// 해제 이벤트에서, 가드를 역참조한 횟수 (`access-count` 기능)
+    ///
+    /// 오래 쥔 가드가 실제로 일을 했는지, 그냥 들고만 있었는지 가늠할 때 쓴다.
+    pub accesses: Option<u64>,
+    /// `with_strict_fifo` 락의 획득 이벤트에서, 락을 받은 차례 (0 부터)
+    pub ticket: Option<u64>,
+    /// 획득 이벤트에서, 락을 얻은 지점까지 `enter_frame` 으로 표시한 호출 깊이
+    /// (`LockConfig::record_stack_depth`)
+    pub stack_depth: Option<usize>,
+    /// 이벤트를 만든 태스크가 `with_operation_id` 안에서 돌고 있었다면 그 id
+    pub operation_id: Option<String>,
+}
+
+/// 사람이 읽는 형식. 타임스탬프는 마이크로초 정밀도의 RFC3339(UTC)로 출력한다.
+impl fmt::Display for LockEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            EventKind::Acquire => "acquired",
+            EventKind::Release => "released",
+            EventKind::Cancelled => "acquisition cancelled",
+            EventKind::Initialized => "initialized",
+            EventKind::Warning => "warning",
+            EventKind::Busy => "busy",
+            EventKind::Waiting => "waiting for a write",
+            EventKind::Woken => "woken by a write",
+            EventKind::Created => "created",
+            EventKind::Destroyed => "destroyed",
+            EventKind::Transition => "transitioned",
+            EventKind::Misuse => "misused",
+            EventKind::Starvation => "starved",
+            EventKind::Peek => "peeked",
+            EventKind::Deadlock => "deadlocked",
+        };
+        write!(f, "{} #{} ", Rfc3339(self.timestamp), self.seq)?;
+        // 수명 이벤트는 접근 종류와 상관없다.
+        match (self.kind, self.access) {
+            (EventKind::Created | EventKind::Destroyed | EventKind::Transition, _) => {
+                write!(f, "lock")?
+            }
+            (_, AccessKind::Read) => write!(f, "Read lock")?,
+            (_, AccessKind::Write) => write!(f, "Write lock")?,
+        }
+        if let Some(name) = &self.name {
+            write!(f, " '{}'", name)?;
+        }
+        match (self.kind, self.previous) {
+            (EventKind::Transition, Some(previous)) => {
+                let name = |access| match access {
+                    AccessKind::Read => "read",
+                    AccessKind::Write => "write",
+                };
+                let direction = match previous {
+                    AccessKind::Write => "downgrade",
+                    AccessKind::Read => "upgrade",
+                };
+                write!(f, " {}→{} {}", name(previous), name(self.access), direction)?;
+            }
+            _ => write!(f, " {}", kind)?,
+        }
+        match self.reason {
+            Some(ReleaseReason::Explicit) => write!(f, " explicitly")?,
+            Some(ReleaseReason::Unwound) => write!(f, " during unwind")?,
+            Some(ReleaseReason::EarlyExit) => write!(f, " on early exit")?,
+            Some(ReleaseReason::Dropped) | None => {}
+        }
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(held) = self.held {
+            write!(f, ". Duration: {:?}", held)?;
+        }
+        if let Some(accesses) = self.accesses {
+            write!(f, " (accessed {} times)", accesses)?;
+        }
+        if let Some(acquired_at) = self.acquired_at {
+            write!(
+                f,
+                " (acquired at {}:{})",
+                acquired_at.file(),
+                acquired_at.line()
+            )?;
+        }
+        if let Some(waited) = self.waited.filter(|waited| !waited.is_zero()) {
+            write!(f, " after {:?}", waited)?;
+            if let Some(delay) = self.scheduler_delay {
+                write!(
+                    f,
+                    " (lock wait {:?}, scheduler delay {:?})",
+                    waited.saturating_sub(delay),
+                    delay
+                )?;
+            }
+        }
+        if let Some(priority) = self.priority {
+            write!(f, " (priority {})", priority)?;
+        }
+        if let Some(ticket) = self.ticket {
+            write!(f, " (ticket {})", ticket)?;
+        }
+        if let Some(depth) = self.depth {
+            write!(f, " (depth {})", depth)?;
+        }
+        if let Some(frames) = self.stack_depth {
+            write!(f, " (stack depth {})", frames)?;
+        }
+        if let Some(context) = &self.context {
+            write!(f, " [{}]", context)?;
+        }
+        if let Some(operation) = &self.operation_id {
+            write!(f, " (op {})", operation)?;
+        }
+        if let (Some(trace_id), Some(span_id)) = (&self.trace_id, &self.span_id) {
+            write!(f, " (trace {}, span {})", trace_id, span_id)?;
+        }
+        write!(
+            f,
+            " at {}:{} on thread {}",
+            self.location.file(),
+            self.location.line(),
+            self.thread
+        )
+    }
+}
+
+/// `OutputFormat::Pretty` 한 줄. `color` 가 꺼져 있으면 ANSI 코드를 쓰지 않는다.
+// `tracing-backend` 는 형식을 쓰지 않는다.
+#[cfg_attr(
+    all(feature = "tracing-backend", not(feature = "log-backend")),
+    allow(dead_code)
+)]
+pub(crate) struct Pretty<'a> {
+    pub(crate) event: &'a LockEvent,
+    pub(crate) color: bool,
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let event = self.event;
+        let (label, color) = match event.kind {
+            EventKind::Acquire => ("acquire", "32"),
+            EventKind::Release => ("release", "34"),
+            EventKind::Cancelled => ("cancel", ""),
+            EventKind::Initialized => ("init", ""),
+            EventKind::Warning => ("warning", "33"),
+            EventKind::Busy => ("busy", ""),
+            EventKind::Waiting => ("waiting", ""),
+            EventKind::Woken => ("woken", ""),
+            EventKind::Created => ("created", ""),
+            EventKind::Destroyed => ("destroyed", ""),
+            EventKind::Transition => ("transition", ""),
+            EventKind::Misuse => ("misuse", "33"),
+            EventKind::Starvation => ("starved", "31"),
+            EventKind::Peek => ("peek", ""),
+            EventKind::Deadlock => ("deadlock", "31"),
+        };
+        let access = match (event.kind, event.access) {
+            (EventKind::Created | EventKind::Destroyed | EventKind::Transition, _) => "-",
+            (_, AccessKind::Read) => "read",
+            (_, AccessKind::Write) => "write",
+        };
+        write!(f, "{} {:>6} ", Rfc3339(event.timestamp), event.seq)?;
+        if self.color && !color.is_empty() {
+            write!(f, "\x1b[{}m{:<10}\x1b[0m", color, label)?;
+        } else {
+            write!(f, "{:<10}", label)?;
+        }
+        write!(
+            f,
+            " {:<5} {:<16}",
+            access,
+            event.name.as_deref().unwrap_or("-")
+        )?;
+        if let (EventKind::Transition, Some(previous)) = (event.kind, event.previous) {
+            write!(f, " {:?}→{:?}", previous, event.access)?;
+        }
+        if let Some(message) = &event.message {
+            write!(f, " {}", message)?;
+        }
+        if let Some(held) = event.held {
+            write!(f, " held {:?}", held)?;
+        }
+        if let Some(accesses) = event.accesses {
+            write!(f, " accessed {}", accesses)?;
+        }
+        if let Some(waited) = event.waited.filter(|waited| !waited.is_zero()) {
+            write!(f, " waited {:?}", waited)?;
+        }
+        if let Some(priority) = event.priority {
+            write!(f, " priority {}", priority)?;
+        }
+        if let Some(ticket) = event.ticket {
+            write!(f, " ticket {}", ticket)?;
+        }
+        if let Some(depth) = event.depth {
+            write!(f, " depth {}", depth)?;
+        }
+        if let Some(frames) = event.stack_depth {
+            write!(f, " stack {}", frames)?;
+        }
+        if let Some(context) = &event.context {
+            write!(f, " [{}]", context)?;
+        }
+        if let Some(operation) = &event.operation_id {
+            write!(f, " op {}", operation)?;
+        }
+        write!(
+            f,
+            " @ {}:{} ({})",
+            event.location.file(),
+            event.location.line(),
+            event.thread
+        )
+    }
+}
+
+/// 이벤트를 받는 관찰자. `LockConfig::observer` 로 락마다 등록한다.
+pub trait LockObserver: Send + Sync {
+    fn on_event(&self, event: &LockEvent);
+}
+
+impl<F: Fn(&LockEvent) + Send + Sync> LockObserver for F {
+    fn on_event(&self, event: &LockEvent) {
+        self(event)
+    }
+}
+
+type LocationFilter = Arc<dyn Fn(&Location<'_>) -> bool + Send + Sync>;
+
+static HAS_LOCATION_FILTER: AtomicBool = AtomicBool::new(false);
+static LOCATION_FILTER: RwLock<Option<LocationFilter>> = RwLock::new(None);
+
+/// 획득 위치로 획득/해제 이벤트를 거른다. `filter` 가 `false` 를 돌려준 위치에서 얻은 가드의
+/// 획득/해제 이벤트는 내보내지 않는다. 프로세스 전역이며 통계는 그대로 집계된다.
+///
+/// 해제 이벤트도 드롭한 곳이 아니라 가드를 얻은 곳으로 판단하므로 짝이 함께 걸러진다. 경고 등
+/// 다른 이벤트는 영향을 받지 않는다.
+///
+/// ```
+/// // 의존성 안에서 잡는 락은 빼고 이 크레이트의 코드만 본다.
+/// tracing_lock::set_location_filter(|location| !location.file().contains("/.cargo/registry/"));
+/// ```
+pub fn set_location_filter(filter: impl Fn(&Location<'_>) -> bool + Send + Sync + 'static) {
+    *LOCATION_FILTER
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(filter));
+    HAS_LOCATION_FILTER.store(true, Ordering::Release);
+}
+
+/// `set_location_filter` 로 걸러지지 않는 이벤트인지
+pub(crate) fn location_allowed(event: &LockEvent) -> bool {
+    if !HAS_LOCATION_FILTER.load(Ordering::Acquire) {
+        return true;
+    }
+    let location = match event.kind {
+        EventKind::Acquire => event.location,
+        EventKind::Release => event.acquired_at.unwrap_or(event.location),
+        _ => return true,
+    };
+    let filter = LOCATION_FILTER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    filter.map_or(true, |filter| filter(location))
+}
+
+/// `kind` 이벤트를 받을 곳(관찰자나 켜진 출력)이 있는지
+pub(crate) fn enabled(config: &LockConfig, kind: EventKind) -> bool {
+    crate::config::enabled()
+        && (config.observer.is_some()
+            || (kind == EventKind::Warning && config.watchdog_observer.is_some())
+            || (!config.silent && sink_enabled(kind)))
+}
+
+#[cfg(not(any(feature = "log-backend", feature = "tracing-backend")))]
+fn sink_enabled(_kind: EventKind) -> bool {
+    true
+}
+
+#[cfg(feature = "log-backend")]
+fn sink_enabled(kind: EventKind) -> bool {
+    log::log_enabled!(target: "tracing_lock", log_level(kind))
+}
+
+#[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+fn sink_enabled(kind: EventKind) -> bool {
+    crate::trace::enabled(kind)
+}
+
+#[cfg(feature = "log-backend")]
+fn log_level(kind: EventKind) -> log::Level {
+    match kind {
+        EventKind::Misuse | EventKind::Starvation | EventKind::Deadlock => log::Level::Error,
+        EventKind::Warning => log::Level::Warn,
+        EventKind::Created | EventKind::Destroyed => log::Level::Trace,
+        _ => log::Level::Debug,
+    }
+}
+
+static SEQ: AtomicU64 = AtomicU64::new(1);
+
+pub(crate) fn emit(config: &LockConfig, mut event: LockEvent) {
+    // 한도는 출력에만 건다. 관찰자는 모든 이벤트를 받는다. 버린 이벤트의 요약은 이 이벤트보다
+    // 먼저 번호를 받고 나간다.
+    let write_out = !config.silent
+        && config
+            .rate_limit
+            .0
+            .as_ref()
+            .map_or(true, |limiter| limiter.admit(config, &event));
+    event.seq = SEQ.fetch_add(1, Ordering::Relaxed);
+    if write_out {
+        write(config, &event);
+    }
+    if let Some(observer) = &config.observer {
+        observer.on_event(&event);
+    }
+}
+
+// 켜진 백엔드 하나로 이벤트를 내보낸다.
+#[cfg_attr(
+    all(feature = "tracing-backend", not(feature = "log-backend")),
+    allow(unused_variables)
+)]
+fn write(config: &LockConfig, event: &LockEvent) {
+    #[cfg(not(any(feature = "log-backend", feature = "tracing-backend")))]
+    match config.format {
+        OutputFormat::Plain => println!("{}", event),
+        OutputFormat::Pretty => println!(
+            "{}",
+            Pretty {
+                event,
+                color: stdout_is_terminal(),
+            }
+        ),
+    }
+    #[cfg(feature = "log-backend")]
+    match config.format {
+        OutputFormat::Plain => {
+            log::log!(target: "tracing_lock", log_level(event.kind), "{}", event)
+        }
+        OutputFormat::Pretty => log::log!(
+            target: "tracing_lock",
+            log_level(event.kind),
+            "{}",
+            Pretty {
+                event,
+                color: false,
+            }
+        ),
+    }
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    crate::trace::event(event);
+}
+
+// 파일이나 파이프로 리디렉션된 출력에는 색을 넣지 않는다. `NO_COLOR` 가 있으면 늘 끈다.
+#[cfg(not(any(feature = "log-backend", feature = "tracing-backend")))]
+fn stdout_is_terminal() -> bool {
+    use std::io::IsTerminal;
+
+    static TERMINAL: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *TERMINAL
+        .get_or_init(|| std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal())
+}
+
+struct Rfc3339(SystemTime);
+
+impl fmt::Display for Rfc3339 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 1970년 이전 시각은 표현하지 않는다.
+        let since_epoch = self.0.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs = since_epoch.as_secs();
+        let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+        let rem = secs % 86_400;
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z",
+            year,
+            month,
+            day,
+            rem / 3600,
+            rem / 60 % 60,
+            rem % 60,
+            since_epoch.subsec_micros()
+        )
+    }
+}
+
+// 1970-01-01 기준 일수를 (년, 월, 일)로 변환한다. (Howard Hinnant 의 civil_from_days)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "YYYY-MM-DDTHH:MM:SS.ffffffZ" 를 UNIX 시각(마이크로초)으로 되돌린다.
+    fn parse_rfc3339(s: &str) -> Option<u128> {
+        let (date, time) = s.strip_suffix('Z')?.split_once('T')?;
+        let mut date = date.split('-').map(|v| v.parse::<i64>().ok());
+        let (y, m, d) = (date.next()??, date.next()??, date.next()??);
+        let (hms, micros) = time.split_once('.')?;
+        let mut hms = hms.split(':').map(|v| v.parse::<u128>().ok());
+        let (hh, mm, ss) = (hms.next()??, hms.next()??, hms.next()??);
+        if micros.len() != 6 {
+            return None;
+        }
+        // days_from_civil
+        let y = if m <= 2 { y - 1 } else { y };
+        let era = y.div_euclid(400);
+        let yoe = y.rem_euclid(400);
+        let mp = if m > 2 { m - 3 } else { m + 9 };
+        let doy = (153 * mp + 2) / 5 + d - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        let days = (era * 146_097 + doe - 719_468) as u128;
+        Some(
+            ((days * 86_400 + hh * 3600 + mm * 60 + ss) * 1_000_000)
+                + micros.parse::<u128>().ok()?,
+        )
+    }
+
+    #[test]
+    fn formats_known_timestamps() {
+        let at = |micros: u64| Rfc3339(UNIX_EPOCH + Duration::from_micros(micros)).to_string();
+        assert_eq!(at(0), "1970-01-01T00:00:00.000000Z");
+        assert_eq!(at(951_782_400_000_001), "2000-02-29T00:00:00.000001Z");
+        assert_eq!(at(1_790_000_000_123_456), "2026-09-21T14:13:20.123456Z");
+    }
+
+    #[test]
+    fn event_contains_parseable_timestamp() {
+        let timestamp = SystemTime::now();
+        let event = LockEvent {
+            seq: 7,
+            kind: EventKind::Release,
+            access: AccessKind::Write,
+            name: Some("config".into()),
+            location: Location::caller(),
+            thread: "main".into(),
+            timestamp,
+            held: Some(Duration::from_millis(3)),
+            context: Some("req-7".into()),
+            message: None,
+            depth: None,
+            waited: None,
+            scheduler_delay: None,
+            reason: None,
+            acquired_at: None,
+            trace_id: None,
+            span_id: None,
+            previous: None,
+            priority: None,
+            accesses: None,
+            ticket: None,
+            stack_depth: None,
+            operation_id: None,
+        };
+        let line = event.to_string();
+        let stamp = line.split(' ').next().unwrap();
+        let expected = timestamp.duration_since(UNIX_EPOCH).unwrap().as_micros();
+        assert_eq!(parse_rfc3339(stamp), Some(expected));
+        assert!(line.contains(" #7 Write lock 'config' released. Duration: 3ms [req-7]"));
+
+        let pretty = Pretty {
+            event: &event,
+            color: false,
+        }
+        .to_string();
+        assert!(!pretty.contains('\x1b'), "{pretty}");
+        assert!(
+            pretty.contains("      7 release    write config           held 3ms [req-7] @ "),
+            "{pretty}"
+        );
+        let colored = Pretty {
+            event: &event,
+            color: true,
+        }
+        .to_string();
+        assert!(colored.contains("\x1b[34mrelease   \x1b[0m"), "{colored}");
+    }
+
+    #[cfg(not(any(feature = "log-backend", feature = "tracing-backend")))]
+    #[test]
+    fn pretty_output_has_no_color_without_terminal() {
+        use std::io::IsTerminal;
+
+        // 테스트 하네스의 stdout 은 파이프이거나 캡처되므로 터미널이 아니다.
+        if std::io::stdout().is_terminal() {
+            return;
+        }
+        assert!(!stdout_is_terminal());
+    }
+}
@@ -0,0 +1,671 @@
+use std::{
+    future::Future,
+    num::NonZeroU64,
+    panic::Location,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Context as TaskContext, Poll, Wake, Waker},
+    time::Duration,
+};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard, Semaphore};
+use tokio::time::Instant;
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::guard::{GuardContext, GuardState, LoggingRwLockReadGuard, LoggingRwLockWriteGuard};
+use crate::registry::{Entry, HolderToken};
+use crate::slowest::SlowBy;
+use crate::stats::{ReadPermit, WaitGuard, WriterWaitGuard};
+use crate::task;
+use crate::timeline;
+
+type Acquire<'a, G> = Pin<Box<dyn Future<Output = G> + Send + 'a>>;
+// 안쪽 가드와 읽기 허가, `with_strict_fifo` 의 차례 번호
+type Inner<G> = ((G, Option<ReadPermit>), Option<u64>);
+
+/// 현재 스레드를 막고 `future` 를 끝까지 돌린다. (`blocking_read`/`blocking_write`)
+///
+/// tokio 의 `blocking_*` 처럼 런타임 안에서 부르면 패닉한다. 런타임 워커를 막으면 락을 쥔 태스크가
+/// 돌지 못해 교착될 수 있기 때문이다.
+#[track_caller]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    struct Unpark(std::thread::Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    assert!(
+        tokio::runtime::Handle::try_current().is_err(),
+        "cannot block the current thread from within a runtime; use `read`/`write` instead"
+    );
+    let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+    let mut cx = TaskContext::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::park();
+    }
+}
+
+/// `with_strict_fifo` 의 차례 대기열. tokio 의 `Mutex` 는 기다린 순서대로 넘겨주므로, 읽기든
+/// 쓰기든 이것을 쥔 채 안쪽 락을 기다리면 요청 순서대로 락을 받는다.
+#[derive(Debug, Default)]
+pub(crate) struct Fifo {
+    queue: tokio::sync::Mutex<()>,
+    next_ticket: AtomicU64,
+}
+
+impl Fifo {
+    /// 기다리지 않는 획득의 차례. 대기열에 누가 서 있으면 `None` 이다. 돌려받은 차례를 쥔 동안에는
+    /// 새로 온 획득이 대기열에서 기다린다.
+    pub(crate) fn try_turn(&self) -> Option<tokio::sync::MutexGuard<'_, ()>> {
+        self.queue.try_lock().ok()
+    }
+
+    /// 차례를 쥔 채 락을 얻었을 때 붙일 번호
+    pub(crate) fn ticket(&self) -> u64 {
+        self.next_ticket.fetch_add(1, Ordering::Relaxed)
+    }
+}
+
+// 차례가 오면 `lock` 으로 안쪽 락을 얻는다. `permits` 가 있으면 읽기든 쓰기든 락보다 먼저 허가를
+// 얻는다. (`with_max_readers`)
+pub(crate) fn acquisition<'a, G>(
+    config: &LockConfig,
+    entry: &Entry,
+    permits: Option<&Arc<Semaphore>>,
+    lock: impl Future<Output = G> + Send + 'a,
+) -> Acquire<'a, Inner<G>> {
+    #[cfg(feature = "chaos")]
+    let delay = config.chaos.as_ref().map(|chaos| chaos.next_delay());
+    let permits = permits.map(|permits| (permits.clone(), entry.stats.clone()));
+    Box::pin(in_turn(config.fifo.clone(), async move {
+        #[cfg(feature = "chaos")]
+        if let Some(delay) = delay {
+            delay.apply().await;
+        }
+        let permit = match permits {
+            Some((permits, stats)) => {
+                let permit = permits
+                    .acquire_owned()
+                    .await
+                    .expect("read permits are never closed");
+                Some(stats.hold_read_permit(permit))
+            }
+            None => None,
+        };
+        (lock.await, permit)
+    }))
+}
+
+// 차례가 오면 안쪽 락을 기다리고, 받은 차례 번호를 함께 돌려준다.
+async fn in_turn<F: Future>(fifo: Option<Arc<Fifo>>, acquire: F) -> (F::Output, Option<u64>) {
+    let Some(fifo) = fifo else {
+        return (acquire.await, None);
+    };
+    let _turn = fifo.queue.lock().await;
+    let ticket = fifo.ticket();
+    (acquire.await, Some(ticket))
+}
+
+/// `TokioRwLockTrace::read` 가 반환하는 이름 있는 획득 future.
+///
+/// `FuturesUnordered` 등에 담거나 `select!` 에서 이름으로 다룰 수 있다. 처음 poll 될 때부터
+/// 대기자로 집계되며, 획득 전에 드롭되면 취소 이벤트를 남긴다.
+/// 언제나 `Send` 이므로 만들려면 `T: Send + Sync` 여야 한다.
+///
+/// ```
+/// use futures::stream::{FuturesUnordered, StreamExt};
+/// use tracing_lock::{ReadFuture, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let locks: Vec<_> = (0..3).map(TokioRwLockTrace::new).collect();
+/// let pending: FuturesUnordered<ReadFuture<'_, i32>> = locks.iter().map(|l| l.read()).collect();
+/// let sum: i32 = pending.map(|guard| *guard).collect::<Vec<_>>().await.iter().sum();
+/// assert_eq!(sum, 3);
+/// # }
+/// ```
+pub struct ReadFuture<'a, T, C: GuardContext = ()> {
+    state: State<'a, Inner<RwLockReadGuard<'a, T>>, C>,
+}
+
+/// `TokioRwLockTrace::write` 가 반환하는 이름 있는 획득 future. (`ReadFuture` 참고)
+pub struct WriteFuture<'a, T, C: GuardContext = ()> {
+    state: State<'a, Inner<RwLockWriteGuard<'a, T>>, C>,
+}
+
+pub(crate) struct State<'a, G, C: GuardContext> {
+    acquire: Acquire<'a, G>,
+    config: &'a Arc<LockConfig>,
+    entry: &'a Arc<Entry>,
+    access: AccessKind,
+    // `read()`/`write()` 를 부른 위치. 보유자 기록에 쓴다.
+    location: &'static Location<'static>,
+    // 획득이 끝나면 가드로 옮겨진다.
+    context: Option<C>,
+    waiting: Option<(WaitGuard<'a>, Option<WriterWaitGuard<'a>>)>,
+    // `warn_if_wait_exceeds` 가 있으면 처음 `Pending` 일 때 대기자 목록에 오른다.
+    waiter: Option<HolderToken>,
+    // `deadlock::set_enabled` 가 켜져 있으면 처음 `Pending` 일 때 대기 그래프에 오른다.
+    deadlock: Option<crate::deadlock::WaitToken>,
+    // `timeline::start` 로 기록 중이면 처음 poll 될 때 대기 시작을 남긴다.
+    timeline: Option<timeline::Pending>,
+    // 처음 poll 된 시각
+    started: Option<Instant>,
+    // 경합이 생겼을 때만 만든다.
+    wake: Option<Arc<WakeRecorder>>,
+    scheduler_delay: Duration,
+    // `write_prio` 로 붙인 우선순위
+    priority: Option<u8>,
+    // 한 번이라도 `Pending` 을 돌려줌. 경합률에 쓴다.
+    contended: bool,
+    // `peek`: 획득 이벤트를 남기지 않고 해제 때 `Peek` 하나만 남긴다.
+    peek: bool,
+    // `escalate_writer_starvation`: 기다리기 시작할 때 맞춘 타이머. 울리고 나면 `None`.
+    escalation: Option<Pin<Box<tokio::time::Sleep>>>,
+    escalated: bool,
+}
+
+struct Acquired<G, C> {
+    guard: G,
+    context: C,
+    // `with_timing(false)` 면 없다.
+    waited: Option<Duration>,
+    scheduler_delay: Option<Duration>,
+    timeline: Option<NonZeroU64>,
+}
+
+/// 태스크 waker 를 감싸서 깨워진 시각을 기록한다.
+///
+/// 깨워진 뒤 다시 poll 될 때까지의 시간은 락이 아니라 런타임이 태스크를 돌리지 못한 시간
+/// (스케줄러 지연)으로 본다. 근사치이다.
+struct WakeRecorder {
+    state: Mutex<(Waker, Option<Instant>)>,
+}
+
+impl WakeRecorder {
+    fn waker(self: &Arc<Self>, task: &Waker) -> Waker {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if !state.0.will_wake(task) {
+            state.0 = task.clone();
+        }
+        Waker::from(self.clone())
+    }
+
+    fn take_woken(&self) -> Option<Instant> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .1
+            .take()
+    }
+}
+
+impl Wake for WakeRecorder {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let waker = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.1.get_or_insert_with(Instant::now);
+            state.0.clone()
+        };
+        waker.wake();
+    }
+}
+
+impl<'a, G, C: GuardContext> State<'a, G, C> {
+    fn poll_acquire(&mut self, cx: &mut TaskContext<'_>) -> Poll<Acquired<G, C>> {
+        if self.context.is_none() {
+            panic!("lock future polled after completion");
+        }
+        let now = self.config.timed().then(Instant::now);
+        if let Some(now) = now {
+            self.started.get_or_insert(now);
+        }
+        if self.waiting.is_none() {
+            let stats = &self.entry.stats;
+            let writer = (self.access == AccessKind::Write).then(|| stats.start_write_wait());
+            if let Some(priority) = self.priority {
+                stats.note_waiting_priority(priority);
+            }
+            self.waiting = Some((stats.start_wait(), writer));
+            self.timeline = timeline::begin(self.config, self.entry.id, self.access, self.location);
+        }
+        let guard = match &self.wake {
+            Some(wake) => {
+                if let (Some(now), Some(woken)) = (now, wake.take_woken()) {
+                    self.scheduler_delay += now.saturating_duration_since(woken);
+                }
+                let waker = wake.waker(cx.waker());
+                self.acquire
+                    .as_mut()
+                    .poll(&mut TaskContext::from_waker(&waker))
+            }
+            None => match self.acquire.as_mut().poll(cx) {
+                Poll::Ready(guard) => Poll::Ready(guard),
+                // 처음 경합이 생겼을 때 깨워진 시각을 기록하는 waker 로 다시 등록한다.
+                Poll::Pending if now.is_some() => {
+                    let wake = Arc::new(WakeRecorder {
+                        state: Mutex::new((cx.waker().clone(), None)),
+                    });
+                    let waker = wake.waker(cx.waker());
+                    self.wake = Some(wake);
+                    self.acquire
+                        .as_mut()
+                        .poll(&mut TaskContext::from_waker(&waker))
+                }
+                poll => poll,
+            },
+        };
+        let Poll::Ready(guard) = guard else {
+            self.contended = true;
+            if self.waiter.is_none() {
+                let since = self
+                    .started
+                    .map_or_else(std::time::Instant::now, Instant::into_std);
+                self.waiter =
+                    self.entry
+                        .track_waiter(self.access, self.location, self.config, since);
+                self.deadlock = crate::deadlock::waiting(
+                    self.config,
+                    self.entry.id,
+                    self.access,
+                    self.location,
+                );
+            }
+            self.poll_escalation(cx);
+            return Poll::Pending;
+        };
+        self.waiting = None;
+        self.waiter = None;
+        self.deadlock = None;
+        match self.access {
+            AccessKind::Read => self.entry.stats.record_read(),
+            AccessKind::Write => self.entry.stats.record_write(),
+        }
+        #[cfg(feature = "tokio-console")]
+        crate::console::acquired(self.entry, self.access);
+        self.record_contention();
+        // 첫 poll 에 바로 얻었다면 기다리지 않은 것으로 본다.
+        let waited = self.started.map(|started| match self.wake {
+            Some(_) => started.elapsed(),
+            None => Duration::ZERO,
+        });
+        if let Some(waited) = waited {
+            self.entry
+                .slowest
+                .record(SlowBy::Wait, self.access, self.location, waited);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::acquired(self.entry, self.access, waited);
+        Poll::Ready(Acquired {
+            guard,
+            context: self.context.take().expect("checked above"),
+            waited,
+            scheduler_delay: self.started.map(|_| self.scheduler_delay),
+            timeline: self.timeline.take().map(timeline::Pending::acquired),
+        })
+    }
+}
+
+impl<G, C: GuardContext> State<'_, G, C> {
+    // 기다리는 writer 의 차례가 `escalate_writer_starvation` 을 넘기면 쥐고 있는 reader 를 남긴다.
+    fn poll_escalation(&mut self, cx: &mut TaskContext<'_>) {
+        let Some(after) = self.config.starvation_escalation else {
+            return;
+        };
+        // `blocking_write` 처럼 런타임 밖에서 기다리면 타이머를 걸 수 없다.
+        if self.access != AccessKind::Write
+            || self.escalated
+            || tokio::runtime::Handle::try_current().is_err()
+        {
+            return;
+        }
+        let timer = self
+            .escalation
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(after)));
+        if timer.as_mut().poll(cx).is_pending() {
+            return;
+        }
+        self.escalation = None;
+        self.escalated = true;
+        let readers = self.entry.holder_sites(AccessKind::Read);
+        let held_by = match readers.len() {
+            0 => "no readers recorded (another writer may hold it)".to_string(),
+            n => {
+                let sites: Vec<_> = readers
+                    .iter()
+                    .map(|site| format!("{}:{}", site.file(), site.line()))
+                    .collect();
+                format!("{} reader(s) still holding it from {}", n, sites.join(", "))
+            }
+        };
+        log_call_info!(
+            self.config,
+            EventKind::Starvation,
+            AccessKind::Write,
+            location: self.location,
+            waited: self.started.map(|started| started.elapsed()),
+            message: Some(format!("writer waited over {:?}; {}", after, held_by)),
+        );
+    }
+
+    fn record_contention(&self) {
+        let stats = &self.entry.stats;
+        stats.record_contention(self.contended);
+        let Some(threshold) = self.config.contention_threshold else {
+            return;
+        };
+        if let Some(ratio) = stats.contention_crossed(threshold) {
+            log_call_info!(
+                self.config,
+                EventKind::Warning,
+                self.access,
+                location: self.location,
+                message: Some(format!(
+                    "contention ratio {:.2} exceeded {:.2} over the last {:?}",
+                    ratio,
+                    threshold,
+                    crate::stats::CONTENTION_WINDOW * 2
+                )),
+            );
+        }
+    }
+}
+
+impl<G, C: GuardContext> Drop for State<'_, G, C> {
+    fn drop(&mut self) {
+        // 대기 중에 드롭된 경우만 취소로 본다.
+        if self.waiting.take().is_some() {
+            if let Some(context) = self.context.take() {
+                log_call_info!(
+                    self.config,
+                    EventKind::Cancelled,
+                    self.access,
+                    location: self.location,
+                    context: context.render(),
+                );
+            }
+        }
+    }
+}
+
+impl<'a, T: Send + Sync, C: GuardContext> ReadFuture<'a, T, C> {
+    #[track_caller]
+    pub(crate) fn new(
+        lock: &'a RwLock<T>,
+        config: &'a Arc<LockConfig>,
+        entry: &'a Arc<Entry>,
+        permits: Option<&Arc<Semaphore>>,
+        context: C,
+    ) -> Self {
+        let acquire = acquisition(config, entry, permits, lock.read());
+        let location = Location::caller();
+        ReadFuture {
+            state: State::new(acquire, config, entry, AccessKind::Read, location, context),
+        }
+    }
+}
+
+impl<'a, T: Send + Sync, C: GuardContext> WriteFuture<'a, T, C> {
+    #[track_caller]
+    pub(crate) fn new(
+        lock: &'a RwLock<T>,
+        config: &'a Arc<LockConfig>,
+        entry: &'a Arc<Entry>,
+        permits: Option<&Arc<Semaphore>>,
+        context: C,
+    ) -> Self {
+        let acquire = acquisition(config, entry, permits, lock.write());
+        let location = Location::caller();
+        WriteFuture {
+            state: State::new(acquire, config, entry, AccessKind::Write, location, context),
+        }
+    }
+}
+
+impl<'a, G, C: GuardContext> State<'a, G, C> {
+    /// 안쪽 락을 얻는 `acquire` 를 계측한다. `location` 은 `read()`/`write()` 를 부른 곳이다.
+    pub(crate) fn new(
+        acquire: Acquire<'a, G>,
+        config: &'a Arc<LockConfig>,
+        entry: &'a Arc<Entry>,
+        access: AccessKind,
+        location: &'static Location<'static>,
+        context: C,
+    ) -> Self {
+        entry.announce(config);
+        State {
+            acquire,
+            config,
+            entry,
+            access,
+            location,
+            context: Some(context),
+            waiting: None,
+            waiter: None,
+            deadlock: None,
+            timeline: None,
+            started: None,
+            wake: None,
+            scheduler_delay: Duration::ZERO,
+            priority: None,
+            contended: false,
+            peek: false,
+            escalation: None,
+            escalated: false,
+        }
+    }
+}
+
+impl<'a, T, C: GuardContext> ReadFuture<'a, T, C> {
+    pub(crate) fn peek(mut self) -> Self {
+        self.state.peek = true;
+        self
+    }
+}
+
+impl<'a, T, C: GuardContext> WriteFuture<'a, T, C> {
+    pub(crate) fn with_priority(mut self, priority: u8) -> Self {
+        self.state.priority = Some(priority);
+        self
+    }
+}
+
+impl<G, C: GuardContext> State<'_, Inner<G>, C> {
+    /// 안쪽 락을 얻으면 획득 이벤트를 남기고, 안쪽 가드와 허가, 가드의 계측 상태를 돌려준다.
+    pub(crate) fn poll_guard(
+        &mut self,
+        cx: &mut TaskContext<'_>,
+    ) -> Poll<(G, Option<ReadPermit>, GuardState<C>)> {
+        let acquired = std::task::ready!(self.poll_acquire(cx));
+        let (held, depth) = task::push(self.entry.id).unzip();
+        let context = acquired.context;
+        let ((guard, permit), ticket) = acquired.guard;
+        let event = if self.peek {
+            None
+        } else {
+            lock_event!(
+                self.config,
+                EventKind::Acquire,
+                self.access,
+                // poll 이 아니라 `read()` 를 부른 위치
+                location: self.location,
+                context: context.render(),
+                depth: depth,
+                stack_depth: self.config.stack_depth.then(task::stack_depth),
+                waited: acquired.waited,
+                scheduler_delay: acquired.scheduler_delay,
+                priority: self.priority,
+                ticket: ticket,
+            )
+        };
+        let mut state = GuardState::new(
+            self.config,
+            self.entry,
+            self.access,
+            context,
+            held,
+            event,
+            self.location,
+        );
+        state.peek = self.peek;
+        state.priority = self.priority;
+        state.site = crate::sites::acquired(
+            self.config,
+            self.entry,
+            self.access,
+            self.location,
+            acquired.waited,
+        );
+        state.timeline = acquired.timeline;
+        Poll::Ready((guard, permit, state))
+    }
+}
+
+impl<'a, T, C: GuardContext> Future for ReadFuture<'a, T, C> {
+    type Output = LoggingRwLockReadGuard<'a, T, C>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let (guard, permit, state) = std::task::ready!(self.state.poll_guard(cx));
+        Poll::Ready(LoggingRwLockReadGuard {
+            state,
+            guard,
+            _permit: permit,
+        })
+    }
+}
+
+impl<'a, T, C: GuardContext> Future for WriteFuture<'a, T, C> {
+    type Output = LoggingRwLockWriteGuard<'a, T, C>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let (guard, permit, state) = std::task::ready!(self.state.poll_guard(cx));
+        Poll::Ready(LoggingRwLockWriteGuard {
+            state,
+            guard,
+            _permit: permit,
+        })
+    }
+}
+
+// 컨텍스트 `C` 를 구조적으로 pin 하지 않으므로 언제나 Unpin 이다.
+impl<T, C: GuardContext> Unpin for ReadFuture<'_, T, C> {}
+impl<T, C: GuardContext> Unpin for WriteFuture<'_, T, C> {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::stream::{FuturesUnordered, StreamExt};
+
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig, ReadFuture, TokioRwLockTrace};
+
+    #[tokio::test]
+    async fn drives_read_futures_in_futures_unordered() {
+        let lock = TokioRwLockTrace::new(7);
+        let writer = lock.write().await;
+
+        let mut pending: FuturesUnordered<ReadFuture<'_, i32>> =
+            (0..3).map(|_| lock.read()).collect();
+        // writer 가 잡고 있으므로 아무것도 끝나지 않고 셋 다 대기자로 집계된다.
+        assert!(futures::poll!(pending.next()).is_pending());
+        assert_eq!(lock.stats().waiters, 3);
+
+        drop(writer);
+        let mut seen = 0;
+        while let Some(guard) = pending.next().await {
+            assert_eq!(*guard, 7);
+            seen += 1;
+        }
+        assert_eq!(seen, 3);
+        assert_eq!((lock.stats().reads, lock.stats().waiters), (3, 0));
+    }
+
+    #[tokio::test]
+    async fn dropping_a_pending_future_logs_cancellation() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        let reader = lock.read().await;
+
+        let mut write = lock.write();
+        assert!(futures::poll!(&mut write).is_pending());
+        assert_eq!(lock.stats().waiters, 1);
+        drop(write);
+        assert_eq!(lock.stats().waiters, 0);
+        // 한 번도 poll 되지 않은 future 는 취소로 보지 않는다.
+        drop(lock.write());
+        drop(reader);
+
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Cancelled,
+                EventKind::Release
+            ]
+        );
+    }
+
+    #[test]
+    fn scheduler_delay_is_reported_on_saturated_runtime() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone())),
+        );
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let writer = lock.write().await;
+            let reader = tokio::spawn({
+                let lock = lock.clone();
+                async move { drop(lock.read().await) }
+            });
+            tokio::task::yield_now().await;
+            assert_eq!(lock.stats().waiters, 1);
+
+            // 해제와 동시에 reader 가 깨워지지만, 스레드를 막아서 런타임이 돌지 못하게 한다.
+            drop(writer);
+            std::thread::sleep(std::time::Duration::from_millis(30));
+            reader.await.unwrap();
+        });
+
+        let events = events.events();
+        let read = events
+            .iter()
+            .find(|e| e.kind == EventKind::Acquire && e.access == crate::AccessKind::Read)
+            .unwrap();
+        let scheduler_delay = read.scheduler_delay.unwrap();
+        assert!(
+            scheduler_delay >= std::time::Duration::from_millis(25),
+            "{scheduler_delay:?}"
+        );
+        assert!(read.waited.unwrap() >= scheduler_delay);
+    }
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn futures_are_send_for_send_sync_values() {
+        let lock = TokioRwLockTrace::new(String::new());
+        assert_send(&lock.read());
+        assert_send(&lock.write_ctx(1));
+    }
+}
@@ -0,0 +1,624 @@
+use std::{
+    fmt,
+    num::NonZeroU64,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{RwLockReadGuard, RwLockWriteGuard};
+
+use crate::config::LockConfig;
+use crate::event::{self, AccessKind, EventKind, LockEvent, ReleaseReason};
+use crate::registry::{Entry, HolderToken};
+use crate::sites::Site;
+use crate::slowest::SlowBy;
+use crate::stats::ReadPermit;
+use crate::task::{self, Held};
+use crate::timeline;
+
+/*
+ * * Deref 및 DerefMut 트레이트 구현
+ *   Deref와 DerefMut 트레이트를 구현하면, 해당 구조체가 감싸고 있는 타입의 메서드에 자동으로 접근할 수 있다. (Rust의 자동 참조 역참조(dereferencing) 기능)
+ *   `Deref` 트레이트는 `*` 연산자를 오버로딩하는데 사용된다 .* 연산자를 사용하여 대상 객체를 불변 참조로 변환
+ *   `DerefMut` 트레이트는 `*mut` 연산자를 오버로딩하는데 사용된다. *mut 연산자를 사용하여 대상 객체를 가변 참조로 변환
+ * * 메서드 탐색
+ *   - 메서드 호출 시 컴파일러는 해당 메서드가 현재 타입에 존재하는지 확인.
+ *   - 존재하지 않으면, Deref 또는 DerefMut를 통해 반환된 타입에서 메서드를 탐색
+ * * 락을 획득하고 해제하는 시점을 정확히 로그에 기록하려면, RwLockWriteGuard와 RwLockReadGuard의 드롭 시점도 추적해야 한다.
+ */
+
+/// 읽기/쓰기 가드가 공유하는 계측 상태
+pub(crate) struct GuardState<C: GuardContext> {
+    pub(crate) config: Arc<LockConfig>,
+    pub(crate) entry: Arc<Entry>,
+    // 지금 잡고 있는 접근 종류. `downgrade` 하면 바뀐다.
+    access: AccessKind,
+    // 런타임이 내려간 뒤에 드롭되어도 쓸 수 있도록 `std` 의 Instant 를 쓴다.
+    // `with_timing(false)` 면 없다.
+    pub(crate) start_time: Option<Instant>,
+    // 획득할 때 tokio 런타임 안이었는지 (디버그 빌드 전용)
+    #[cfg(debug_assertions)]
+    pub(crate) in_runtime: bool,
+    pub(crate) context: C,
+    pub(crate) held: Option<Held>,
+    pub(crate) reason: ReleaseReason,
+    // `complete()` 로 임계 구역이 끝까지 갔다고 표시됨
+    pub(crate) completed: bool,
+    // `min_logged_hold` 가 있으면 점유 시간을 알 때까지 보류한 획득 이벤트
+    pub(crate) deferred: Option<LockEvent>,
+    // 가드를 얻은 호출 위치
+    pub(crate) acquired_at: &'static Location<'static>,
+    // `write_prio` 로 얻은 쓰기 가드의 우선순위
+    pub(crate) priority: Option<u8>,
+    // 역참조 횟수. 읽기 가드는 `&self` 로 역참조하므로 원자적으로 센다.
+    #[cfg(feature = "access-count")]
+    pub(crate) accesses: std::sync::atomic::AtomicU64,
+    // 누수 검사용 가드의 자리 (디버그 빌드 전용)
+    #[cfg(debug_assertions)]
+    live: crate::registry::LiveSlot,
+    // `registry::install_panic_hook` 이후에만 있다.
+    holder: Option<HolderToken>,
+    // `peek` 으로 얻은 가드. 해제 이벤트 대신 `Peek` 을 남긴다.
+    pub(crate) peek: bool,
+    // `track_sites` 면 가드를 얻은 호출 위치의 기록
+    pub(crate) site: Option<Arc<Site>>,
+    // `deadlock::set_enabled` 가 켜져 있을 때만 있다.
+    deadlock: Option<crate::deadlock::HoldToken>,
+    // `timeline::start` 로 기록 중에 얻었으면 그 획득 번호
+    pub(crate) timeline: Option<NonZeroU64>,
+    // 가드의 수명을 덮는 span. 이 가드의 이벤트는 이 안에서 나간다.
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    span: tracing::Span,
+}
+
+impl<C: GuardContext> GuardState<C> {
+    pub(crate) fn new(
+        config: &Arc<LockConfig>,
+        entry: &Arc<Entry>,
+        access: AccessKind,
+        context: C,
+        held: Option<Held>,
+        acquired: Option<LockEvent>,
+        location: &'static Location<'static>,
+    ) -> Self {
+        // 시간을 재지 않으면 점유 시간 하한도 적용할 수 없다.
+        let floor = config.min_logged_hold.filter(|_| config.timed());
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let span = crate::trace::guard_span(
+            config,
+            access,
+            location,
+            acquired.as_ref().and_then(|acquired| acquired.waited),
+        );
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let _entered = span.enter();
+        let deferred = match (acquired, floor) {
+            (Some(acquired), None) => {
+                event::emit(config, acquired);
+                None
+            }
+            (acquired, _) => acquired,
+        };
+        GuardState {
+            config: config.clone(),
+            entry: entry.clone(),
+            access,
+            start_time: config.timed().then(|| config.now()),
+            #[cfg(debug_assertions)]
+            in_runtime: tokio::runtime::Handle::try_current().is_ok(),
+            context,
+            held,
+            reason: ReleaseReason::Dropped,
+            completed: false,
+            deferred,
+            acquired_at: location,
+            priority: None,
+            #[cfg(feature = "access-count")]
+            accesses: std::sync::atomic::AtomicU64::new(0),
+            #[cfg(debug_assertions)]
+            live: entry.guard_created(access, location),
+            holder: entry.track_holder(access, location, config),
+            peek: false,
+            site: None,
+            deadlock: crate::deadlock::held(entry.id, access, location),
+            timeline: None,
+            #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+            span: span.clone(),
+        }
+    }
+
+    /// 해제 없이 `from` 에서 `to` 로 바뀐 것을 기록한다. 보류된 획득 이벤트는 먼저 내보낸다.
+    #[track_caller]
+    fn transition(&mut self, from: AccessKind, to: AccessKind) {
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let span = self.span.clone();
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let _entered = span.enter();
+        self.access = to;
+        if let Some(holder) = &self.holder {
+            holder.set_access(to);
+        }
+        if let Some(site) = &self.site {
+            site.transition(from, to);
+        }
+        if let Some(deadlock) = &self.deadlock {
+            deadlock.set_access(to);
+        }
+        if let Some(acquisition) = self.timeline {
+            let (config, lock_id) = (&*self.config, self.entry.id);
+            timeline::released(config, lock_id, from, self.acquired_at, acquisition);
+            self.timeline = timeline::acquired(config, lock_id, to, self.acquired_at);
+        }
+        if let Some(acquired) = self.deferred.take() {
+            event::emit(&self.config, acquired);
+        }
+        log_call_info!(
+            &self.config,
+            EventKind::Transition,
+            to,
+            context: self.context.render(),
+            previous: Some(from),
+        );
+    }
+
+    /// 쓰기 가드가 놓지 않고 읽기 가드로 바뀐 것을 기록한다. 안쪽 가드는 부른 쪽이 바꾼다.
+    #[track_caller]
+    pub(crate) fn downgrade(&mut self) {
+        self.entry.stats.record_downgrade();
+        #[cfg(feature = "tokio-console")]
+        crate::console::downgraded(&self.entry);
+        // 쓴 값은 이제 읽을 수 있으므로 `wait_while` 을 깨운다.
+        self.entry.stats.mark_written();
+        self.entry.written.notify_waiters();
+        self.transition(AccessKind::Write, AccessKind::Read);
+    }
+
+    /// 명시적 해제로 표시하고 등록된 비동기 해제 훅을 차례로 기다린다. 락은 아직 잡혀 있다.
+    async fn run_release_hooks(&mut self) {
+        self.reason = ReleaseReason::Explicit;
+        for hook in &self.config.release_hooks {
+            hook(self.access).await;
+        }
+    }
+
+    fn release(&mut self) {
+        let access = self.access;
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let span = self.span.clone();
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let _entered = span.enter();
+        #[cfg(debug_assertions)]
+        self.entry.guard_dropped(self.live);
+        // 안쪽 가드가 풀리기 전에 그래프에서 내린다.
+        self.deadlock = None;
+        let duration = self.start_time.map(|start| self.held_for(start, access));
+        self.entry
+            .stats
+            .record_release(access, duration.unwrap_or_default());
+        if let Some(duration) = duration {
+            self.entry
+                .slowest
+                .record(SlowBy::Hold, access, self.acquired_at, duration);
+        }
+        #[cfg(feature = "tokio-console")]
+        crate::console::released(&self.entry, access);
+        #[cfg(feature = "metrics")]
+        crate::metrics::released(&self.entry, access, duration);
+        if let Some(site) = &self.site {
+            site.released(access, duration);
+        }
+        if let Some(acquisition) = self.timeline {
+            timeline::released(
+                &self.config,
+                self.entry.id,
+                access,
+                self.acquired_at,
+                acquisition,
+            );
+        }
+        if access == AccessKind::Write {
+            self.entry.stats.mark_written();
+            self.entry.written.notify_waiters();
+        }
+        if let Some(duration) = duration {
+            if access == AccessKind::Read {
+                self.warn_if_blocking_writer(duration);
+            }
+            self.warn_if_priority_inverted(access, duration);
+        }
+        #[cfg(debug_assertions)]
+        self.warn_if_runtime_left(access);
+        let depth = self.held.as_ref().map(task::pop);
+        // 시간을 재지 않으면 획득 이벤트만 남긴다.
+        let Some(duration) = duration else {
+            return;
+        };
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        crate::trace::record_held(&span, duration);
+        if self
+            .config
+            .min_logged_hold
+            .is_some_and(|floor| duration < floor)
+        {
+            return;
+        }
+        if let Some(acquired) = self.deferred.take() {
+            event::emit(&self.config, acquired);
+        }
+        if self.peek {
+            if !self.config.quiet_peeks {
+                log_call_info!(
+                    &self.config,
+                    EventKind::Peek,
+                    access,
+                    location: self.acquired_at,
+                    held: Some(duration),
+                );
+            }
+            return;
+        }
+        log_call_info!(
+            &self.config,
+            EventKind::Release,
+            access,
+            held: Some(duration),
+            context: self.context.render(),
+            depth: depth,
+            reason: Some(self.release_reason()),
+            acquired_at: Some(self.acquired_at),
+            priority: self.priority,
+            accesses: self.accesses(),
+        );
+    }
+
+    // `tag_early_exits` 면 그냥 드롭된 가드가 어떤 경로로 빠져나갔는지 가린다.
+    fn release_reason(&self) -> ReleaseReason {
+        match self.reason {
+            ReleaseReason::Dropped if self.config.tag_early_exits => {
+                if std::thread::panicking() {
+                    ReleaseReason::Unwound
+                } else if self.completed {
+                    ReleaseReason::Dropped
+                } else {
+                    ReleaseReason::EarlyExit
+                }
+            }
+            reason => reason,
+        }
+    }
+
+    // 역참조 한 번을 센다. 기능이 꺼져 있으면 아무것도 하지 않는다.
+    #[inline]
+    pub(crate) fn touch(&self) {
+        #[cfg(feature = "access-count")]
+        self.accesses
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    fn accesses(&self) -> Option<u64> {
+        #[cfg(feature = "access-count")]
+        return Some(self.accesses.load(std::sync::atomic::Ordering::Relaxed));
+        #[cfg(not(feature = "access-count"))]
+        None
+    }
+
+    // 시계가 거꾸로 가면 점유 시간을 0 으로 보고 그 사실을 남긴다.
+    fn held_for(&self, start_time: Instant, access: AccessKind) -> Duration {
+        let now = self.config.now();
+        match now.checked_duration_since(start_time) {
+            Some(duration) => duration,
+            None => {
+                log_call_info!(
+                    &self.config,
+                    EventKind::Warning,
+                    access,
+                    message: Some(format!(
+                        "clock went backwards by {:?}; hold time recorded as zero",
+                        start_time - now
+                    )),
+                );
+                Duration::ZERO
+            }
+        }
+    }
+
+    /// 런타임 안에서 얻은 가드가 런타임 밖(예: 런타임을 내린 뒤)에서 드롭되면 경고한다.
+    ///
+    /// 테스트마다 런타임을 새로 만들 때 가드가 런타임보다 오래 살아남는 실수를 잡는다.
+    #[cfg(debug_assertions)]
+    fn warn_if_runtime_left(&self, access: AccessKind) {
+        if self.in_runtime && tokio::runtime::Handle::try_current().is_err() {
+            log_call_info!(
+                &self.config,
+                EventKind::Warning,
+                access,
+                message: Some(
+                    "guard acquired inside a tokio runtime was dropped outside of it".to_string()
+                ),
+            );
+        }
+    }
+
+    /// 더 높은 우선순위의 writer 가 기다리는 동안 점유했다면 경고한다.
+    ///
+    /// 우선순위가 없는 가드는 0 으로 본다. 겹친 시간이 `warn_reader_blocking_writer` 의 유예를
+    /// 넘어야 하며, 유예가 없으면 조금이라도 겹치면 경고한다.
+    fn warn_if_priority_inverted(&self, access: AccessKind, duration: Duration) {
+        let stats = &self.entry.stats;
+        let (Some(waiting), Some(waited)) = (stats.waiting_priority(), stats.writer_waited())
+        else {
+            return;
+        };
+        let mine = self.priority.unwrap_or(0);
+        let grace = self.config.reader_blocking_grace.unwrap_or_default();
+        if waiting > mine && waited.min(duration) > grace {
+            log_call_info!(
+                &self.config,
+                EventKind::Warning,
+                access,
+                held: Some(duration),
+                message: Some(format!(
+                    "priority inversion: a priority-{} writer waited {:?} behind this priority-{} holder",
+                    waiting, waited, mine
+                )),
+                priority: self.priority,
+            );
+        }
+    }
+
+    fn warn_if_blocking_writer(&self, duration: Duration) {
+        let (Some(grace), Some(waited)) = (
+            self.config.reader_blocking_grace,
+            self.entry.stats.writer_waited(),
+        ) else {
+            return;
+        };
+        // writer 대기와 이 reader 의 점유가 겹친 시간만 본다.
+        if waited.min(duration) > grace {
+            log_call_info!(
+                &self.config,
+                EventKind::Warning,
+                AccessKind::Read,
+                held: Some(duration),
+                message: Some(format!(
+                    "held a read lock while a writer waited for {:?}",
+                    waited
+                )),
+            );
+        }
+    }
+}
+
+// 가드 쪽에는 `Drop` 이 없으므로 `downgrade` 가 필드를 그대로 옮길 수 있다.
+impl<C: GuardContext> Drop for GuardState<C> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// `TokioRwLockTrace::read` 가 반환하는 읽기 가드. 드롭 시 점유 시간을 출력한다.
+///
+/// `C` 는 `read_ctx` 로 붙인 컨텍스트이며 기본값 `()` 는 아무것도 기록하지 않는다.
+///
+/// 가드는 언제나 `Unpin` 이다. pin 된 future 나 구조체 안에 담아 `.await` 를 건너도 되고,
+/// 가드 필드를 구조적으로 pin 할 필요가 없으므로 `Pin<&mut Self>` 에서는 `get_mut` 으로 꺼내 쓴다.
+/// 가드는 락을 빌리므로 같은 구조체가 락도 가지려면 `Arc<TokioRwLockTrace<T>>` 를 clone 해서 async
+/// 블록으로 옮기는 식으로 락의 수명이 가드보다 길게 한다.
+pub struct LoggingRwLockReadGuard<'a, T, C: GuardContext = ()> {
+    // 필드는 선언 순서대로 드롭된다. 해제 기록은 락을 쥔 채로 남기고, 허가는 락을 놓은 뒤에
+    // 돌려준다.
+    pub(crate) state: GuardState<C>,
+    pub(crate) guard: RwLockReadGuard<'a, T>,
+    pub(crate) _permit: Option<ReadPermit>,
+}
+
+impl<'a, T, C: GuardContext> LoggingRwLockReadGuard<'a, T, C> {
+    /// 가드를 소비하면서 명시적 해제로 표시된 해제 이벤트를 남긴다.
+    ///
+    /// `drop(guard)` 과 같지만 로그에서 스코프 끝의 암묵적 해제와 구분된다.
+    pub fn release(mut self) {
+        self.state.reason = ReleaseReason::Explicit;
+    }
+
+    /// `LockConfig::on_release_async` 로 등록한 비동기 훅을 기다린 뒤 가드를 놓는다.
+    ///
+    /// 그냥 드롭해도 락은 풀리지만 비동기 훅은 실행되지 않는다.
+    pub async fn release_async(mut self) {
+        self.state.run_release_hooks().await;
+    }
+
+    /// 임계 구역이 끝까지 진행됐다고 표시한다. `LockConfig::tag_early_exits` 가 켜져 있으면
+    /// 이 표시 없이 드롭된 가드는 조기 종료로 기록된다.
+    pub fn complete(&mut self) {
+        self.state.completed = true;
+    }
+}
+
+impl<'a, T, C: GuardContext> LoggingRwLockReadGuard<'a, T, C> {
+    /// 가드를 넘기지 않고 값만 빌려준다. `&*guard` 와 같다.
+    pub fn reborrow(&self) -> &T {
+        self.state.touch();
+        &self.guard
+    }
+}
+
+impl<'a, T, C: GuardContext> Deref for LoggingRwLockReadGuard<'a, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.touch();
+        &self.guard
+    }
+}
+
+/// `AsRef<T>` 로 받는 제네릭 함수에 가드를 그대로 넘길 수 있다. `Deref` 와 같다.
+impl<T, C: GuardContext> AsRef<T> for LoggingRwLockReadGuard<'_, T, C> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+// 컨텍스트 `C` 를 구조적으로 pin 하지 않으므로 (`Pin<&mut C>` 를 내주지 않는다) 언제나 Unpin 이다.
+impl<T, C: GuardContext> Unpin for LoggingRwLockReadGuard<'_, T, C> {}
+impl<T, C: GuardContext> Unpin for LoggingRwLockWriteGuard<'_, T, C> {}
+
+/// `TokioRwLockTrace::write` 가 반환하는 쓰기 가드. 드롭 시 점유 시간을 출력한다.
+///
+/// 읽기 가드처럼 언제나 `Unpin` 이다.
+pub struct LoggingRwLockWriteGuard<'a, T, C: GuardContext = ()> {
+    // 읽기 가드와 같은 순서로 드롭된다.
+    pub(crate) state: GuardState<C>,
+    pub(crate) guard: RwLockWriteGuard<'a, T>,
+    // `with_max_readers` 면 `downgrade` 한 읽기 가드가 이어받을 허가
+    pub(crate) _permit: Option<ReadPermit>,
+}
+
+impl<'a, T, C: GuardContext> LoggingRwLockWriteGuard<'a, T, C> {
+    /// 가드를 소비하면서 명시적 해제로 표시된 해제 이벤트를 남긴다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(vec![3, 1, 2]);
+    /// let mut numbers = lock.write().await;
+    /// numbers.sort();
+    /// let snapshot = numbers.clone();
+    /// numbers.release(); // 오래 걸리는 계산 전에 해제
+    /// assert_eq!(snapshot, [1, 2, 3]);
+    /// # }
+    /// ```
+    pub fn release(mut self) {
+        self.state.reason = ReleaseReason::Explicit;
+    }
+
+    /// `LockConfig::on_release_async` 로 등록한 비동기 훅을 기다린 뒤 가드를 놓는다.
+    ///
+    /// 그냥 드롭해도 락은 풀리지만 비동기 훅은 실행되지 않는다.
+    pub async fn release_async(mut self) {
+        self.state.run_release_hooks().await;
+    }
+
+    /// 임계 구역이 끝까지 진행됐다고 표시한다. `LockConfig::tag_early_exits` 가 켜져 있으면
+    /// 이 표시 없이 드롭된 가드는 조기 종료로 기록된다.
+    pub fn complete(&mut self) {
+        self.state.completed = true;
+    }
+}
+
+impl<'a, T, C: GuardContext> LoggingRwLockWriteGuard<'a, T, C> {
+    /// 가드를 넘기지 않고 값만 가변으로 빌려준다. `&mut *guard` 와 같다.
+    ///
+    /// 가드 자체를 다른 함수로 옮겼다가 돌려받아도 하나의 점유로 기록된다. 해제 이벤트의
+    /// `acquired_at` 이 처음 획득한 위치를 가리킨다.
+    ///
+    /// ```
+    /// use tracing_lock::{LoggingRwLockWriteGuard, TokioRwLockTrace};
+    ///
+    /// fn stage(mut guard: LoggingRwLockWriteGuard<'_, Vec<u32>>) -> LoggingRwLockWriteGuard<'_, Vec<u32>> {
+    ///     guard.push(1);
+    ///     guard
+    /// }
+    ///
+    /// fn fill(items: &mut Vec<u32>) {
+    ///     items.push(2);
+    /// }
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(Vec::new());
+    /// let mut guard = stage(lock.write().await);
+    /// fill(guard.reborrow());
+    /// assert_eq!(*guard, [1, 2]);
+    /// # }
+    /// ```
+    pub fn reborrow(&mut self) -> &mut T {
+        self.state.touch();
+        &mut self.guard
+    }
+
+    /// 쓰기 락을 놓지 않고 읽기 가드로 바꾼다. 다른 writer 가 끼어들 틈이 없다.
+    ///
+    /// 해제/획득 쌍 대신 `write→read downgrade` 전이 이벤트 하나가 남고, 점유 시간은 처음
+    /// 획득한 때부터 읽기 가드가 해제될 때까지로 한 번 기록된다. `with_max_readers` 면 쓰기 가드가
+    /// 잡아 둔 허가를 바뀐 읽기 가드가 이어받는다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(1);
+    /// let mut guard = lock.write().await;
+    /// *guard += 1;
+    /// let reader = guard.downgrade();
+    /// assert_eq!(*reader, 2);
+    /// assert!(lock.try_write().is_err());
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn downgrade(self) -> LoggingRwLockReadGuard<'a, T, C> {
+        let Self {
+            mut state,
+            guard,
+            _permit,
+        } = self;
+        state.downgrade();
+        LoggingRwLockReadGuard {
+            state,
+            guard: guard.downgrade(),
+            _permit,
+        }
+    }
+}
+
+impl<'a, T, C: GuardContext> Deref for LoggingRwLockWriteGuard<'a, T, C> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.state.touch();
+        &self.guard
+    }
+}
+
+impl<'a, T, C: GuardContext> DerefMut for LoggingRwLockWriteGuard<'a, T, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.state.touch();
+        &mut self.guard
+    }
+}
+
+impl<T, C: GuardContext> AsRef<T> for LoggingRwLockWriteGuard<'_, T, C> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T, C: GuardContext> AsMut<T> for LoggingRwLockWriteGuard<'_, T, C> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+/// 가드에 붙는 사용자 컨텍스트. 컨텍스트가 없는 경로는 `()` 로 비용이 없다.
+pub trait GuardContext {
+    fn render(&self) -> Option<String>;
+}
+
+impl GuardContext for () {
+    fn render(&self) -> Option<String> {
+        None
+    }
+}
+
+/// `Display` 로 이벤트에 기록되는 컨텍스트 (예: 요청 id)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Context<C>(pub C);
+
+impl<C: fmt::Display> GuardContext for Context<C> {
+    fn render(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    sync::{Mutex, OnceLock, PoisonError},
+    time::Instant,
+};
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::future::{ReadFuture, WriteFuture};
+use crate::lock::TokioRwLockTrace;
+use crate::stats::LockStatsSnapshot;
+
+/// 처음 접근할 때 값을 만들어 계측 락을 생성하는 래퍼. 전역 `static` 에 쓸 수 있다.
+///
+/// 초기화는 `OnceLock` 으로 한 번만 일어나며, 초기화에 걸린 시간은 획득 이벤트와 별도로
+/// `Initialized` 이벤트로 남는다.
+///
+/// ```
+/// use tracing_lock::LazyRwLockTrace;
+///
+/// static SETTINGS: LazyRwLockTrace<Vec<String>> = LazyRwLockTrace::new(|| vec!["default".into()]);
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// assert!(!SETTINGS.is_initialized());
+/// assert_eq!(SETTINGS.read().await.len(), 1);
+/// SETTINGS.write().await.push("override".into());
+/// # }
+/// ```
+pub struct LazyRwLockTrace<T, F = fn() -> T> {
+    lock: OnceLock<TokioRwLockTrace<T>>,
+    init: Mutex<Option<F>>,
+    config: Option<LockConfig>,
+}
+
+impl<T, F: FnOnce() -> T> LazyRwLockTrace<T, F> {
+    pub const fn new(init: F) -> Self {
+        LazyRwLockTrace {
+            lock: OnceLock::new(),
+            init: Mutex::new(Some(init)),
+            config: None,
+        }
+    }
+
+    /// `new` 와 같다.
+    pub const fn from_fn(init: F) -> Self {
+        Self::new(init)
+    }
+
+    /// 초기화될 때 적용할 설정
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.lock.get().is_some()
+    }
+
+    /// 초기화된 뒤의 통계. 아직 초기화되지 않았다면 `None`.
+    pub fn stats(&self) -> Option<LockStatsSnapshot> {
+        self.lock.get().map(TokioRwLockTrace::stats)
+    }
+
+    /// 필요하면 초기화한 뒤 내부 계측 락을 돌려준다.
+    pub fn get(&self) -> &TokioRwLockTrace<T> {
+        self.get_or_init(AccessKind::Read)
+    }
+
+    pub fn read(&self) -> ReadFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        self.get_or_init(AccessKind::Read).read()
+    }
+
+    pub fn write(&self) -> WriteFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        self.get_or_init(AccessKind::Write).write()
+    }
+
+    fn get_or_init(&self, access: AccessKind) -> &TokioRwLockTrace<T> {
+        self.lock.get_or_init(|| {
+            let init = self
+                .init
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+                .expect("LazyRwLockTrace initializer panicked previously");
+            let started = Instant::now();
+            let value = init();
+            let elapsed = started.elapsed();
+            let config = self.config.clone().unwrap_or_else(LockConfig::inherited);
+            log_call_info!(
+                &config,
+                EventKind::Initialized,
+                access,
+                message: Some(format!("lazily initialized in {:?}", elapsed)),
+            );
+            TokioRwLockTrace::new(value).with_config(config)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn first_access_initializes_once_under_concurrent_readers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            LazyRwLockTrace::new({
+                let calls = calls.clone();
+                move || {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    42
+                }
+            })
+            .with_config(LockConfig::new().observer(events.clone())),
+        );
+        assert!(!lock.is_initialized());
+        assert_eq!(lock.stats(), None);
+
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let lock = lock.clone();
+                tokio::spawn(async move { *lock.read().await })
+            })
+            .collect();
+        for reader in readers {
+            assert_eq!(reader.await.unwrap(), 42);
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(lock.stats().unwrap().reads, 8);
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds
+                .iter()
+                .filter(|k| **k == EventKind::Initialized)
+                .count(),
+            1
+        );
+        assert_eq!(kinds[0], EventKind::Initialized);
+    }
+}
@@ -0,0 +1,247 @@
+//! `tokio::sync::RwLock` 의 획득/해제 시점과 점유 시간을 기록하는 래퍼.
+//!
+//! ```
+//! use tracing_lock::{LockConfig, TokioRwLockTrace};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let lock = TokioRwLockTrace::new(vec![1, 2, 3]).with_config(LockConfig::new().name("numbers"));
+//!
+//! {
+//!     let numbers = lock.read().await;
+//!     assert_eq!(numbers.len(), 3);
+//! } // 여기서 해제 로그가 출력된다.
+//!
+//! lock.write().await.push(4);
+//! # }
+//! ```
+//!
+//! `try_read`/`try_write`, `read_owned`/`write_owned`(`Arc` 를 쥐는 가드), `blocking_read`/
+//! `blocking_write`, 쓰기 가드의 `downgrade` 처럼 tokio `RwLock` 의 나머지 API 도 같은 계측을 거친다.
+//! 락을 잡지 않는 `get_mut`/`into_inner` 는 이벤트를 남기지 않는다.
+//!
+//! # 다른 primitive
+//!
+//! `TracedMutex` 와 `TracedSemaphore` 는 tokio 의 `Mutex`/`Semaphore` 를 같은 설정, 통계, 이벤트로
+//! 계측한다. 스레드를 막는 락은 기능으로 켠다. `TracedRwLock` 은 `TokioRwLockTrace` 의 다른 이름이다.
+//! (아래 "계측 끄기" 참고)
+//!
+//! - `std-sync`: `TracedStdMutex`, `TracedStdRwLock`. 오염된 락도 계측된 가드를 `PoisonError` 에
+//!   담아 돌려준다.
+//! - `parking-lot`: `TracedParkingLotMutex`, `TracedParkingLotRwLock`.
+//!
+//! # 감시
+//!
+//! `LockConfig::warn_if_wait_exceeds`/`warn_if_held_exceeds` 를 주면 감시 스레드 하나가 문턱을 넘긴
+//! 대기와 점유를, 가드가 드롭되기 전에 호출 위치와 지금까지의 시간을 담은 `Warning` 으로 알린다.
+//! `on_watchdog` 로 이 경고만 받는 콜백을 달 수 있다.
+//!
+//! # 교착 검사
+//!
+//! `deadlock::set_enabled(true)` 동안에는 태스크마다 쥔 가드와 기다리는 획득으로 대기 그래프를 만들고,
+//! 여러 락에 걸친 순환이 생기면 간선마다의 호출 위치를 담아 `Deadlock` 이벤트와
+//! `deadlock::on_deadlock` 콜백으로 알린다.
+//!
+//! # 계측 끄기
+//!
+//! `set_enabled(false)` 는 살아 있는 프로세스에서 모든 락의 이벤트와 시계 읽기를 멈춘다. 가드
+//! 래퍼와 통계 카운터는 남는다. 비용을 아예 없애려면 기본 기능 `trace` 를 끈다. 그러면
+//! `TracedRwLock`, `TracedMutex`, `TracedSemaphore` 와 `std-sync`/`parking-lot` 래퍼는 원래
+//! primitive 와 그 가드를 얇게 감싸는 `Passthrough*` 타입이 된다. 가드 타입을
+//! `TracedRwLockReadGuard`, `TracedMutexGuard` 처럼 적어 두면 두 빌드에서 같은 코드가 컴파일된다.
+//! 공개 API 는 모두 같은 이름으로 남는다. 설정 메서드와 `report`, `slowest` 같은 진단 메서드는
+//! 아무것도 하지 않거나 빈 값을 돌려주고, `downgrade`, `wait_while`, `read_fresh` 처럼 동작이 있는
+//! 메서드는 계측 없이 같은 동작을 한다. `TokioRwLockTrace` 는 기능과 상관없이 계측된다.
+//!
+//! # 출력 백엔드
+//!
+//! 백엔드는 한 번에 하나만 켜진다. `LockConfig::silent()` 로 락마다 출력을 끌 수 있으며, 받을 곳이
+//! 없는 이벤트(구독자나 로거가 거르는 레벨 포함)는 만들지 않는다.
+//!
+//! - `tracing-backend` (기본): `tracing` 이벤트로 보낸다. target 은 `tracing_lock` 이며, 사용 규칙
+//!   위반은 `error`, 경고는 `warn`, 락 생성/소멸은 `trace`, 나머지는 `debug` 레벨이다. 락
+//!   이름(`lock`), 접근 종류(`access`), 호출 위치(`caller.file`/`caller.line`), 대기/점유
+//!   시간(`waited_us`/`held_us`, 마이크로초) 같은 필드가 구조화된 채로 남아 구독자에서 거르거나
+//!   모을 수 있다. 가드마다 수명을 덮는 `lock_guard` span(`debug`)을 열고 획득/해제 이벤트를 그
+//!   안에서 내보내므로, 락을 잡은 쪽의 span 아래에 이어 붙는다. `OutputFormat` 은 쓰지 않는다.
+//! - `log-backend`: `log` 크레이트의 매크로로 보낸다. target 과 레벨은 위와 같고, 필드는 메시지
+//!   안에 평탄화된다. 기본 기능과 함께 켜도 이쪽이 우선하므로 `default-features` 를 끄지 않아도
+//!   된다. `lock_guard` span 은 열지 않는다.
+//! - 백엔드 기능을 모두 끄면 사람이 읽는 형식으로 stdout 에 출력한다. 로컬 디버깅용 대체 경로다.
+//!   `LockConfig::format(OutputFormat::Pretty)` 를 고르면 열을 맞추고 터미널에서는 색을 입힌다.
+//!
+//! # 지표 내보내기
+//!
+//! `LockConfig::track_sites()` 를 켠 락은 이름과 `read()`/`write()` 호출 위치마다 획득 수,
+//! 대기/점유 시간 히스토그램, 지금 쥔 가드 수를 모은다. `sites::snapshot()` 으로 읽을 수 있고,
+//! 아래 기능이 켜져 있으면 `site` 레이블(`file:line`)이 붙은 `tracing_lock_site_*` 시계열로도
+//! 나간다.
+//!
+//! - `prometheus`: `registry::prometheus_encode()` 가 살아 있는 락의 획득 수, 대기자 수,
+//!   누적 점유 시간, 최대 동시 reader 수를 Prometheus 텍스트 형식으로 돌려준다.
+//!
+//! - `metrics`: 획득마다 `tracing_lock_acquisitions_total` counter 를, 기다린 시간과 쥔 시간을
+//!   `tracing_lock_wait_seconds`/`tracing_lock_hold_seconds` histogram(초 단위)으로 `metrics`
+//!   파사드에 보낸다. 레이블은 `lock`(락 이름)과 `kind`(`read`/`write`)다. 설치된 recorder 가
+//!   없으면 아무 일도 하지 않는다.
+//!
+//! `timeline::start()` 와 `timeline::stop()` 사이에는 모든 락의 대기 시작, 획득, 해제를 태스크와
+//! 호출 위치와 함께 메모리에 쌓는다. 받은 `Timeline` 은 Chrome 추적 형식(Perfetto)이나 줄마다
+//! JSON 으로 써서 부하 테스트 뒤에 시간축으로 볼 수 있다.
+//!
+//! # 분산 추적
+//!
+//! - `otel`: 획득/해제 이벤트의 `trace_id`/`span_id` 에 현재 OpenTelemetry 컨텍스트의 id 를
+//!   붙인다. `tracing-opentelemetry` 레이어가 있으면 현재 `tracing` span 의 컨텍스트를, 없으면
+//!   `opentelemetry::Context::current()` 를 쓴다. 유효한 span 이 없으면 비워 둔다.
+//!
+//! # tokio-console
+//!
+//! - `tokio-console`: 락마다 `runtime.resource` span 을 열고 획득/해제를
+//!   `runtime::resource::state_update` 이벤트로 남겨 `tokio-console` 의 리소스 뷰에 보이게 한다.
+//!   필드는 tokio 의 `RwLock` 계측과 같다. 콘솔 레이어가 `runtime=trace` 를 받아야 한다.
+//!
+//! # 취소
+//!
+//! - `cancel`: `read_with_cancel`/`write_with_cancel` 이 `tokio_util` 의 `CancellationToken` 이
+//!   먼저 취소되면 기다리기를 그만두고 `Err(Cancelled)` 를 돌려준다.
+//!
+//! # derive
+//!
+//! - `derive`: `#[derive(TracedLocks)]` 로 구조체의 락 필드 통계를 필드 이름별로 모으는
+//!   `lock_report()` 와, 락에 필드 이름을 붙이는 `name_locks()` 를 만든다.
+//!
+//! # 테스트 전용 기능
+//!
+//! - `chaos`: `TokioRwLockTrace::with_chaos(seed)` 로 획득 순서를 시드 기반으로 흔든다.
+//!   운영 빌드에서는 켜지 말 것.
+//! - `dry-run`: `read_dry_run`/`write_dry_run` 이 계측은 모두 하되 비어 있는 더미 락을 잡는다.
+//!   `cargo bench --features dry-run` 으로 계측 비용만 따로 잴 수 있다.
+//! - `access-count`: 가드를 역참조할 때마다 세어 해제 이벤트의 `accesses` 에 남긴다. 오래 쥔
+//!   가드가 실제로 값을 만졌는지 볼 때 쓴다. 역참조마다 원자 연산이 하나 더 든다.
+//! - `test-util`: `testing::CapturingObserver` 가 이벤트를 모아 두고 `assert_acquired`,
+//!   `assert_hold_at_least` 같은 단언을 제공한다. 락에는 읽기가 막히지 않음을 단언하는
+//!   `assert_read_uncontended` 가 생긴다.
+
+#[macro_use]
+mod macros;
+
+mod backoff;
+mod borrowed;
+mod bundle;
+#[cfg(feature = "cancel")]
+mod cancel;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod clock;
+mod condvar;
+mod config;
+#[cfg(feature = "tokio-console")]
+mod console;
+pub mod deadlock;
+mod event;
+mod future;
+mod guard;
+mod lazy;
+mod lock;
+mod maybe;
+#[cfg(feature = "metrics")]
+mod metrics;
+#[cfg(feature = "trace")]
+mod mutex;
+mod oneshot;
+#[cfg(feature = "otel")]
+mod otel;
+mod owned;
+#[cfg(all(feature = "parking-lot", feature = "trace"))]
+mod parking;
+mod passthrough;
+#[cfg(feature = "trace")]
+mod probe;
+pub mod ranked;
+mod rate_limit;
+pub mod registry;
+mod scoped;
+#[cfg(feature = "trace")]
+mod semaphore;
+pub mod sites;
+mod slowest;
+mod static_lock;
+mod stats;
+#[cfg(all(feature = "std-sync", feature = "trace"))]
+mod std_sync;
+mod task;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod timeline;
+#[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+mod trace;
+mod traced;
+mod watchdog;
+mod weak;
+
+pub use backoff::Contended;
+pub use borrowed::BorrowedRwLockTrace;
+pub use bundle::try_lock_all;
+#[cfg(feature = "cancel")]
+pub use cancel::Cancelled;
+pub use clock::Clock;
+pub use config::{set_default_config, set_enabled, LockConfig};
+pub use event::{
+    set_location_filter, AccessKind, EventKind, LockEvent, LockObserver, OutputFormat,
+    ReleaseReason,
+};
+pub use future::{ReadFuture, WriteFuture};
+pub use guard::{Context, GuardContext, LoggingRwLockReadGuard, LoggingRwLockWriteGuard};
+pub use lazy::LazyRwLockTrace;
+pub use lock::{
+    TokioRwLockTrace, TracedRwLock, TracedRwLockReadGuard, TracedRwLockWriteGuard, TryReadError,
+};
+pub use maybe::MaybeTraced;
+#[cfg(feature = "trace")]
+pub use mutex::{TracedMutex, TracedMutexGuard};
+pub use oneshot::OneShotWriteLock;
+pub use owned::{OwnedLoggingRwLockReadGuard, OwnedLoggingRwLockWriteGuard};
+#[cfg(all(feature = "parking-lot", feature = "trace"))]
+pub use parking::{
+    TracedParkingLotMutex, TracedParkingLotMutexGuard, TracedParkingLotReadGuard,
+    TracedParkingLotRwLock, TracedParkingLotWriteGuard,
+};
+pub use passthrough::{
+    PassthroughBorrowedRwLock, PassthroughGuard, PassthroughMutex, PassthroughOwnedReadGuard,
+    PassthroughOwnedWriteGuard, PassthroughReadGuard, PassthroughRwLock, PassthroughSemaphore,
+    PassthroughWeakRwLock, PassthroughWriteGuard,
+};
+#[cfg(feature = "parking-lot")]
+pub use passthrough::{PassthroughParkingLotMutex, PassthroughParkingLotRwLock};
+#[cfg(feature = "std-sync")]
+pub use passthrough::{PassthroughStdMutex, PassthroughStdRwLock};
+#[cfg(not(feature = "trace"))]
+pub use passthrough::{TracedMutex, TracedMutexGuard, TracedSemaphore, TracedSemaphorePermit};
+#[cfg(all(feature = "parking-lot", not(feature = "trace")))]
+pub use passthrough::{
+    TracedParkingLotMutex, TracedParkingLotMutexGuard, TracedParkingLotReadGuard,
+    TracedParkingLotRwLock, TracedParkingLotWriteGuard,
+};
+#[cfg(all(feature = "std-sync", not(feature = "trace")))]
+pub use passthrough::{
+    TracedStdMutex, TracedStdMutexGuard, TracedStdReadGuard, TracedStdRwLock, TracedStdWriteGuard,
+};
+#[cfg(feature = "trace")]
+pub use probe::TracedGuard;
+#[cfg(feature = "trace")]
+pub use semaphore::{TracedSemaphore, TracedSemaphorePermit};
+pub use slowest::{SlowAcquisition, SlowBy, SLOWEST_KEPT};
+pub use static_lock::StaticRwLockTrace;
+pub use stats::{LockStatsDelta, LockStatsSnapshot};
+#[cfg(all(feature = "std-sync", feature = "trace"))]
+pub use std_sync::{
+    TracedStdMutex, TracedStdMutexGuard, TracedStdReadGuard, TracedStdRwLock, TracedStdWriteGuard,
+};
+pub use task::{
+    enter_frame, set_nesting_depth, set_thread_name_fallback, with_operation_id, StackFrame,
+};
+pub use traced::{LockBacking, TracedLock, TracedReadGuard, TracedWriteGuard};
+#[cfg(feature = "derive")]
+pub use tracing_lock_derive::TracedLocks;
+pub use weak::WeakRwLockTrace;
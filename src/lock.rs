@@ -0,0 +1,2510 @@
+use std::{error::Error, fmt::Display, future::Future, sync::Arc, time::Duration};
+use tokio::sync::{RwLock, Semaphore, TryLockError};
+
+use crate::borrowed::BorrowedRwLockTrace;
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::future::{Fifo, ReadFuture, WriteFuture};
+use crate::guard::{
+    Context, GuardContext, GuardState, LoggingRwLockReadGuard, LoggingRwLockWriteGuard,
+};
+use crate::registry::{self, Entry};
+use crate::slowest::{SlowAcquisition, SlowBy};
+use crate::stats::LockStatsSnapshot;
+use crate::task;
+
+/// `TracedMutex`, `TracedSemaphore` 와 이름을 맞춘 `TokioRwLockTrace`.
+///
+/// `trace` 기능(기본값)을 끄면 계측 없는 `PassthroughRwLock` 이 된다.
+#[cfg(feature = "trace")]
+pub type TracedRwLock<T> = TokioRwLockTrace<T>;
+/// `trace` 기능이 꺼져 있으므로 계측 없는 `PassthroughRwLock` 이다.
+#[cfg(not(feature = "trace"))]
+pub type TracedRwLock<T> = crate::passthrough::PassthroughRwLock<T>;
+
+/// `TracedRwLock::read` 가 돌려주는 가드. `trace` 기능을 끄면 `PassthroughReadGuard` 다.
+#[cfg(feature = "trace")]
+pub type TracedRwLockReadGuard<'a, T> = LoggingRwLockReadGuard<'a, T>;
+/// `TracedRwLock::read` 가 돌려주는 가드. `trace` 기능을 끄면 `PassthroughReadGuard` 다.
+#[cfg(not(feature = "trace"))]
+pub type TracedRwLockReadGuard<'a, T> = crate::passthrough::PassthroughReadGuard<'a, T>;
+
+/// `TracedRwLock::write` 가 돌려주는 가드. `trace` 기능을 끄면 `PassthroughWriteGuard` 다.
+#[cfg(feature = "trace")]
+pub type TracedRwLockWriteGuard<'a, T> = LoggingRwLockWriteGuard<'a, T>;
+/// `TracedRwLock::write` 가 돌려주는 가드. `trace` 기능을 끄면 `PassthroughWriteGuard` 다.
+#[cfg(not(feature = "trace"))]
+pub type TracedRwLockWriteGuard<'a, T> = crate::passthrough::PassthroughWriteGuard<'a, T>;
+
+/// `try_read_capped` 가 읽기 락을 바로 얻지 못한 까닭
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryReadError {
+    /// writer 가 쥐고 있거나 먼저 기다리고 있다.
+    Locked,
+    /// `with_max_readers` 로 정한 읽기 허가가 모두 쓰이고 있다.
+    ReadersExhausted,
+}
+
+impl Display for TryReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Locked => "read lock would block on a writer",
+            Self::ReadersExhausted => "read lock would block: all reader permits are in use",
+        })
+    }
+}
+
+impl Error for TryReadError {}
+
+impl From<TryLockError> for TryReadError {
+    fn from(_: TryLockError) -> Self {
+        Self::Locked
+    }
+}
+
+/// 획득/해제 시점을 기록하는 `tokio::sync::RwLock` 래퍼.
+///
+/// ```
+/// use tracing_lock::TokioRwLockTrace;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let lock = TokioRwLockTrace::new(5);
+/// assert_eq!(*lock.read().await, 5);
+///
+/// *lock.write().await += 1;
+/// assert_eq!(*lock.read().await, 6);
+/// # }
+/// ```
+pub struct TokioRwLockTrace<T> {
+    pub(crate) inner: Arc<RwLock<T>>,
+    pub(crate) config: Arc<LockConfig>,
+    pub(crate) entry: Arc<Entry>,
+    // `with_max_readers` 로 정한 동시 읽기 상한. `upgrade` 한 핸들과 나눠 쓴다.
+    pub(crate) read_permits: Option<Arc<Semaphore>>,
+    // `read_dry_run`/`write_dry_run` 이 잡는 더미 락
+    #[cfg(feature = "dry-run")]
+    pub(crate) dry_run: RwLock<()>,
+}
+
+impl<T> TokioRwLockTrace<T> {
+    /// 기존의 RwLock을 감싸는 새로운 생성자
+    ///
+    /// 통계는 감싼 시점부터 센다. 감쌀 때 이미 누가(다른 래퍼나 계측 없는 코드) 락을 쥐고 있으면
+    /// 생성 이벤트의 `message` 에 그 사실이 남는다. `try_write`/`try_read` 로 본 최선의 추정이다.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tokio::sync::RwLock;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// let raw = Arc::new(RwLock::new("shared"));
+    /// let lock = TokioRwLockTrace::from(raw.clone());
+    /// # let _ = lock;
+    /// ```
+    #[track_caller]
+    pub fn from(inner: Arc<RwLock<T>>) -> Self {
+        let config = LockConfig::inherited();
+        let entry = registry::register();
+        entry.configure(&config);
+        // 다른 래퍼나 계측 없는 코드가 이미 쥐고 있는지 (최선의 추정)
+        if inner.try_write().is_err() {
+            let access = match inner.try_read() {
+                Ok(_) => AccessKind::Read,
+                Err(_) => AccessKind::Write,
+            };
+            let _ = entry.attached_in_use.set(access);
+        }
+        TokioRwLockTrace {
+            inner,
+            config: Arc::new(config),
+            entry,
+            read_permits: None,
+            #[cfg(feature = "dry-run")]
+            dry_run: RwLock::new(()),
+        }
+    }
+
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        Self::from(Arc::new(RwLock::new(value)))
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다.
+    ///
+    /// 나머지는 만들 때 물려받은 전역 기본 설정(`set_default_config`)이 남는다. `with_group`,
+    /// `with_timing` 처럼 락의 빌더로 정한 값도 부른 순서와 상관없이 남는다. 여러 번 부르면 차례로
+    /// 덮어쓴다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        let config = config.overlay(&self.config);
+        self.entry.configure(&config);
+        self.config = Arc::new(config);
+        self
+    }
+
+    /// 동시에 살아 있는 읽기 가드를 `max` 개로 제한한다. 넘치는 reader 는 허가가 날 때까지
+    /// 기다리며, 그 시간은 획득 이벤트의 대기 시간에 포함된다.
+    ///
+    /// 쓰기 가드도 허가 하나를 잡아 두었다가 `downgrade` 한 읽기 가드에 넘기므로, 바뀐 reader 도
+    /// 한도 안에 든다. writer 가 쥔 허가도 `read_permits_in_use` 에 센다. 읽기 가드가 남아 있는
+    /// 동안에는 어차피 쓰기 락을 잡을 수 없으므로 writer 가 더 기다리지는 않는다.
+    ///
+    /// `max` 가 0 이면 패닉한다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let buffers = TokioRwLockTrace::new(vec![0u8; 1024]).with_max_readers(4);
+    /// let reader = buffers.read().await;
+    /// assert_eq!(buffers.stats().read_permits_in_use, 1);
+    /// # drop(reader);
+    /// # }
+    /// ```
+    pub fn with_max_readers(mut self, max: usize) -> Self {
+        assert!(max > 0, "max_readers must be at least 1");
+        self.read_permits = Some(Arc::new(Semaphore::new(max)));
+        self
+    }
+
+    /// 점유 시간이 `floor` 보다 짧은 획득/해제 이벤트 쌍을 내보내지 않는다. 통계에는 그대로 집계된다.
+    ///
+    /// 획득 이벤트는 해제될 때까지 보류했다가 원래 타임스탬프로 해제 이벤트 바로 앞에 나간다.
+    /// 경고 이벤트는 영향을 받지 않는다.
+    pub fn with_min_logged_hold(mut self, floor: Duration) -> Self {
+        Arc::make_mut(&mut self.config).min_logged_hold = Some(floor);
+        self
+    }
+
+    /// 읽기와 쓰기를 가리지 않고 요청한 순서대로 락을 넘겨준다.
+    ///
+    /// 모든 획득이 내부 차례 대기열을 하나씩 지나가므로 나중에 온 reader 가 먼저 온 writer 를
+    /// 앞지르는 일이 없다. 처리량보다 순서가 중요한 테스트용이다. 획득 이벤트의
+    /// `ticket` 에 받은 차례가 남는다. `try_read`/`try_write` 도 대기열을 앞지르지 않으므로, 누가
+    /// 줄을 서 있으면 락이 비어 있어도 `Busy` 로 실패한다.
+    pub fn with_strict_fifo(mut self) -> Self {
+        Arc::make_mut(&mut self.config).fifo = Some(Arc::default());
+        self
+    }
+
+    /// 샤딩된 자료구조의 조각들처럼 하나의 논리적 락을 이루는 락들에 같은 `group` 을 붙인다.
+    ///
+    /// 락마다 id 로 구분되면서도 `registry::group_report` 로 묶음 전체의 통계를 합산할 수 있다.
+    ///
+    /// ```
+    /// use tracing_lock::{registry, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let shards: Vec<_> = (0..4)
+    ///     .map(|_| TokioRwLockTrace::new(0).with_group("counters"))
+    ///     .collect();
+    /// *shards[1].write().await += 1;
+    /// assert_eq!(registry::group_report("counters").unwrap().writes, 1);
+    /// # }
+    /// ```
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.config).group = Some(group.into());
+        self.entry.configure(&self.config);
+        self
+    }
+
+    /// `false` 면 점유/대기 시간을 재지 않는다. 가드는 시계를 읽지 않고 획득 이벤트만 남긴다.
+    ///
+    /// 점유 시간이 상관없는 아주 뜨거운 읽기 락용이다. 시간 통계는 0 으로 남고 해제 이벤트,
+    /// `with_min_logged_hold`, reader 가 writer 를 막는 경고는 동작하지 않는다.
+    pub fn with_timing(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.config).untimed = !enabled;
+        self
+    }
+
+    /// 획득 직전에 `seed` 로 결정되는 의사 난수 지연을 넣는다. (`chaos` 기능, 테스트 전용)
+    ///
+    /// 같은 시드는 같은 지연 순서를 만들므로 경합 버그를 재현할 수 있다.
+    #[cfg(feature = "chaos")]
+    pub fn with_chaos(mut self, seed: u64) -> Self {
+        Arc::make_mut(&mut self.config).chaos = Some(Arc::new(crate::chaos::Chaos::new(seed)));
+        self
+    }
+
+    /// `#[derive(TracedLocks)]` 전용. 이름이 없으면 `name` 을 붙인다.
+    #[doc(hidden)]
+    pub fn __name_if_unnamed(&mut self, name: &str) {
+        if self.config.name.is_none() {
+            Arc::make_mut(&mut self.config).name = Some(name.to_string());
+            self.entry.set_name(self.config.name.clone());
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.config.name.as_deref()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.entry.stats.snapshot()
+    }
+
+    /// 통계를 사람이 읽는 여러 줄 요약으로 만든다. 형식은 바뀌지 않는다.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs"));
+    /// *lock.write().await += 1;
+    /// let report = lock.report();
+    /// // 락 id 와 점유 시간은 실행마다 다르다.
+    /// assert!(report.starts_with("lock 'jobs' (#"));
+    /// assert!(report.contains("\n  acquisitions: 1 (0 reads, 1 writes)\n"));
+    /// assert!(report.contains("\n  hold: avg "));
+    /// assert!(report.contains("\n  readers: 0 now, 0 peak\n"));
+    /// assert!(report.ends_with("\n  waiters: 0"));
+    /// # }
+    /// ```
+    pub fn report(&self) -> String {
+        use std::fmt::Write;
+
+        let stats = self.stats();
+        let mut out = String::with_capacity(160);
+        let _ = match (self.name(), registry::unnamed_site(self.entry.created_at)) {
+            (Some(name), _) => writeln!(out, "lock '{}' (#{})", name, self.entry.id),
+            (None, Some(site)) => writeln!(out, "lock #{} {}", self.entry.id, site),
+            (None, None) => writeln!(out, "lock #{}", self.entry.id),
+        };
+        let _ = write!(
+            out,
+            "  acquisitions: {} ({} reads, {} writes)",
+            stats.reads + stats.writes,
+            stats.reads,
+            stats.writes
+        );
+        let _ = match stats.sample_every {
+            Some(every) => writeln!(out, " (estimated, 1 in {} sampled)", every),
+            None => writeln!(out),
+        };
+        let _ = writeln!(
+            out,
+            "  hold: avg {:?}, max {:?}, total {:?}",
+            stats.avg_hold.unwrap_or_default(),
+            stats.max_hold,
+            stats.total_hold
+        );
+        let _ = writeln!(
+            out,
+            "  readers: {} now, {} peak",
+            stats.readers, stats.peak_readers
+        );
+        let _ = write!(out, "  waiters: {}", stats.waiters);
+        out
+    }
+
+    /// 소유권이나 `Arc` 없이 빌린 `RwLock` 을 계측한다.
+    ///
+    /// ```
+    /// use tokio::sync::RwLock;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let raw = RwLock::new(1);
+    /// let lock = TokioRwLockTrace::borrow(&raw);
+    /// assert_eq!(*lock.read().await, 1);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn borrow(inner: &RwLock<T>) -> BorrowedRwLockTrace<'_, T> {
+        BorrowedRwLockTrace::new(inner)
+    }
+
+    /// 계측 없이 안쪽 `RwLock` 을 그대로 빌려준다.
+    ///
+    /// 여기서 얻은 가드는 이벤트도 통계도 남기지 않는다. `RwLock<T>` 로의 `Deref` 는 계측을
+    /// 모르게 건너뛰게 만들어서 없앴으며, 건너뛸 때는 이 메서드로 의도를 드러낸다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(1);
+    /// assert_eq!(*lock.raw().read().await, 1);
+    /// assert_eq!(lock.stats().reads, 0);
+    /// # }
+    /// ```
+    pub fn raw(&self) -> &RwLock<T> {
+        &self.inner
+    }
+
+    /// 락을 잡지 않고 값을 가변으로 빌려준다. `&mut self` 가 있으면 다른 가드가 있을 수 없으므로
+    /// 이벤트도 통계도 남기지 않는다.
+    ///
+    /// # Panics
+    ///
+    /// `from` 으로 감싼 `Arc<RwLock<T>>` 의 다른 복제본이나 `upgrade` 한 핸들이 남아 있으면 패닉한다.
+    pub fn get_mut(&mut self) -> &mut T {
+        Arc::get_mut(&mut self.inner)
+            .expect("get_mut on a lock whose RwLock is still shared")
+            .get_mut()
+    }
+
+    /// 락을 소비하고 값을 꺼낸다. 소멸 이벤트는 그냥 드롭할 때처럼 남는다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// let lock = TokioRwLockTrace::new(vec![1]);
+    /// assert_eq!(lock.into_inner(), [1]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// `get_mut` 과 같이, 감싼 `RwLock` 을 다른 곳에서도 쥐고 있으면 패닉한다.
+    pub fn into_inner(self) -> T {
+        let inner = self.inner.clone();
+        drop(self);
+        match Arc::try_unwrap(inner) {
+            Ok(lock) => lock.into_inner(),
+            Err(_) => panic!("into_inner on a lock whose RwLock is still shared"),
+        }
+    }
+}
+
+// 획득 future 는 `Send` 이므로 `.await` 로 얻는 메서드는 값이 스레드를 건널 수 있어야 한다.
+impl<T: Send + Sync> TokioRwLockTrace<T> {
+    #[track_caller]
+    pub fn read(&self) -> ReadFuture<'_, T> {
+        ReadFuture::new(
+            &self.inner,
+            &self.config,
+            &self.entry,
+            self.read_permits.as_ref(),
+            (),
+        )
+    }
+
+    /// 읽기 락을 잡아 `f` 에 값을 빌려주고, `f` 가 끝나는 즉시 놓는다.
+    ///
+    /// 아주 자주 도는 짧은 읽기용이다. 획득/해제 이벤트 쌍 대신 해제 때 `Peek` 이벤트 하나만
+    /// 남기며, `LockConfig::quiet_peeks` 면 그것도 남기지 않는다. 통계와 경고는 `read` 와 같다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(vec![1, 2, 3]);
+    /// assert_eq!(lock.peek(|numbers| numbers.len()).await, 3);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn peek<'a, R>(&'a self, f: impl FnOnce(&T) -> R + 'a) -> impl Future<Output = R> + 'a {
+        let read = self.read().peek();
+        async move {
+            let guard = read.await;
+            f(&guard)
+        }
+    }
+
+    #[track_caller]
+    pub fn write(&self) -> WriteFuture<'_, T> {
+        WriteFuture::new(
+            &self.inner,
+            &self.config,
+            &self.entry,
+            self.read_permits.as_ref(),
+            (),
+        )
+    }
+
+    /// 스레드를 막고 읽기 락을 기다린다. 동기 코드에서 쓴다. 계측은 `read` 와 같다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// let lock = TokioRwLockTrace::new(1);
+    /// assert_eq!(*lock.blocking_read(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// tokio 의 `blocking_read` 처럼 런타임 안에서 부르면 패닉한다.
+    #[track_caller]
+    pub fn blocking_read(&self) -> LoggingRwLockReadGuard<'_, T> {
+        crate::future::block_on(self.read())
+    }
+
+    /// 스레드를 막고 쓰기 락을 기다린다. (`blocking_read` 참고)
+    #[track_caller]
+    pub fn blocking_write(&self) -> LoggingRwLockWriteGuard<'_, T> {
+        crate::future::block_on(self.write())
+    }
+
+    /// `read` 와 같은 계측(위치, 시간, 이벤트, 통계)을 모두 하되 실제 값 대신 이 락에 딸린 비어
+    /// 있는 더미 락을 잡는다. (`dry-run` 기능, 계측 비용 측정 전용)
+    ///
+    /// 실제 경합과 분리해서 계측 자체의 비용만 잴 수 있다. 통계도 실제 락의 통계에 더해진다.
+    #[cfg(feature = "dry-run")]
+    #[track_caller]
+    pub fn read_dry_run(&self) -> ReadFuture<'_, ()> {
+        ReadFuture::new(&self.dry_run, &self.config, &self.entry, None, ())
+    }
+
+    /// `write` 의 더미 락 버전. (`read_dry_run` 참고)
+    ///
+    /// 더미 락도 진짜 락이므로 여러 태스크가 동시에 부르면 서로 기다린다.
+    #[cfg(feature = "dry-run")]
+    #[track_caller]
+    pub fn write_dry_run(&self) -> WriteFuture<'_, ()> {
+        WriteFuture::new(&self.dry_run, &self.config, &self.entry, None, ())
+    }
+
+    /// `ctx` 를 붙여 읽기 락을 획득한다. 획득/해제 이벤트에 `ctx` 가 `Display` 로 기록된다.
+    #[track_caller]
+    pub fn read_ctx<C: Display>(&self, ctx: C) -> ReadFuture<'_, T, Context<C>> {
+        ReadFuture::new(
+            &self.inner,
+            &self.config,
+            &self.entry,
+            self.read_permits.as_ref(),
+            Context(ctx),
+        )
+    }
+
+    /// `ctx` 를 붙여 쓰기 락을 획득한다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let request_id = 42;
+    /// *lock.write_ctx(request_id).await += 1; // "... released. Duration: ... [42] ..."
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn write_ctx<C: Display>(&self, ctx: C) -> WriteFuture<'_, T, Context<C>> {
+        WriteFuture::new(
+            &self.inner,
+            &self.config,
+            &self.entry,
+            self.read_permits.as_ref(),
+            Context(ctx),
+        )
+    }
+
+    /// 우선순위를 붙여 쓰기 락을 획득한다. 값이 클수록 중요하다.
+    ///
+    /// tokio 는 우선순위대로 깨우지 않으므로 순서는 `write` 와 같다. 우선순위는 획득/해제
+    /// 이벤트에 기록되고, 이 writer 가 기다리는 동안 더 낮은 우선순위(우선순위 없는 가드는
+    /// 0)의 가드가 락을 쥐고 있었다면 그 가드가 해제될 때 우선순위 역전 경고가 남는다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// *lock.write_prio(200).await += 1; // "... (priority 200) ..."
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn write_prio(&self, prio: u8) -> WriteFuture<'_, T> {
+        self.write().with_priority(prio)
+    }
+}
+
+impl<T> TokioRwLockTrace<T> {
+    /// 이 락의 이름, id, 접근 종류를 필드로 가진 `lock` span 을 만든다.
+    ///
+    /// 가드를 쥔 채 실행하는 future 를 이 span 으로 `instrument` 하면 그 안의 로그가 락과
+    /// 연결된다. 이름 없는 락은 `lock.name` 을 비워 둔다.
+    ///
+    /// ```
+    /// use tracing::Instrument;
+    /// use tracing_lock::{AccessKind, LockConfig, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let cache = TokioRwLockTrace::new(Vec::new()).with_config(LockConfig::new().name("cache"));
+    /// let mut entries = cache.write().await;
+    /// async {
+    ///     entries.push("fresh");
+    ///     tracing::debug!(len = entries.len(), "cache refilled"); // lock{lock.name="cache" ..}
+    /// }
+    /// .instrument(cache.lock_span(AccessKind::Write))
+    /// .await;
+    /// # }
+    /// ```
+    pub fn lock_span(&self, access: AccessKind) -> tracing::Span {
+        let access = match access {
+            AccessKind::Read => "read",
+            AccessKind::Write => "write",
+        };
+        let span = tracing::debug_span!(
+            "lock",
+            lock.name = tracing::field::Empty,
+            lock.id = self.entry.id,
+            lock.access = access,
+        );
+        if let Some(name) = self.name() {
+            span.record("lock.name", name);
+        }
+        span
+    }
+
+    /// 지금까지 가장 오래 기다린 획득 `SLOWEST_KEPT` 개 (긴 순서). `slowest_by(SlowBy::Wait)` 와 같다.
+    ///
+    /// 로그를 뒤지지 않고 최악의 호출 위치를 찾을 때 쓴다. 대기 없이 얻은 획득은 남지 않는다.
+    pub fn slowest(&self) -> Vec<SlowAcquisition> {
+        self.slowest_by(SlowBy::Wait)
+    }
+
+    /// `by` 기준으로 가장 길었던 획득들 (긴 순서). 점유 시간은 `with_timing(false)` 면 남지 않는다.
+    ///
+    /// ```
+    /// use tracing_lock::{SlowBy, TokioRwLockTrace};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// drop(lock.write().await);
+    /// for slow in lock.slowest_by(SlowBy::Hold) {
+    ///     println!("{:?} at {}", slow.duration, slow.location);
+    /// }
+    /// # }
+    /// ```
+    pub fn slowest_by(&self, by: SlowBy) -> Vec<SlowAcquisition> {
+        self.entry.slowest.get(by)
+    }
+
+    /// 누적 점유 시간으로 비교되는 정렬 키. 점유가 길수록 크다.
+    ///
+    /// 가장 많이 쓰인 락부터 처리하려고 힙에 넣거나 정렬할 때 쓴다. 락 자체에 `Ord` 를 두면
+    /// 같은 값의 락끼리도 다르게 비교돼 헷갈리므로 키를 따로 꺼낸다. 키는 부른 시점의 값이며
+    /// 락이 더 쓰여도 바뀌지 않는다. 전역으로 보려면 `registry::top_contended` 를 쓴다.
+    ///
+    /// ```
+    /// use std::cmp::Reverse;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// let mut locks = vec![TokioRwLockTrace::new(1), TokioRwLockTrace::new(2)];
+    /// locks.sort_by_key(|lock| Reverse(lock.by_hold_time()));
+    /// ```
+    pub fn by_hold_time(&self) -> impl Ord + Copy {
+        self.entry.stats.hold_nanos()
+    }
+
+    /// 최근 10~20초 동안의 획득 중 기다려야 했던 것의 비율 (0.0 ~ 1.0). 획득이 없었으면 0.
+    ///
+    /// 10초 창 두 개(직전, 현재)를 합쳐 계산한다. 처음 poll 에 바로 얻지 못한 획득을 기다린 것으로
+    /// 본다. `LockConfig::warn_contention_ratio` 로 문턱을 넘을 때 경고를 받을 수 있다.
+    pub fn contention_ratio(&self) -> f64 {
+        self.entry.stats.contention().0
+    }
+
+    /// 사용자 span 의 필드 값으로 쓸 락 식별자. `이름#id` (이름이 없으면 `#id`) 로 기록된다.
+    ///
+    /// ```
+    /// use tracing_lock::{LockConfig, TokioRwLockTrace};
+    ///
+    /// let sessions = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("sessions"));
+    /// let span = tracing::info_span!("request", lock = tracing::field::Empty);
+    /// span.record("lock", sessions.field()); // lock=sessions#1
+    /// ```
+    pub fn field(&self) -> impl tracing::Value + '_ {
+        tracing::field::display(LockField {
+            name: self.name(),
+            id: self.entry.id,
+        })
+    }
+
+    /// 기다리지 않고 쓰기 락을 시도한다. 이미 잡혀 있으면 `Busy` 이벤트를 남기고 실패한다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let reader = lock.read().await;
+    /// assert!(lock.try_write().is_err());
+    /// drop(reader);
+    /// *lock.try_write().unwrap() += 1;
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn try_write(&self) -> Result<LoggingRwLockWriteGuard<'_, T>, TryLockError> {
+        self.try_write_with((), None)
+    }
+
+    /// 지금 읽기 락을 기다리지 않고 얻을 수 있어야 한다. 아니면 패닉한다. (`test-util` 기능)
+    ///
+    /// "읽기는 막히지 않는다" 는 불변식을 테스트에서 단언할 때 쓴다. 락을 쥐지 않고 곧바로 놓으며
+    /// 이벤트도 남기지 않는다. 보유자 기록이 켜져 있으면(`registry::install_panic_hook`) 패닉
+    /// 메시지에 쓰기 락을 쥔 위치가 들어간다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let _reader = lock.read().await;
+    /// lock.assert_read_uncontended();
+    /// # }
+    /// ```
+    #[cfg(any(test, feature = "test-util"))]
+    #[track_caller]
+    pub fn assert_read_uncontended(&self) {
+        if self.inner.try_read().is_ok() {
+            return;
+        }
+        let lock = match &self.config.name {
+            Some(name) => format!("lock {name:?}"),
+            None => format!("lock #{}", self.entry.id),
+        };
+        let writers = self.entry.holder_sites(AccessKind::Write);
+        if writers.is_empty() {
+            panic!("read on {lock} would block: it is write-held (holder not recorded)");
+        }
+        let sites: Vec<_> = writers
+            .iter()
+            .map(|site| format!("{}:{}", site.file(), site.line()))
+            .collect();
+        panic!(
+            "read on {lock} would block: write-held from {}",
+            sites.join(", ")
+        );
+    }
+
+    /// 기다리지 않고 읽기 락을 시도한다. writer 가 쥐고 있거나 `with_max_readers` 의 허가가
+    /// 남지 않았으면 `Busy` 이벤트를 남기고 실패한다.
+    ///
+    /// tokio 와 같이 `TryLockError` 를 돌려주므로 실패한 까닭은 가리지 않는다. 허가가 모자라
+    /// 실패했는지 알려면 `try_read_capped` 를 쓴다. `Busy` 이벤트에는 까닭이 메시지로 남는다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let writer = lock.write().await;
+    /// assert!(lock.try_read().is_err());
+    /// drop(writer);
+    /// assert_eq!(*lock.try_read().unwrap(), 0);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn try_read(&self) -> Result<LoggingRwLockReadGuard<'_, T>, TryLockError> {
+        self.try_read_capped().map_err(|_| would_block())
+    }
+
+    /// `try_read` 와 같지만 writer 때문이면 `TryReadError::Locked`, `with_max_readers` 의 허가가
+    /// 남지 않았으면 `TryReadError::ReadersExhausted` 로 실패한다.
+    ///
+    /// ```
+    /// use tracing_lock::{TokioRwLockTrace, TryReadError};
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0).with_max_readers(1);
+    /// let writer = lock.write().await;
+    /// assert_eq!(lock.try_read_capped().err(), Some(TryReadError::Locked));
+    /// drop(writer);
+    /// let reader = lock.try_read_capped().unwrap();
+    /// assert_eq!(lock.try_read_capped().err(), Some(TryReadError::ReadersExhausted));
+    /// assert_eq!(*reader, 0);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn try_read_capped(&self) -> Result<LoggingRwLockReadGuard<'_, T>, TryReadError> {
+        self.entry.announce(&self.config);
+        // 클로저 안에서는 `#[track_caller]` 위치가 이어지지 않는다.
+        let location = std::panic::Location::caller();
+        let busy = |err| {
+            log_call_info!(
+                &self.config,
+                EventKind::Busy,
+                AccessKind::Read,
+                location: location,
+                message: (err == TryReadError::ReadersExhausted)
+                    .then(|| "no reader permits left (with_max_readers)".to_string()),
+            );
+            Err(err)
+        };
+        // 먼저 줄을 선 획득을 앞지르지 않는다. (`with_strict_fifo`)
+        let fifo = self.config.fifo.as_deref();
+        let turn = match fifo.map(Fifo::try_turn) {
+            Some(None) => return busy(TryReadError::Locked),
+            turn => turn.flatten(),
+        };
+        // writer 도 허가를 쥐므로, 락을 먼저 봐야 writer 때문에 실패한 것을 가릴 수 있다.
+        let guard = match self.inner.try_read() {
+            Ok(guard) => guard,
+            Err(err) => return busy(err.into()),
+        };
+        let permit = match self.read_permits.clone().map(Semaphore::try_acquire_owned) {
+            Some(Ok(permit)) => Some(self.entry.stats.hold_read_permit(permit)),
+            Some(Err(_)) => return busy(TryReadError::ReadersExhausted),
+            None => None,
+        };
+        self.entry.stats.record_read();
+        self.entry.stats.record_contention(false);
+        #[cfg(feature = "tokio-console")]
+        crate::console::acquired(&self.entry, AccessKind::Read);
+        #[cfg(feature = "metrics")]
+        crate::metrics::acquired(
+            &self.entry,
+            AccessKind::Read,
+            self.config.timed().then_some(Duration::ZERO),
+        );
+        let ticket = fifo.map(Fifo::ticket);
+        let (held, depth) = task::push(self.entry.id).unzip();
+        let event = lock_event!(
+            &self.config,
+            EventKind::Acquire,
+            AccessKind::Read,
+            depth: depth,
+            stack_depth: self.config.stack_depth.then(task::stack_depth),
+            waited: self.config.timed().then_some(Duration::ZERO),
+            ticket: ticket,
+        );
+        drop(turn);
+        let mut state = GuardState::new(
+            &self.config,
+            &self.entry,
+            AccessKind::Read,
+            (),
+            held,
+            event,
+            location,
+        );
+        state.site = crate::sites::acquired(
+            &self.config,
+            &self.entry,
+            AccessKind::Read,
+            location,
+            self.config.timed().then_some(Duration::ZERO),
+        );
+        state.timeline =
+            crate::timeline::acquired(&self.config, self.entry.id, AccessKind::Read, location);
+        Ok(LoggingRwLockReadGuard {
+            state,
+            guard,
+            _permit: permit,
+        })
+    }
+
+    /// `try_lock_all!` 전용. 실패 이벤트에 묶음 획득을 포기했다는 메시지를 붙인다.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn __try_write_in_bundle(&self) -> Option<LoggingRwLockWriteGuard<'_, T>> {
+        let message = "try_lock_all backed off, releasing locks taken so far";
+        self.try_write_with((), Some(message.to_string())).ok()
+    }
+
+    /// 쓰기 락을 잡고 값을 `value` 로 바꾼 뒤 곧바로 해제하고, 이전 값을 돌려준다.
+    ///
+    /// 획득/해제 이벤트에는 `[replace]` 컨텍스트가 붙는다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let config = TokioRwLockTrace::new("v1");
+    /// assert_eq!(config.replace("v2").await, "v1");
+    /// assert_eq!(*config.read().await, "v2");
+    /// # }
+    /// ```
+    pub async fn replace(&self, value: T) -> T
+    where
+        T: Send + Sync,
+    {
+        let mut guard = self.write_ctx("replace").await;
+        std::mem::replace(&mut *guard, value)
+    }
+
+    /// 기다리지 않는 `replace`. 락이 잡혀 있으면 `value` 를 `Err` 로 그대로 돌려준다.
+    #[track_caller]
+    pub fn try_replace(&self, value: T) -> Result<T, T> {
+        match self.try_write_with(Context("replace"), None) {
+            Ok(mut guard) => Ok(std::mem::replace(&mut *guard, value)),
+            Err(_) => Err(value),
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn try_write_with<C: GuardContext>(
+        &self,
+        context: C,
+        busy_message: Option<String>,
+    ) -> Result<LoggingRwLockWriteGuard<'_, T, C>, TryLockError> {
+        self.entry.announce(&self.config);
+        // 클로저 안에서는 `#[track_caller]` 위치가 이어지지 않는다.
+        let location = std::panic::Location::caller();
+        let busy = |context: &C, message| {
+            log_call_info!(
+                &self.config,
+                EventKind::Busy,
+                AccessKind::Write,
+                location: location,
+                context: context.render(),
+                message: message,
+            );
+        };
+        // 먼저 줄을 선 획득을 앞지르지 않는다. (`with_strict_fifo`)
+        let fifo = self.config.fifo.as_deref();
+        let turn = match fifo.map(Fifo::try_turn) {
+            Some(None) => {
+                busy(&context, busy_message);
+                return Err(would_block());
+            }
+            turn => turn.flatten(),
+        };
+        // 허가가 모자라면 읽기 가드가 남아 있다는 뜻이므로 락도 잡히지 않는다.
+        let permit = match self.read_permits.clone().map(Semaphore::try_acquire_owned) {
+            Some(Ok(permit)) => Some(self.entry.stats.hold_read_permit(permit)),
+            Some(Err(_)) => {
+                busy(&context, busy_message);
+                return Err(would_block());
+            }
+            None => None,
+        };
+        let guard = match self.inner.try_write() {
+            Ok(guard) => guard,
+            Err(err) => {
+                busy(&context, busy_message);
+                return Err(err);
+            }
+        };
+        self.entry.stats.record_write();
+        self.entry.stats.record_contention(false);
+        #[cfg(feature = "tokio-console")]
+        crate::console::acquired(&self.entry, AccessKind::Write);
+        #[cfg(feature = "metrics")]
+        crate::metrics::acquired(
+            &self.entry,
+            AccessKind::Write,
+            self.config.timed().then_some(Duration::ZERO),
+        );
+        let ticket = fifo.map(Fifo::ticket);
+        let (held, depth) = task::push(self.entry.id).unzip();
+        let event = lock_event!(
+            &self.config,
+            EventKind::Acquire,
+            AccessKind::Write,
+            context: context.render(),
+            depth: depth,
+            stack_depth: self.config.stack_depth.then(task::stack_depth),
+            waited: self.config.timed().then_some(Duration::ZERO),
+            ticket: ticket,
+        );
+        drop(turn);
+        let mut state = GuardState::new(
+            &self.config,
+            &self.entry,
+            AccessKind::Write,
+            context,
+            held,
+            event,
+            location,
+        );
+        state.site = crate::sites::acquired(
+            &self.config,
+            &self.entry,
+            AccessKind::Write,
+            location,
+            self.config.timed().then_some(Duration::ZERO),
+        );
+        state.timeline =
+            crate::timeline::acquired(&self.config, self.entry.id, AccessKind::Write, location);
+        Ok(LoggingRwLockWriteGuard {
+            state,
+            guard,
+            _permit: permit,
+        })
+    }
+}
+
+// `TryLockError` 는 밖에서 만들 수 없으므로, 허가가 모자라 `try_read` 가 실패할 때는 잡혀 있는
+// 빈 락에서 얻어 온다.
+fn would_block() -> TryLockError {
+    let lock = RwLock::const_new(());
+    let _held = lock.try_write().expect("fresh lock");
+    lock.try_write().expect_err("already write-held")
+}
+
+impl<T> Drop for TokioRwLockTrace<T> {
+    fn drop(&mut self) {
+        // `upgrade` 로 만든 다른 핸들이 남아 있으면 아직 살아 있는 락이다.
+        if !self.entry.drop_handle() {
+            return;
+        }
+        self.entry.announce(&self.config);
+        let stats = self.entry.stats.snapshot();
+        log_call_info!(
+            &self.config,
+            EventKind::Destroyed,
+            AccessKind::Write,
+            // 드롭 위치는 알 수 없으므로 생성 위치로 짝을 맞춘다.
+            location: self.entry.created_at,
+            message: Some(format!("{} acquisitions in total", stats.reads + stats.writes)),
+        );
+        // 가드는 락을 빌리므로 여기까지 살아 있는 가드는 드롭되지 않고 잊힌 것이다.
+        #[cfg(debug_assertions)]
+        if let Some((count, first)) = self.entry.live_guards() {
+            log_call_info!(
+                &self.config,
+                EventKind::Misuse,
+                AccessKind::Write,
+                location: first,
+                message: Some(format!(
+                    "{} guard(s) were never dropped (mem::forget or a leaked Box?)",
+                    count
+                )),
+            );
+            self.entry.retire();
+        }
+    }
+}
+
+// `TokioRwLockTrace::field` 의 표시 형식
+struct LockField<'a> {
+    name: Option<&'a str>,
+    id: u64,
+}
+
+impl Display for LockField<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}", self.name.unwrap_or(""), self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+
+    #[tokio::test]
+    async fn test_spawn() {
+        let rw_lock = Arc::new(RwLock::new(5));
+        let logging_lock = TokioRwLockTrace::from(rw_lock.clone());
+
+        let _ = tokio::spawn(async move {
+            {
+                let read_guard = logging_lock.read().await;
+                println!("Read value: {}", *read_guard);
+            }
+
+            {
+                let mut write_guard = logging_lock.write().await;
+                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                *write_guard += 1;
+                println!("Updated value: {}", *write_guard);
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn stats_count_acquisitions() {
+        let lock = TokioRwLockTrace::new(0);
+        drop(lock.write().await);
+        drop(lock.read().await);
+        drop(lock.read().await);
+        drop(lock.write().await);
+
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (2, 2));
+        assert_eq!(stats.avg_reads_per_write, Some(2.0));
+    }
+
+    #[tokio::test]
+    async fn observer_receives_timestamped_events() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        drop(lock.write().await);
+
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [EventKind::Created, EventKind::Acquire, EventKind::Release]
+        );
+        assert!(events[0].timestamp <= events[1].timestamp);
+        assert!(events[1].timestamp <= events[2].timestamp);
+    }
+
+    #[tokio::test]
+    async fn context_is_included_in_release_event() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        *lock.write_ctx("req-42").await += 1;
+        drop(lock.read().await);
+
+        let events = events.events();
+        let release = &events[2];
+        assert_eq!(release.kind, EventKind::Release);
+        assert_eq!(release.context.as_deref(), Some("req-42"));
+        assert!(release.to_string().contains("[req-42]"));
+        assert_eq!(events[4].context, None);
+    }
+
+    #[tokio::test]
+    async fn warns_when_reader_lingers_while_writer_waits() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(
+                LockConfig::new()
+                    .warn_reader_blocking_writer(tokio::time::Duration::from_millis(10))
+                    .observer(events.clone()),
+            ),
+        );
+
+        // writer 가 없을 때의 짧은 읽기는 경고하지 않는다.
+        drop(lock.read().await);
+
+        let reader = lock.read().await;
+        let writer = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.write().await += 1 }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(30)).await;
+        drop(reader);
+        writer.await.unwrap();
+
+        let events = events.events();
+        let warnings: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Warning)
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].access, AccessKind::Read);
+        let message = warnings[0].message.as_deref().unwrap();
+        assert!(
+            message.starts_with("held a read lock while a writer waited for"),
+            "{message}"
+        );
+    }
+
+    #[tokio::test]
+    async fn high_priority_writer_behind_readers_warns_of_inversion() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone())),
+        );
+
+        // 우선순위 없는 writer 가 기다릴 때는 역전이 아니다.
+        let reader = lock.read().await;
+        let plain = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.write().await += 1 }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        drop(reader);
+        plain.await.unwrap();
+
+        let reader = lock.read().await;
+        let urgent = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.write_prio(200).await += 1 }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(reader);
+        urgent.await.unwrap();
+
+        let events = events.events();
+        let warnings: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Warning)
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].access, AccessKind::Read);
+        assert!(warnings[0].held.unwrap() >= Duration::from_millis(20));
+        let message = warnings[0].message.as_deref().unwrap();
+        assert!(
+            message.starts_with("priority inversion: a priority-200 writer waited"),
+            "{message}"
+        );
+        assert!(
+            message.ends_with("behind this priority-0 holder"),
+            "{message}"
+        );
+        let prioritized: Vec<_> = events
+            .iter()
+            .filter(|e| e.priority == Some(200))
+            .map(|e| (e.kind, e.access))
+            .collect();
+        assert_eq!(
+            prioritized,
+            [
+                (EventKind::Acquire, AccessKind::Write),
+                (EventKind::Release, AccessKind::Write),
+            ]
+        );
+        assert!(events
+            .iter()
+            .filter(|e| e.priority.is_some())
+            .all(|e| e.to_string().contains("(priority 200)")));
+    }
+
+    #[tokio::test]
+    async fn max_readers_makes_extra_readers_wait() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0)
+                .with_max_readers(2)
+                .with_config(LockConfig::new().observer(events.clone())),
+        );
+        let (first, second) = (lock.read().await, lock.read().await);
+        assert_eq!(lock.stats().read_permits_in_use, 2);
+
+        let third = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.read().await }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        assert!(!third.is_finished());
+        // reader 가 있으니 writer 는 허가도 락도 얻지 못한다.
+        assert!(lock.try_write().is_err());
+
+        drop(first);
+        third.await.unwrap();
+        drop(second);
+        assert_eq!(lock.stats().read_permits_in_use, 0);
+        assert_eq!(lock.stats().peak_readers, 2);
+
+        let events = events.events();
+        let waited: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Acquire && e.access == AccessKind::Read)
+            .map(|e| e.waited.unwrap())
+            .collect();
+        assert_eq!(waited.len(), 3);
+        assert!(
+            waited[2] >= tokio::time::Duration::from_millis(20),
+            "{waited:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn downgraded_reader_keeps_the_writers_permit() {
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0)
+                .with_max_readers(1)
+                .with_config(LockConfig::new().silent()),
+        );
+        let writer = lock.write().await;
+        assert_eq!(lock.stats().read_permits_in_use, 1);
+        let reader = writer.downgrade();
+        assert_eq!(lock.stats().read_permits_in_use, 1);
+        // 바뀐 reader 도 한도를 채우므로 다른 reader 는 기다린다.
+        assert_eq!(
+            lock.try_read_capped().err(),
+            Some(TryReadError::ReadersExhausted)
+        );
+        let second = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.read().await }
+        });
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+        assert!(!second.is_finished());
+        drop(reader);
+        assert_eq!(second.await.unwrap(), 0);
+        assert_eq!(lock.stats().read_permits_in_use, 0);
+    }
+
+    #[tokio::test]
+    async fn replace_swaps_config_and_returns_old_value() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            version: u32,
+        }
+
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(Config { version: 1 })
+            .with_config(LockConfig::new().observer(events.clone()));
+        assert_eq!(
+            lock.replace(Config { version: 2 }).await,
+            Config { version: 1 }
+        );
+        assert_eq!(
+            lock.try_replace(Config { version: 3 }),
+            Ok(Config { version: 2 })
+        );
+        {
+            let _reader = lock.read().await;
+            assert_eq!(
+                lock.try_replace(Config { version: 4 }),
+                Err(Config { version: 4 })
+            );
+        }
+        assert_eq!(lock.read().await.version, 3);
+
+        let events = events.events();
+        let replaced: Vec<_> = events
+            .iter()
+            .filter(|e| e.context.as_deref() == Some("replace"))
+            .map(|e| (e.kind, e.access))
+            .collect();
+        use crate::{AccessKind::Write, EventKind::*};
+        assert_eq!(
+            replaced,
+            [
+                (Acquire, Write),
+                (Release, Write),
+                (Acquire, Write),
+                (Release, Write),
+                (Busy, Write)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn holds_below_floor_are_not_logged() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().observer(events.clone()))
+            .with_min_logged_hold(Duration::from_millis(10));
+
+        drop(lock.read().await);
+        {
+            let _slow = lock.write().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        lock.try_write().unwrap().release();
+
+        let events = events.events();
+        let logged: Vec<_> = events.iter().map(|e| (e.kind, e.access)).collect();
+        assert_eq!(
+            logged,
+            [
+                (EventKind::Created, AccessKind::Write),
+                (EventKind::Acquire, AccessKind::Write),
+                (EventKind::Release, AccessKind::Write),
+            ]
+        );
+        assert!(events[1].timestamp < events[2].timestamp);
+        assert!(events[2].held.unwrap() >= Duration::from_millis(20));
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn report_summarizes_known_workload() {
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("report").silent());
+        {
+            let _a = lock.read().await;
+            let _b = lock.read().await;
+            let _c = lock.read().await;
+        }
+        {
+            let _slow = lock.write().await;
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let reader = lock.read().await;
+
+        let report = lock.report();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(lines[0], format!("lock 'report' (#{})", lock.entry.id));
+        assert_eq!(lines[1], "  acquisitions: 5 (4 reads, 1 writes)");
+        assert!(lines[2].starts_with("  hold: avg "), "{report}");
+        assert_eq!(lines[3], "  readers: 1 now, 3 peak");
+        assert_eq!(lines[4], "  waiters: 0");
+        assert!(lock.stats().max_hold >= Duration::from_millis(5));
+        drop(reader);
+    }
+
+    #[tokio::test]
+    async fn clock_going_backwards_records_zero_hold() {
+        let base = std::time::Instant::now() + Duration::from_secs(60);
+        let readings = std::sync::Mutex::new(vec![base - Duration::from_secs(1), base]);
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                // 획득 때 `base`, 해제 때 그보다 1초 이른 시각을 준다.
+                .clock(move || readings.lock().unwrap().pop().unwrap())
+                .observer(events.clone()),
+        );
+        drop(lock.write().await);
+
+        let events = events.events();
+        let release = events
+            .iter()
+            .find(|e| e.kind == EventKind::Release)
+            .unwrap();
+        assert_eq!(release.held, Some(Duration::ZERO));
+        assert_eq!(lock.stats().total_hold, Duration::ZERO);
+        let warning = events
+            .iter()
+            .find(|e| e.kind == EventKind::Warning)
+            .unwrap();
+        assert!(warning
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("clock went backwards by 1s"));
+    }
+
+    #[tokio::test]
+    async fn with_config_keeps_builder_settings() {
+        let lock = TokioRwLockTrace::new(0)
+            .with_group("kept")
+            .with_timing(false)
+            .with_min_logged_hold(Duration::from_secs(1))
+            .with_strict_fifo()
+            .with_config(LockConfig::new().name("kept").silent());
+        assert_eq!(lock.name(), Some("kept"));
+        assert!(lock.config.untimed && lock.config.fifo.is_some());
+        assert_eq!(lock.config.min_logged_hold, Some(Duration::from_secs(1)));
+        *lock.write().await += 1;
+        assert_eq!(crate::registry::group_report("kept").unwrap().writes, 1);
+    }
+
+    #[tokio::test]
+    async fn with_config_only_overrides_what_it_sets() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().observer(events.clone()).quiet_peeks())
+            .with_config(LockConfig::new().name("renamed"));
+        assert_eq!(lock.name(), Some("renamed"));
+        assert!(lock.config.quiet_peeks);
+        drop(lock.read().await);
+        assert!(events
+            .events()
+            .iter()
+            .any(|e| e.kind == EventKind::Acquire && e.name.as_deref() == Some("renamed")));
+
+        let lock = lock.with_config(LockConfig::new().without_observers());
+        assert!(lock.config.observer.is_none());
+        assert_eq!(lock.name(), Some("renamed"));
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_bracket_acquisitions() {
+        let events = CapturingObserver::new();
+        let config = |name: &str| LockConfig::new().name(name).observer(events.clone());
+        let created_line = line!() + 1;
+        let lock = TokioRwLockTrace::new(0).with_config(config("churn"));
+        *lock.write().await += 1;
+        drop(lock.read().await);
+        let handle = lock.downgrade().upgrade().unwrap();
+        drop(handle);
+        // 레지스트리 스냅샷이나 감시 스레드가 잠깐 쥔 항목은 핸들로 치지 않는다.
+        let snapshot = lock.entry.clone();
+        drop(lock);
+        drop(snapshot);
+        // 한 번도 잡히지 않은 락도 생성과 소멸은 남긴다.
+        drop(TokioRwLockTrace::new(0).with_config(config("idle")));
+
+        let events = events.events();
+        let kinds: Vec<_> = events
+            .iter()
+            .map(|e| (e.kind, e.name.as_deref().unwrap()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (EventKind::Created, "churn"),
+                (EventKind::Acquire, "churn"),
+                (EventKind::Release, "churn"),
+                (EventKind::Acquire, "churn"),
+                (EventKind::Release, "churn"),
+                (EventKind::Destroyed, "churn"),
+                (EventKind::Created, "idle"),
+                (EventKind::Destroyed, "idle"),
+            ]
+        );
+        let created = &events[0];
+        assert!(created.timestamp <= events[1].timestamp);
+        assert_eq!(created.location.line(), created_line);
+        assert!(created.to_string().contains(&format!(
+            "lock 'churn' created at {}:{}",
+            file!(),
+            created_line
+        )));
+        assert_eq!(
+            events[5].message.as_deref(),
+            Some("2 acquisitions in total")
+        );
+        assert_eq!(
+            events[7].message.as_deref(),
+            Some("0 acquisitions in total")
+        );
+    }
+
+    #[tokio::test]
+    async fn release_async_awaits_hooks_before_unlocking() {
+        use tokio::sync::mpsc;
+
+        // 용량 1 인 채널을 미리 채워 두면 훅의 `send` 는 수신자가 비울 때까지 기다려야 한다.
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.try_send("queued").unwrap();
+        let lock =
+            TokioRwLockTrace::new(0).with_config(LockConfig::new().silent().on_release_async(
+                move |access: AccessKind| {
+                    let tx = tx.clone();
+                    async move {
+                        tx.send(if access == AccessKind::Write {
+                            "write"
+                        } else {
+                            "read"
+                        })
+                        .await
+                        .unwrap()
+                    }
+                },
+            ));
+
+        let mut guard = lock.write().await;
+        *guard += 1;
+        let drain = async {
+            tokio::task::yield_now().await;
+            // 훅이 기다리는 동안 락은 아직 잡혀 있다.
+            assert!(lock.try_write().is_err());
+            rx.recv().await
+        };
+        let ((), first) = tokio::join!(guard.release_async(), drain);
+        assert_eq!(first, Some("queued"));
+        assert_eq!(rx.recv().await, Some("write"));
+        assert!(lock.try_write().is_ok());
+
+        // 그냥 드롭하면 훅을 건너뛴다.
+        drop(lock.read().await);
+        assert!(rx.try_recv().is_err());
+        lock.read().await.release_async().await;
+        assert_eq!(rx.recv().await, Some("read"));
+    }
+
+    #[tokio::test]
+    async fn downgrade_emits_one_transition_instead_of_release_and_acquire() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().name("staged").observer(events.clone()));
+
+        let mut guard = lock.write_ctx("job-7").await;
+        *guard += 1;
+        let downgraded_line = line!() + 1;
+        let reader = guard.downgrade();
+        assert_eq!(*reader, 1);
+        assert!(lock.raw().try_read().is_ok());
+        assert!(lock.raw().try_write().is_err());
+        assert_eq!(lock.stats().readers, 1);
+        drop(reader);
+
+        let events = events.events();
+        let kinds: Vec<_> = events
+            .iter()
+            .skip(1)
+            .map(|e| (e.kind, e.access, e.previous))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (EventKind::Acquire, AccessKind::Write, None),
+                (
+                    EventKind::Transition,
+                    AccessKind::Read,
+                    Some(AccessKind::Write)
+                ),
+                (EventKind::Release, AccessKind::Read, None),
+            ]
+        );
+        let transition = events[2].to_string();
+        assert!(
+            transition.contains(&format!(
+                "lock 'staged' write→read downgrade [job-7] at {}:{}",
+                file!(),
+                downgraded_line
+            )),
+            "{transition}"
+        );
+        assert_eq!(events[3].context.as_deref(), Some("job-7"));
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes, stats.downgrades), (0, 1, 1));
+        assert_eq!(stats.readers, 0);
+    }
+
+    #[tokio::test]
+    async fn raw_access_bypasses_instrumentation() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+
+        *lock.raw().write().await += 1;
+        assert_eq!(*lock.raw().try_read().unwrap(), 1);
+        assert!(events.events().is_empty());
+        assert_eq!(lock.stats().reads + lock.stats().writes, 0);
+
+        *lock.write().await += 1;
+        // 생성, 획득, 해제
+        assert_eq!(events.events().len(), 3);
+        assert_eq!(lock.stats().writes, 1);
+        assert_eq!(*lock.raw().read().await, 2);
+    }
+
+    #[tokio::test]
+    async fn untimed_lock_never_reads_the_clock() {
+        let reads = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = reads.clone();
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(
+                LockConfig::new()
+                    .clock(move || {
+                        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        std::time::Instant::now()
+                    })
+                    .observer(events.clone()),
+            )
+            .with_timing(false);
+
+        drop(lock.read().await);
+        {
+            // writer 가 한 번은 기다리게 한다.
+            let reader = lock.read().await;
+            let release = async move {
+                tokio::task::yield_now().await;
+                drop(reader);
+            };
+            tokio::join!(release, async { *lock.write().await += 1 });
+        }
+        lock.try_write().unwrap().release();
+
+        assert_eq!(reads.load(std::sync::atomic::Ordering::Relaxed), 0);
+        let events = events.events();
+        assert_eq!(events[0].kind, EventKind::Created);
+        assert_eq!(events.len(), 5);
+        for event in &events[1..] {
+            assert_eq!(event.kind, EventKind::Acquire);
+            assert_eq!((event.held, event.waited), (None, None));
+            assert_eq!(event.scheduler_delay, None);
+        }
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (2, 2));
+        assert_eq!(
+            (stats.total_hold, stats.max_hold),
+            (Duration::ZERO, Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn guard_handed_through_functions_is_one_hold() {
+        use crate::LoggingRwLockWriteGuard;
+
+        fn validate(
+            guard: LoggingRwLockWriteGuard<'_, Vec<i32>>,
+        ) -> LoggingRwLockWriteGuard<'_, Vec<i32>> {
+            assert!(guard.len() <= 1);
+            guard
+        }
+
+        fn stage(
+            mut guard: LoggingRwLockWriteGuard<'_, Vec<i32>>,
+        ) -> LoggingRwLockWriteGuard<'_, Vec<i32>> {
+            guard.reborrow().push(1);
+            validate(guard)
+        }
+
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(Vec::new())
+            .with_config(LockConfig::new().observer(events.clone()));
+        let acquired_line = line!() + 1;
+        let guard = lock.write().await;
+        let mut guard = stage(validate(guard));
+        guard.push(2);
+        drop(guard);
+        assert_eq!(*lock.try_write().unwrap(), [1, 2]);
+
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().skip(1).take(2).map(|e| e.kind).collect();
+        assert_eq!(kinds, [EventKind::Acquire, EventKind::Release]);
+        let acquired_at = events[2].acquired_at.unwrap();
+        assert_eq!(
+            (acquired_at.file(), acquired_at.line()),
+            (file!(), acquired_line)
+        );
+        assert!(events[2].to_string().contains(&format!(
+            "(acquired at {}:{})",
+            file!(),
+            acquired_line
+        )));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn sequence_numbers_increase_across_concurrent_tasks() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0)
+                .with_config(LockConfig::new().silent().observer(events.clone())),
+        );
+        let tasks: Vec<_> = (0..8)
+            .map(|task| {
+                let lock = lock.clone();
+                tokio::spawn(async move {
+                    for _ in 0..20 {
+                        drop(lock.read_ctx(task).await);
+                        *lock.write_ctx(task).await += 1;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let events = events.events();
+        let mut seqs: Vec<_> = events.iter().map(|e| e.seq).collect();
+        seqs.sort_unstable();
+        seqs.dedup();
+        assert_eq!(seqs.len(), events.len(), "sequence numbers are unique");
+        // 한 태스크 안에서는 내보낸 순서대로 증가하고, 해제는 짝인 획득보다 뒤다.
+        for task in 0..8 {
+            let mine: Vec<_> = events
+                .iter()
+                .filter(|e| e.context == Some(task.to_string()))
+                .collect();
+            assert!(mine.windows(2).all(|pair| pair[0].seq < pair[1].seq));
+            assert_eq!(mine.first().unwrap().kind, EventKind::Acquire);
+        }
+    }
+
+    #[cfg(feature = "dry-run")]
+    #[tokio::test]
+    async fn dry_run_instruments_without_touching_value() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        let held = lock.write().await;
+        // 실제 락이 잡혀 있어도 더미 락은 바로 얻는다.
+        drop(lock.read_dry_run().await);
+        drop(lock.write_dry_run().await);
+        drop(held);
+
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        // 생성 이벤트 하나와 획득/해제 세 쌍
+        assert_eq!(kinds.len(), 7);
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn explicit_release_is_tagged_differently() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        lock.write().await.release();
+        drop(lock.write().await);
+        lock.read().await.release();
+
+        let events = events.events();
+        let releases: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Release)
+            .collect();
+        let reasons: Vec<_> = releases.iter().map(|e| e.reason).collect();
+        use crate::ReleaseReason::{Dropped, Explicit};
+        assert_eq!(reasons, [Some(Explicit), Some(Dropped), Some(Explicit)]);
+        assert!(releases[0].to_string().contains("released explicitly"));
+        assert!(!releases[1].to_string().contains("explicitly"));
+    }
+
+    #[tokio::test]
+    async fn tags_releases_abandoned_by_early_return() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(Vec::new()).with_config(
+            LockConfig::new()
+                .silent()
+                .tag_early_exits()
+                .observer(events.clone()),
+        );
+        use std::num::ParseIntError;
+        async fn append(
+            lock: &TokioRwLockTrace<Vec<u32>>,
+            input: &str,
+        ) -> Result<(), ParseIntError> {
+            let mut items = lock.write().await;
+            items.push(input.parse()?);
+            items.complete();
+            Ok(())
+        }
+        append(&lock, "1").await.unwrap();
+        assert!(append(&lock, "x").await.is_err());
+        lock.write().await.release();
+
+        let events = events.events();
+        let releases: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Release)
+            .collect();
+        let reasons: Vec<_> = releases.iter().map(|e| e.reason).collect();
+        use crate::ReleaseReason::{Dropped, EarlyExit, Explicit};
+        assert_eq!(reasons, [Some(Dropped), Some(EarlyExit), Some(Explicit)]);
+        assert!(releases[1].to_string().contains("released on early exit"));
+    }
+
+    #[test]
+    fn tags_releases_during_unwind() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .silent()
+                .tag_early_exits()
+                .observer(events.clone()),
+        );
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            runtime.block_on(async {
+                let _guard = lock.read().await;
+                panic!("boom");
+            })
+        }));
+        assert!(result.is_err());
+
+        let events = events.events();
+        let release = events
+            .iter()
+            .find(|e| e.kind == EventKind::Release)
+            .unwrap();
+        assert_eq!(release.reason, Some(crate::ReleaseReason::Unwound));
+    }
+
+    #[tokio::test]
+    async fn strict_fifo_grants_in_request_order() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(Vec::new())
+                .with_config(LockConfig::new().silent().observer(events.clone()))
+                .with_strict_fifo(),
+        );
+
+        let held = lock.write().await;
+        let mut tasks = Vec::new();
+        for (i, write) in [false, true, false, false, true, false]
+            .into_iter()
+            .enumerate()
+        {
+            let queued = lock.clone();
+            tasks.push(tokio::spawn(async move {
+                if write {
+                    queued.write().await.push(i);
+                } else {
+                    let _guard = queued.read().await;
+                    tokio::task::yield_now().await;
+                }
+            }));
+            // 다음 요청을 내기 전에 이 요청이 줄을 섰는지 확인한다.
+            while lock.stats().waiters < i as u64 + 1 {
+                tokio::task::yield_now().await;
+            }
+        }
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let events = events.events();
+        let granted: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Acquire)
+            .map(|e| (e.ticket, e.access))
+            .collect();
+        use AccessKind::{Read, Write};
+        let expected: Vec<_> = [Write, Read, Write, Read, Read, Write, Read]
+            .into_iter()
+            .enumerate()
+            .map(|(ticket, access)| (Some(ticket as u64), access))
+            .collect();
+        assert_eq!(granted, expected);
+        assert!(events[1].to_string().contains("(ticket 0)"));
+    }
+
+    #[tokio::test]
+    async fn strict_fifo_try_does_not_jump_the_queue() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().silent().observer(events.clone()))
+            .with_strict_fifo();
+
+        // 락은 비어 있지만 차례를 기다리는 요청이 있다.
+        let queued = lock.config.fifo.as_deref().unwrap().try_turn().unwrap();
+        assert!(lock.try_read().is_err());
+        assert!(lock.try_write().is_err());
+        drop(queued);
+
+        drop(lock.try_read().unwrap());
+        drop(lock.try_write().unwrap());
+        let tickets: Vec<_> = events
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Acquire)
+            .map(|e| e.ticket)
+            .collect();
+        assert_eq!(tickets, [Some(0), Some(1)]);
+    }
+
+    #[tokio::test]
+    async fn starved_writer_escalates_with_reader_sites() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(
+                LockConfig::new()
+                    .silent()
+                    .warn_reader_blocking_writer(Duration::from_millis(10))
+                    .escalate_writer_starvation(Duration::from_millis(30))
+                    .observer(events.clone()),
+            ),
+        );
+        let first_line = line!() + 1;
+        let first = lock.read().await;
+        let second_line = line!() + 1;
+        let second = lock.read().await;
+        let writer = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.write().await += 1 }
+        });
+        let starvation = || {
+            events
+                .events()
+                .iter()
+                .find(|e| e.kind == EventKind::Starvation)
+                .cloned()
+        };
+        let escalated = loop {
+            if let Some(event) = starvation() {
+                break event;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+        drop((first, second));
+        writer.await.unwrap();
+
+        let message = escalated.message.unwrap();
+        assert!(
+            message.starts_with("writer waited over 30ms; 2 reader(s)"),
+            "{message}"
+        );
+        for line in [first_line, second_line] {
+            assert!(
+                message.contains(&format!("{}:{}", file!(), line)),
+                "{message}"
+            );
+        }
+        assert!(escalated.waited.unwrap() >= Duration::from_millis(30));
+        // 놓을 때는 기존의 경고가 남고, 기아 이벤트는 writer 하나당 한 번이다.
+        let events = events.events();
+        let count = |kind| events.iter().filter(|e| e.kind == kind).count();
+        assert_eq!(count(EventKind::Starvation), 1);
+        assert_eq!(count(EventKind::Warning), 2);
+    }
+
+    #[tokio::test]
+    async fn slowest_keeps_the_worst_waits_and_holds() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let base = std::time::Instant::now();
+        let elapsed_ms = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let elapsed_ms = elapsed_ms.clone();
+            move || base + Duration::from_millis(elapsed_ms.load(Ordering::SeqCst))
+        };
+        let lock =
+            Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent().clock(clock)));
+        let hold_lines: Vec<_> = (0..12u64)
+            .map(|ms| {
+                let line = line!() + 1;
+                let guard = lock.try_write().unwrap();
+                elapsed_ms.fetch_add(ms, Ordering::SeqCst);
+                drop(guard);
+                line
+            })
+            .collect();
+        let holds = lock.slowest_by(SlowBy::Hold);
+        let held: Vec<_> = holds.iter().map(|s| s.duration.as_millis()).collect();
+        assert_eq!(held, [11, 10, 9, 8, 7, 6, 5, 4]);
+        assert!(holds
+            .iter()
+            .all(|s| s.by == SlowBy::Hold && s.location.line() == hold_lines[0]));
+        assert!(lock.slowest().is_empty());
+
+        let held = lock.write().await;
+        let reader_line = line!() + 3;
+        let reader = tokio::spawn({
+            let lock = lock.clone();
+            async move { drop(lock.read().await) }
+        });
+        while lock.stats().waiters == 0 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(held);
+        reader.await.unwrap();
+        let waits = lock.slowest();
+        assert_eq!(waits.len(), 1);
+        assert_eq!(waits[0].access, AccessKind::Read);
+        assert_eq!(waits[0].location.line(), reader_line);
+        assert!(waits[0].duration >= Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn locks_sort_by_hold_time() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let base = std::time::Instant::now();
+        let elapsed_ms = Arc::new(AtomicU64::new(0));
+        let clock = {
+            let elapsed_ms = elapsed_ms.clone();
+            move || base + Duration::from_millis(elapsed_ms.load(Ordering::SeqCst))
+        };
+        let locks: Vec<_> = ["short", "long", "idle", "medium"]
+            .into_iter()
+            .map(|name| {
+                TokioRwLockTrace::new(0)
+                    .with_config(LockConfig::new().name(name).silent().clock(clock.clone()))
+            })
+            .collect();
+        for (lock, ms) in locks.iter().zip([5, 50, 0, 20]) {
+            if ms > 0 {
+                let _guard = lock.write().await;
+                elapsed_ms.fetch_add(ms, Ordering::SeqCst);
+            }
+        }
+
+        let mut sorted: Vec<_> = locks.iter().collect();
+        sorted.sort_by_key(|lock| std::cmp::Reverse(lock.by_hold_time()));
+        let names: Vec<_> = sorted.iter().map(|lock| lock.name().unwrap()).collect();
+        assert_eq!(names, ["long", "medium", "short", "idle"]);
+        assert!(locks[2].by_hold_time() < locks[0].by_hold_time());
+    }
+
+    #[tokio::test]
+    async fn from_notes_an_already_held_lock() {
+        let raw = Arc::new(RwLock::new(0));
+        let created = |lock: TokioRwLockTrace<i32>| {
+            let events = CapturingObserver::new();
+            let lock = lock.with_config(LockConfig::new().silent().observer(events.clone()));
+            let _ = lock.try_write();
+            let first = events.events()[0].clone();
+            assert_eq!(first.kind, EventKind::Created);
+            first.message
+        };
+
+        assert_eq!(created(TokioRwLockTrace::from(raw.clone())), None);
+        let held = raw.write().await;
+        let message = created(TokioRwLockTrace::from(raw.clone())).unwrap();
+        assert!(message.contains("already write-held"), "{message}");
+        drop(held);
+        let _reader = raw.read().await;
+        let message = created(TokioRwLockTrace::from(raw.clone())).unwrap();
+        assert!(message.contains("already read-held"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn peek_emits_a_single_event() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(vec![1, 2, 3])
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        drop(lock.read().await);
+        events.clear();
+
+        let line = line!() + 1;
+        let sum = lock.peek(|numbers| numbers.iter().sum::<i32>()).await;
+        assert_eq!(sum, 6);
+        let peeked = events.take();
+        assert_eq!(peeked.len(), 1);
+        assert_eq!(peeked[0].kind, EventKind::Peek);
+        assert!(peeked[0].held.is_some());
+        assert_eq!(peeked[0].location.line(), line);
+        assert_eq!(lock.stats().reads, 2);
+        // 놓았으므로 바로 쓸 수 있다.
+        assert!(lock.try_write().is_ok());
+
+        let quiet = TokioRwLockTrace::new(1).with_config(
+            LockConfig::new()
+                .silent()
+                .quiet_peeks()
+                .observer(|e: &crate::LockEvent| {
+                    assert!(
+                        matches!(e.kind, EventKind::Created | EventKind::Destroyed),
+                        "{e}"
+                    );
+                }),
+        );
+        assert_eq!(quiet.peek(|value| *value + 1).await, 2);
+        assert_eq!(quiet.stats().reads, 1);
+    }
+
+    #[tokio::test]
+    async fn operation_id_stamps_events_inside_scope() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        crate::with_operation_id(17, async {
+            *lock.write().await += 1;
+            drop(lock.read().await);
+            crate::with_operation_id("inner", async { drop(lock.read().await) }).await;
+        })
+        .await;
+        drop(lock.read().await);
+
+        let operations: Vec<_> = events
+            .events()
+            .iter()
+            .map(|e| (e.kind, e.operation_id.clone()))
+            .collect();
+        let op = |id: &str| Some(id.to_string());
+        assert_eq!(
+            operations,
+            [
+                // 생성 이벤트는 처음 쓸 때 남는다.
+                (EventKind::Created, op("17")),
+                (EventKind::Acquire, op("17")),
+                (EventKind::Release, op("17")),
+                (EventKind::Acquire, op("17")),
+                (EventKind::Release, op("17")),
+                (EventKind::Acquire, op("inner")),
+                (EventKind::Release, op("inner")),
+                (EventKind::Acquire, None),
+                (EventKind::Release, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_depth_grows_with_recursion() {
+        fn nested(lock: &TokioRwLockTrace<i32>, levels: u32) {
+            let _frame = crate::enter_frame();
+            if levels == 0 {
+                drop(lock.try_write().unwrap());
+            } else {
+                nested(lock, levels - 1);
+            }
+        }
+
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .silent()
+                .record_stack_depth()
+                .observer(events.clone()),
+        );
+        nested(&lock, 0);
+        nested(&lock, 40);
+
+        let depths: Vec<_> = events
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Acquire)
+            .map(|e| e.stack_depth.unwrap())
+            .collect();
+        assert_eq!(depths, [1, 41]);
+    }
+
+    #[tokio::test]
+    async fn guards_pass_as_ref_and_as_mut() {
+        fn total(numbers: impl AsRef<Vec<u32>>) -> u32 {
+            numbers.as_ref().iter().sum()
+        }
+        fn push(mut numbers: impl AsMut<Vec<u32>>, n: u32) {
+            numbers.as_mut().push(n);
+        }
+
+        let lock = TokioRwLockTrace::new(vec![1, 2]).with_config(LockConfig::new().silent());
+        let mut guard = lock.write().await;
+        push(&mut guard, 3);
+        assert_eq!(total(&guard), 6);
+        push(guard, 4);
+        assert_eq!(total(lock.read().await), 10);
+    }
+
+    // `Unpin` 이 아닌 컨텍스트
+    struct PinnedTag(std::marker::PhantomPinned);
+
+    impl std::fmt::Display for PinnedTag {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("pinned")
+        }
+    }
+
+    #[test]
+    fn guards_are_unpin_whatever_the_context() {
+        fn assert_unpin<T: Unpin>() {}
+        assert_unpin::<crate::LoggingRwLockReadGuard<'static, i32>>();
+        assert_unpin::<crate::LoggingRwLockWriteGuard<'static, i32>>();
+        assert_unpin::<crate::LoggingRwLockReadGuard<'static, i32, Context<PinnedTag>>>();
+        assert_unpin::<crate::LoggingRwLockWriteGuard<'static, i32, Context<PinnedTag>>>();
+        assert_unpin::<crate::TracedWriteGuard<'static, i32>>();
+    }
+
+    #[tokio::test]
+    async fn guard_held_inside_pinned_future() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent()));
+        let (resume, resumed) = tokio::sync::oneshot::channel::<()>();
+        let owner = lock.clone();
+        // 락을 가진 Arc 와 그것을 빌린 가드가 같은 (pin 된) future 상태에 산다.
+        let mut task = Box::pin(async move {
+            let mut guard = owner.write_ctx(PinnedTag(std::marker::PhantomPinned)).await;
+            *guard += 1;
+            resumed.await.unwrap();
+            *guard += 1;
+            let value = *guard;
+            drop(guard);
+            value
+        });
+
+        assert!(futures::poll!(task.as_mut()).is_pending());
+        assert!(
+            lock.inner.try_read().is_err(),
+            "guard should be held across the await"
+        );
+        resume.send(()).unwrap();
+        assert_eq!(task.await, 2);
+        assert_eq!(*lock.read().await, 2);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_only_applies_to_the_output() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().rate_limit(20).observer(events.clone()));
+        for _ in 0..200 {
+            drop(lock.read().await);
+        }
+        // 관찰자는 생성 이벤트 하나와 획득/해제 400 개를 모두 받고, 버린 만큼의 요약 경고도 받는다.
+        let received = events
+            .events()
+            .iter()
+            .filter(|e| e.kind != EventKind::Warning)
+            .count();
+        assert_eq!(received, 401);
+        assert_eq!(lock.stats().reads, 200);
+        for _ in 0..300 {
+            if events.events().iter().any(|e| e.kind == EventKind::Warning) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let summary = events
+            .events()
+            .into_iter()
+            .find(|e| e.kind == EventKind::Warning)
+            .and_then(|e| e.message)
+            .unwrap();
+        assert!(summary.starts_with("suppressed "), "{summary}");
+    }
+
+    #[tokio::test]
+    async fn contention_ratio_rises_under_contention() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(
+                LockConfig::new()
+                    .silent()
+                    .warn_contention_ratio(0.5)
+                    .observer(events.clone()),
+            ),
+        );
+        assert_eq!(lock.contention_ratio(), 0.0);
+        for _ in 0..4 {
+            drop(lock.read().await);
+        }
+        assert_eq!(lock.contention_ratio(), 0.0);
+
+        let held = lock.write().await;
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let lock = lock.clone();
+                tokio::spawn(async move { drop(lock.read().await) })
+            })
+            .collect();
+        while lock.stats().waiters < 8 {
+            tokio::task::yield_now().await;
+        }
+        drop(held);
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        // 13 번 중 8 번이 기다렸다.
+        let ratio = lock.contention_ratio();
+        assert!((ratio - 8.0 / 13.0).abs() < 1e-9, "{ratio}");
+        // 문턱을 넘는 순간 한 번만 경고한다.
+        let warnings: Vec<_> = events
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Warning)
+            .filter_map(|e| e.message.clone())
+            .collect();
+        assert_eq!(warnings.len(), 1, "{warnings:?}");
+        assert!(warnings[0].contains("exceeded 0.50"), "{}", warnings[0]);
+    }
+
+    #[tokio::test]
+    async fn first_contention_is_stamped_once() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent()));
+        for _ in 0..3 {
+            *lock.write().await += 1;
+            drop(lock.read().await);
+        }
+        drop(lock.try_write().unwrap());
+        assert_eq!(lock.stats().first_contended, None);
+
+        let before = tokio::time::Instant::now();
+        let contend = || async {
+            let held = lock.write().await;
+            let reader = {
+                let lock = lock.clone();
+                tokio::spawn(async move { drop(lock.read().await) })
+            };
+            while lock.stats().waiters < 1 {
+                tokio::task::yield_now().await;
+            }
+            drop(held);
+            reader.await.unwrap();
+        };
+        contend().await;
+        let first = lock.stats().first_contended.expect("contended");
+        assert!(first >= before);
+        // 이후의 경합은 처음 시각을 바꾸지 않는다.
+        contend().await;
+        assert_eq!(lock.stats().first_contended, Some(first));
+    }
+
+    #[tokio::test]
+    async fn try_read_honours_writers_and_reader_limit() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_max_readers(1)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+
+        let writer = lock.write().await;
+        assert!(lock.try_read().is_err());
+        drop(writer);
+        let line = line!() + 1;
+        let reader = lock.try_read().unwrap();
+        assert_eq!(lock.stats().read_permits_in_use, 1);
+        assert!(lock.try_read().is_err());
+        assert_eq!(
+            lock.try_read_capped().err(),
+            Some(TryReadError::ReadersExhausted)
+        );
+        drop(reader);
+        assert_eq!(lock.stats().read_permits_in_use, 0);
+
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (1, 1));
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Busy,
+                EventKind::Release,
+                EventKind::Acquire,
+                EventKind::Busy,
+                EventKind::Busy,
+                EventKind::Release,
+            ]
+        );
+        assert_eq!(events[2].access, AccessKind::Read);
+        assert_eq!(events[2].message, None);
+        assert_eq!(events[4].location.line(), line);
+        // 허가가 모자라 실패한 것은 메시지로 알 수 있다.
+        assert!(events[5]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("no reader permits left"));
+        assert_eq!(events[7].acquired_at.map(|at| at.line()), Some(line));
+    }
+
+    #[test]
+    fn blocking_acquisitions_wait_outside_a_runtime() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent()));
+        let reader = lock.blocking_read();
+        let writer = std::thread::spawn({
+            let lock = lock.clone();
+            move || *lock.blocking_write() += 1
+        });
+        while lock.stats().waiters == 0 {
+            std::thread::yield_now();
+        }
+        assert_eq!(*reader, 0);
+        drop(reader);
+        writer.join().unwrap();
+        assert_eq!(*lock.blocking_read(), 1);
+        assert_eq!(lock.stats().writes, 1);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "within a runtime")]
+    async fn blocking_read_panics_inside_a_runtime() {
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().silent());
+        let _ = lock.blocking_read();
+    }
+
+    #[tokio::test]
+    async fn into_inner_and_get_mut_skip_the_lock() {
+        let events = CapturingObserver::new();
+        let mut lock = TokioRwLockTrace::new(vec![1])
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        lock.write().await.push(2);
+        lock.get_mut().push(3);
+        assert_eq!(lock.stats().writes, 1);
+        assert_eq!(lock.into_inner(), [1, 2, 3]);
+
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Release,
+                EventKind::Destroyed,
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "still shared")]
+    fn into_inner_panics_while_the_rwlock_is_shared() {
+        let shared = Arc::new(RwLock::new(0));
+        let lock = TokioRwLockTrace::from(shared.clone());
+        let _ = lock.into_inner();
+    }
+
+    #[test]
+    fn unnamed_thread_falls_back_to_thread_id() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        let id = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            runtime.block_on(async { drop(lock.read().await) });
+            std::thread::current().id()
+        })
+        .join()
+        .unwrap();
+
+        let events = events.events();
+        assert_eq!(events[0].thread, format!("{:?}", id));
+        assert!(events[0].to_string().contains("on thread ThreadId("));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn warns_when_guard_outlives_its_runtime() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async { drop(lock.read().await) });
+        let guard = runtime.block_on(lock.write());
+        runtime.shutdown_background();
+        drop(guard);
+
+        let events = events.events();
+        let warnings: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Warning)
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].access, AccessKind::Write);
+        assert!(warnings[0]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("dropped outside of it"));
+    }
+
+    #[test]
+    fn spans_carry_lock_fields() {
+        use std::collections::HashMap;
+        use std::sync::Mutex;
+        use tracing::field::{Field, Visit};
+        use tracing::span::{Attributes, Id, Record};
+
+        /// span 필드를 문자열로 모으는 최소 구독자
+        #[derive(Default)]
+        struct Fields(Mutex<Vec<(&'static str, HashMap<String, String>)>>);
+
+        struct Collect<'a>(&'a mut HashMap<String, String>);
+
+        impl Visit for Collect<'_> {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{:?}", value));
+            }
+
+            fn record_str(&mut self, field: &Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+        }
+
+        impl tracing::Subscriber for Fields {
+            fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes<'_>) -> Id {
+                let mut fields = HashMap::new();
+                span.record(&mut Collect(&mut fields));
+                let mut spans = self.0.lock().unwrap();
+                spans.push((span.metadata().name(), fields));
+                Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, span: &Id, values: &Record<'_>) {
+                let mut spans = self.0.lock().unwrap();
+                values.record(&mut Collect(&mut spans[span.into_u64() as usize - 1].1));
+            }
+
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, _: &tracing::Event<'_>) {}
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+
+        let subscriber = Arc::new(Fields::default());
+        let named = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("cache"));
+        let unnamed = TokioRwLockTrace::new(0);
+        tracing::subscriber::with_default(subscriber.clone(), || {
+            let _ = named.lock_span(AccessKind::Write);
+            let _ = unnamed.lock_span(AccessKind::Read);
+            let span = tracing::info_span!("request", lock = tracing::field::Empty);
+            span.record("lock", named.field());
+            let _ = tracing::info_span!("job", lock = unnamed.field());
+        });
+
+        let spans = subscriber.0.lock().unwrap();
+        let field = |i: usize, name: &str| spans[i].1.get(name).cloned();
+        assert_eq!(spans[0].0, "lock");
+        assert_eq!(field(0, "lock.name").as_deref(), Some("cache"));
+        assert_eq!(field(0, "lock.id"), Some(named.entry.id.to_string()));
+        assert_eq!(field(0, "lock.access").as_deref(), Some("write"));
+        assert_eq!(field(1, "lock.name"), None);
+        assert_eq!(field(1, "lock.access").as_deref(), Some("read"));
+        assert_eq!(field(2, "lock"), Some(format!("cache#{}", named.entry.id)));
+        assert_eq!(field(3, "lock"), Some(format!("#{}", unnamed.entry.id)));
+    }
+}
@@ -0,0 +1,56 @@
+/// 호출 위치와 스레드 이름을 담은 이벤트를 만들어 내보낸다.
+///
+/// `held`, `context` 처럼 생략 가능한 필드는 `필드: 값` 형태로 뒤에 붙인다. 필드 값은 이벤트를
+/// 받을 곳이 있을 때만 평가된다.
+macro_rules! log_call_info {
+    ($config:expr, $($rest:tt)*) => {{
+        let config = $config;
+        if let Some(event) = lock_event!(config, $($rest)*) {
+            $crate::event::emit(config, event);
+        }
+    }};
+}
+
+/// `log_call_info!` 와 같은 이벤트를 만들기만 하고 내보내지 않는다.
+///
+/// 받을 곳이 없으면(`event::enabled`) 위치, 스레드 이름 등을 만들지 않고 `None` 이 된다.
+macro_rules! lock_event {
+    ($config:expr, $kind:expr, $access:expr $(, $field:ident: $value:expr)* $(,)?) => {
+        if $crate::event::enabled($config, $kind) {
+        let location = std::panic::Location::caller();
+        let thread_name = $crate::task::thread_name();
+        #[allow(unused_mut)]
+        let mut event = $crate::event::LockEvent {
+            seq: 0,
+            kind: $kind,
+            access: $access,
+            name: $config.name.clone(),
+            location,
+            thread: thread_name,
+            timestamp: std::time::SystemTime::now(),
+            held: None,
+            context: None,
+            message: None,
+            depth: None,
+            waited: None,
+            scheduler_delay: None,
+            reason: None,
+            acquired_at: None,
+            trace_id: None,
+            span_id: None,
+            previous: None,
+            priority: None,
+            accesses: None,
+            ticket: None,
+            stack_depth: None,
+            operation_id: $crate::task::operation_id(),
+        };
+        $(event.$field = $value;)*
+        #[cfg(feature = "otel")]
+        $crate::otel::stamp(&mut event);
+        $crate::event::location_allowed(&event).then_some(event)
+        } else {
+            None
+        }
+    };
+}
@@ -0,0 +1,141 @@
+//! 락마다 런타임에 계측을 끄고 켤 수 있는 쓰기 가드.
+
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    sync::atomic::Ordering,
+};
+
+use tokio::sync::RwLockWriteGuard;
+
+use crate::guard::LoggingRwLockWriteGuard;
+use crate::lock::TokioRwLockTrace;
+
+/// `write_maybe` 가 돌려주는 쓰기 가드. 계측이 켜져 있으면 보통의 계측 가드이고, 꺼져 있으면
+/// tokio 가드를 그대로 감싼다.
+///
+/// 어느 쪽이든 같은 타입이므로 호출부의 시그니처를 바꾸지 않고 뜨거운 경로의 계측을 끌 수 있다.
+// 계측 경로에 할당을 더하지 않으려고 큰 쪽을 상자에 담지 않는다.
+#[allow(clippy::large_enum_variant)]
+pub enum MaybeTraced<'a, T> {
+    Traced(LoggingRwLockWriteGuard<'a, T>),
+    /// 시계를 읽지 않고 이벤트와 통계도 남기지 않는다.
+    Raw(RwLockWriteGuard<'a, T>),
+}
+
+impl<T> MaybeTraced<'_, T> {
+    pub fn is_traced(&self) -> bool {
+        matches!(self, MaybeTraced::Traced(_))
+    }
+}
+
+impl<T> Deref for MaybeTraced<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            MaybeTraced::Traced(guard) => guard,
+            MaybeTraced::Raw(guard) => guard,
+        }
+    }
+}
+
+impl<T> DerefMut for MaybeTraced<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            MaybeTraced::Traced(guard) => guard,
+            MaybeTraced::Raw(guard) => guard,
+        }
+    }
+}
+
+impl<T> TokioRwLockTrace<T> {
+    /// 이 락의 계측을 런타임에 켜고 끈다. 기본값은 켜짐.
+    ///
+    /// 꺼져 있으면 `write_maybe` 가 계측 없는 가드를 돌려준다. `read()`/`write()` 는 영향을
+    /// 받지 않는다.
+    pub fn set_instrumented(&self, enabled: bool) {
+        self.entry.uninstrumented.store(!enabled, Ordering::Relaxed);
+    }
+
+    /// 계측이 켜져 있으면 `write()` 처럼, 꺼져 있으면 `raw().write()` 처럼 쓰기 락을 얻는다.
+    ///
+    /// 어느 쪽일지는 획득을 시작할 때(`write_maybe` 를 부를 때) 정해진다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// lock.set_instrumented(false);
+    /// let mut guard = lock.write_maybe().await;
+    /// *guard += 1;
+    /// assert!(!guard.is_traced());
+    /// # drop(guard);
+    /// assert_eq!(lock.stats().writes, 0);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn write_maybe(&self) -> impl Future<Output = MaybeTraced<'_, T>> + '_
+    where
+        T: Send + Sync,
+    {
+        let traced = (!self.entry.uninstrumented.load(Ordering::Relaxed)).then(|| self.write());
+        async move {
+            match traced {
+                Some(write) => MaybeTraced::Traced(write.await),
+                None => MaybeTraced::Raw(self.inner.write().await),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use std::time::Instant;
+
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig, TokioRwLockTrace};
+
+    #[tokio::test]
+    async fn uninstrumented_guard_does_no_timing_work() {
+        let clock_reads = Arc::new(AtomicUsize::new(0));
+        let counter = clock_reads.clone();
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .silent()
+                .clock(move || {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                    Instant::now()
+                })
+                .observer(events.clone()),
+        );
+
+        lock.set_instrumented(false);
+        for _ in 0..3 {
+            let mut guard = lock.write_maybe().await;
+            *guard += 1;
+            assert!(!guard.is_traced());
+        }
+        assert_eq!(clock_reads.load(Ordering::Relaxed), 0);
+        assert!(events.events().is_empty());
+        assert_eq!(lock.stats().writes, 0);
+
+        lock.set_instrumented(true);
+        {
+            let mut guard = lock.write_maybe().await;
+            *guard += 1;
+            assert!(guard.is_traced());
+        }
+        assert!(clock_reads.load(Ordering::Relaxed) > 0);
+        assert_eq!(lock.stats().writes, 1);
+        assert!(events.events().iter().any(|e| e.kind == EventKind::Release));
+        assert_eq!(*lock.raw().read().await, 4);
+    }
+}
@@ -0,0 +1,184 @@
+//! 획득/대기/점유를 `metrics` 파사드로 내보낸다. (`metrics` 기능)
+//!
+//! 이름은 `prometheus` 기능의 출력과 같게 맞춘다. 레이블은 락 이름(`lock`, 없으면 빈 문자열)과
+//! 접근 종류(`kind`)뿐이다. id 를 빼 둔 것은 락이 자주 만들어지고 사라질 때 시계열이 끝없이
+//! 늘지 않게 하려는 것이다.
+
+use std::panic::Location;
+use std::sync::{Arc, PoisonError};
+use std::time::Duration;
+
+use metrics::{Key, Label, Level, Metadata};
+
+use crate::event::AccessKind;
+use crate::registry::Entry;
+
+static METADATA: Metadata<'static> =
+    Metadata::new(module_path!(), Level::INFO, Some(module_path!()));
+
+/// 락 하나가 쓰는 키. 획득마다 레이블을 새로 만들지 않도록 이름이 바뀔 때만 다시 만든다.
+/// recorder 가 없으면 키를 빌려 주기만 하므로 할당이 생기지 않는다.
+#[derive(Debug)]
+pub(crate) struct Keys {
+    // [read, write]
+    acquisitions: [Key; 2],
+    wait: [Key; 2],
+    hold: [Key; 2],
+}
+
+impl Keys {
+    pub(crate) fn new(name: Option<&str>) -> Arc<Keys> {
+        let name = name.unwrap_or("").to_owned();
+        let keys = |metric: &'static str| {
+            ["read", "write"].map(|kind| {
+                Key::from_parts(
+                    metric,
+                    vec![
+                        Label::new("lock", name.clone()),
+                        Label::from_static_parts("kind", kind),
+                    ],
+                )
+            })
+        };
+        Arc::new(Keys {
+            acquisitions: keys("tracing_lock_acquisitions_total"),
+            wait: keys("tracing_lock_wait_seconds"),
+            hold: keys("tracing_lock_hold_seconds"),
+        })
+    }
+}
+
+fn keys(entry: &Entry) -> Arc<Keys> {
+    entry
+        .metric_keys
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+fn index(access: AccessKind) -> usize {
+    match access {
+        AccessKind::Read => 0,
+        AccessKind::Write => 1,
+    }
+}
+
+pub(crate) fn set_name(entry: &Entry, name: Option<&str>) {
+    *entry
+        .metric_keys
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Keys::new(name);
+}
+
+/// 획득 한 번. 기다린 시간을 쟀으면 대기 히스토그램에도 넣는다.
+pub(crate) fn acquired(entry: &Entry, access: AccessKind, waited: Option<Duration>) {
+    let keys = keys(entry);
+    let i = index(access);
+    ::metrics::with_recorder(|recorder| {
+        recorder
+            .register_counter(&keys.acquisitions[i], &METADATA)
+            .increment(1);
+        if let Some(waited) = waited {
+            recorder
+                .register_histogram(&keys.wait[i], &METADATA)
+                .record(waited.as_secs_f64());
+        }
+    });
+}
+
+/// 가드를 놓음. 쥔 시간을 쟀을 때만 점유 히스토그램에 넣는다.
+pub(crate) fn released(entry: &Entry, access: AccessKind, held: Option<Duration>) {
+    let Some(held) = held else {
+        return;
+    };
+    let keys = keys(entry);
+    ::metrics::with_recorder(|recorder| {
+        recorder
+            .register_histogram(&keys.hold[index(access)], &METADATA)
+            .record(held.as_secs_f64());
+    });
+}
+
+/// 호출 위치 하나가 쓰는 키. 이름과 위치가 바뀌지 않으므로 처음 볼 때 한 번만 만든다.
+/// (`LockConfig::track_sites`)
+#[derive(Debug)]
+pub(crate) struct SiteKeys {
+    // [read, write]
+    acquisitions: [Key; 2],
+    holders: [Key; 2],
+    wait: Key,
+    hold: Key,
+}
+
+impl SiteKeys {
+    pub(crate) fn new(name: Option<&str>, location: &Location<'_>) -> SiteKeys {
+        let labels = vec![
+            Label::new("lock", name.unwrap_or("").to_owned()),
+            Label::new("site", format!("{}:{}", location.file(), location.line())),
+        ];
+        let by_kind = |metric: &'static str| {
+            ["read", "write"].map(|kind| {
+                let mut labels = labels.clone();
+                labels.push(Label::from_static_parts("kind", kind));
+                Key::from_parts(metric, labels)
+            })
+        };
+        SiteKeys {
+            acquisitions: by_kind("tracing_lock_site_acquisitions_total"),
+            holders: by_kind("tracing_lock_site_holders"),
+            wait: Key::from_parts("tracing_lock_site_wait_seconds", labels.clone()),
+            hold: Key::from_parts("tracing_lock_site_hold_seconds", labels),
+        }
+    }
+}
+
+/// 호출 위치에서의 획득 한 번과, 그 뒤 이 위치에서 얻어 쥐고 있는 가드 수
+pub(crate) fn site_acquired(
+    keys: &SiteKeys,
+    access: AccessKind,
+    waited: Option<Duration>,
+    holders: u64,
+) {
+    let i = index(access);
+    ::metrics::with_recorder(|recorder| {
+        recorder
+            .register_counter(&keys.acquisitions[i], &METADATA)
+            .increment(1);
+        recorder
+            .register_gauge(&keys.holders[i], &METADATA)
+            .set(holders as f64);
+        if let Some(waited) = waited {
+            recorder
+                .register_histogram(&keys.wait, &METADATA)
+                .record(waited.as_secs_f64());
+        }
+    });
+}
+
+/// 호출 위치에서 얻은 가드를 놓음
+pub(crate) fn site_released(
+    keys: &SiteKeys,
+    access: AccessKind,
+    held: Option<Duration>,
+    holders: u64,
+) {
+    ::metrics::with_recorder(|recorder| {
+        recorder
+            .register_gauge(&keys.holders[index(access)], &METADATA)
+            .set(holders as f64);
+        if let Some(held) = held {
+            recorder
+                .register_histogram(&keys.hold, &METADATA)
+                .record(held.as_secs_f64());
+        }
+    });
+}
+
+/// 호출 위치에서 얻은 가드가 `downgrade` 등으로 다른 접근 종류로 옮겨 감
+pub(crate) fn site_holders(keys: &SiteKeys, access: AccessKind, holders: u64) {
+    ::metrics::with_recorder(|recorder| {
+        recorder
+            .register_gauge(&keys.holders[index(access)], &METADATA)
+            .set(holders as f64);
+    });
+}
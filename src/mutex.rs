@@ -0,0 +1,174 @@
+//! 계측된 `tokio::sync::Mutex`.
+
+use std::future::Future;
+
+use tokio::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::probe::{Probe, TracedGuard};
+use crate::stats::LockStatsSnapshot;
+
+/// `TracedMutex::lock` 이 돌려주는 가드
+pub type TracedMutexGuard<'a, T> = TracedGuard<'a, MutexGuard<'a, T>>;
+
+/// 획득/해제 시점과 점유 시간을 기록하는 `tokio::sync::Mutex`.
+///
+/// 이벤트와 통계는 `TokioRwLockTrace` 의 쓰기 쪽과 같다. 모든 획득이 `AccessKind::Write` 로 남는다.
+///
+/// ```
+/// use tracing_lock::{LockConfig, TracedMutex};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let queue = TracedMutex::new(Vec::new()).with_config(LockConfig::new().name("queue"));
+/// queue.lock().await.push(1);
+/// assert_eq!(queue.stats().writes, 1);
+/// # }
+/// ```
+pub struct TracedMutex<T> {
+    probe: Probe,
+    inner: Mutex<T>,
+}
+
+impl<T> TracedMutex<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        TracedMutex {
+            probe: Probe::new(),
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    /// 락을 기다린다. 호출 위치는 future 를 만든 곳이다.
+    #[track_caller]
+    pub fn lock(&self) -> impl Future<Output = TracedMutexGuard<'_, T>> + '_ {
+        let waiting = self.probe.wait(AccessKind::Write);
+        async move {
+            let (guard, contended) = match self.inner.try_lock() {
+                Ok(guard) => (guard, false),
+                Err(_) => {
+                    let _blocked = self.probe.blocked(&waiting, AccessKind::Write);
+                    (self.inner.lock().await, true)
+                }
+            };
+            self.probe
+                .acquired(waiting, guard, AccessKind::Write, contended)
+        }
+    }
+
+    /// 기다리지 않고 시도한다. 이미 잡혀 있으면 `Busy` 이벤트를 남기고 실패한다.
+    #[track_caller]
+    pub fn try_lock(&self) -> Result<TracedMutexGuard<'_, T>, TryLockError> {
+        match self.inner.try_lock() {
+            Ok(guard) => Ok(self.probe.acquired(
+                self.probe.wait(AccessKind::Write),
+                guard,
+                AccessKind::Write,
+                false,
+            )),
+            Err(err) => {
+                self.probe.busy(AccessKind::Write);
+                Err(err)
+            }
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::EventKind;
+
+    #[tokio::test]
+    async fn guard_can_be_held_across_await_in_spawned_task() {
+        let lock = Arc::new(TracedMutex::new(0).with_config(LockConfig::new().silent()));
+        let task = {
+            let lock = lock.clone();
+            tokio::spawn(async move {
+                let mut guard = lock.lock().await;
+                tokio::task::yield_now().await;
+                *guard += 1;
+            })
+        };
+        task.await.unwrap();
+        assert_eq!(*lock.lock().await, 1);
+    }
+
+    #[tokio::test]
+    async fn contended_lock_records_wait_and_hold() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TracedMutex::new(0).with_config(
+                LockConfig::new()
+                    .name("counter")
+                    .silent()
+                    .observer(events.clone()),
+            ),
+        );
+
+        let held = lock.lock().await;
+        assert!(lock.try_lock().is_err());
+        let waiter = {
+            let lock = lock.clone();
+            tokio::spawn(async move { *lock.lock().await += 1 })
+        };
+        while lock.stats().waiters < 1 {
+            tokio::task::yield_now().await;
+        }
+        drop(held);
+        waiter.await.unwrap();
+
+        let stats = lock.stats();
+        assert_eq!((stats.writes, stats.waiters), (2, 0));
+        assert!(stats.first_contended.is_some());
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Busy,
+                EventKind::Release,
+                EventKind::Acquire,
+                EventKind::Release,
+            ]
+        );
+        assert!(events.iter().all(|e| e.name.as_deref() == Some("counter")));
+        // 해제 이벤트는 가드를 얻은 위치로 짝을 맞춘다.
+        for event in &events[1..] {
+            let site = event.acquired_at.unwrap_or(event.location);
+            assert_eq!(site.file(), file!(), "{event}");
+        }
+        assert_eq!(events[1].waited, Some(std::time::Duration::ZERO));
+        assert!(events[4].waited.is_some_and(|waited| !waited.is_zero()));
+        assert!(events[5].held.is_some());
+    }
+}
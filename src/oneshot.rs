@@ -0,0 +1,118 @@
+//! 한 번만 쓰도록 정해진 락.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::future::{ReadFuture, WriteFuture};
+use crate::lock::TokioRwLockTrace;
+use crate::stats::LockStatsSnapshot;
+
+/// 쓰기 락을 딱 한 번(예: 초기화 때)만 잡도록 정한 계측 락.
+///
+/// 두 번째 쓰기부터는 `Misuse` 이벤트를 남긴다. 락 자체는 평소처럼 동작하므로 규칙 위반을
+/// 드러낼 뿐 막지는 않는다.
+///
+/// ```
+/// use tracing_lock::OneShotWriteLock;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let routes = OneShotWriteLock::new(Vec::new());
+/// routes.write().await.push("/health"); // 설정 단계
+/// assert!(routes.is_written());
+/// assert_eq!(routes.read().await.len(), 1);
+/// # }
+/// ```
+pub struct OneShotWriteLock<T> {
+    lock: TokioRwLockTrace<T>,
+    written: AtomicBool,
+}
+
+impl<T> OneShotWriteLock<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        OneShotWriteLock {
+            lock: TokioRwLockTrace::new(value),
+            written: AtomicBool::new(false),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.lock = self.lock.with_config(config);
+        self
+    }
+
+    /// 쓰기 락을 이미 한 번 요청했는지
+    pub fn is_written(&self) -> bool {
+        self.written.load(Ordering::Acquire)
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.lock.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.lock.stats()
+    }
+
+    #[track_caller]
+    pub fn read(&self) -> ReadFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        self.lock.read()
+    }
+
+    /// 쓰기 락을 획득한다. 두 번째 호출부터는 호출 위치와 함께 `Misuse` 이벤트를 남긴다.
+    #[track_caller]
+    pub fn write(&self) -> WriteFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        if self.written.swap(true, Ordering::AcqRel) {
+            log_call_info!(
+                &self.lock.config,
+                EventKind::Misuse,
+                AccessKind::Write,
+                message: Some("one-shot lock written more than once".to_string()),
+            );
+        }
+        self.lock.write()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+
+    #[tokio::test]
+    async fn second_write_logs_misuse_but_still_writes() {
+        let events = CapturingObserver::new();
+        let lock = OneShotWriteLock::new(0)
+            .with_config(LockConfig::new().name("setup").observer(events.clone()));
+
+        *lock.write().await = 1;
+        drop(lock.read().await);
+        assert!(!events.events().iter().any(|e| e.kind == EventKind::Misuse));
+
+        let misuse_line = line!() + 1;
+        *lock.write().await = 2;
+        assert_eq!(*lock.read().await, 2);
+        assert_eq!(lock.stats().writes, 2);
+
+        let events = events.events();
+        let misuses: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Misuse)
+            .collect();
+        assert_eq!(misuses.len(), 1);
+        assert_eq!(misuses[0].location.line(), misuse_line);
+        assert!(misuses[0]
+            .to_string()
+            .contains("Write lock 'setup' misused: one-shot lock written more than once"));
+    }
+}
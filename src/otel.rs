@@ -0,0 +1,85 @@
+//! 이벤트에 OpenTelemetry trace/span id 를 붙인다. (`otel` 기능)
+
+use opentelemetry::trace::{SpanContext, TraceContextExt};
+use opentelemetry::Context;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::event::{EventKind, LockEvent};
+
+/// 획득/해제 이벤트에 현재 컨텍스트의 id 를 적는다. 유효한 span 이 없으면 그대로 둔다.
+pub(crate) fn stamp(event: &mut LockEvent) {
+    if !matches!(event.kind, EventKind::Acquire | EventKind::Release) {
+        return;
+    }
+    if let Some(span) = current_span() {
+        event.trace_id = Some(span.trace_id().to_string());
+        event.span_id = Some(span.span_id().to_string());
+    }
+}
+
+// `tracing-opentelemetry` 레이어가 없으면 `tracing` span 의 컨텍스트는 비어 있다.
+fn current_span() -> Option<SpanContext> {
+    let from_tracing = tracing::Span::current().context();
+    [from_tracing, Context::current()]
+        .into_iter()
+        .map(|cx| cx.span().span_context().clone())
+        .find(SpanContext::is_valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::trace::{SpanId, TraceFlags, TraceId, TraceState};
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::{LockConfig, TokioRwLockTrace};
+
+    fn record() -> (CapturingObserver, TokioRwLockTrace<i32>) {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        (events, lock)
+    }
+
+    #[tokio::test]
+    async fn active_context_ids_appear_on_acquire_and_release() {
+        let (events, lock) = record();
+        let span = SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        );
+        let attached = Context::new().with_remote_span_context(span).attach();
+        drop(lock.write().await);
+        drop(attached);
+
+        let events = events.events();
+        // 생성 이벤트에는 붙이지 않는다.
+        assert_eq!(events[0].kind, EventKind::Created);
+        assert_eq!(events[0].trace_id, None);
+        assert_eq!(events.len(), 3);
+        for event in &events[1..] {
+            assert_eq!(
+                event.trace_id.as_deref(),
+                Some("4bf92f3577b34da6a3ce929d0e0e4736")
+            );
+            assert_eq!(event.span_id.as_deref(), Some("00f067aa0ba902b7"));
+        }
+        assert!(events[2]
+            .to_string()
+            .contains("(trace 4bf92f3577b34da6a3ce929d0e0e4736, span 00f067aa0ba902b7)"));
+    }
+
+    #[tokio::test]
+    async fn no_context_leaves_ids_empty() {
+        let (events, lock) = record();
+        drop(lock.read().await);
+
+        let events = events.events();
+        assert!(events
+            .iter()
+            .all(|e| e.trace_id.is_none() && e.span_id.is_none()));
+    }
+}
@@ -0,0 +1,295 @@
+//! `Arc<TokioRwLockTrace<T>>` 에서 얻는, 락을 빌리지 않는 가드.
+//!
+//! 안쪽은 tokio 의 `read_owned`/`write_owned` 가드이고, 계측 상태(설정과 레지스트리 항목)도 `Arc` 로
+//! 나눠 가진다. 빌린 가드와 같은 `GuardState` 를 쓰므로 시간 재기, 이벤트, 통계가 똑같다.
+
+use std::{
+    future::Future,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::Arc,
+};
+
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard};
+
+use crate::event::{AccessKind, ReleaseReason};
+use crate::future::{acquisition, State};
+use crate::guard::GuardState;
+use crate::lock::TokioRwLockTrace;
+use crate::stats::ReadPermit;
+
+// 획득 future 는 `Send` 이므로 값이 스레드를 건널 수 있어야 한다.
+impl<T: Send + Sync> TokioRwLockTrace<T> {
+    /// `read` 와 같지만 가드가 `Arc` 를 쥐므로 락을 빌리지 않는다. `'static` 태스크로 옮길 때 쓴다.
+    ///
+    /// tokio 의 `read_owned` 처럼 이름 없는 future 를 돌려준다. 획득 전에 드롭되면 취소 이벤트를
+    /// 남긴다.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = Arc::new(TokioRwLockTrace::new(1));
+    /// let guard = lock.clone().read_owned().await;
+    /// tokio::spawn(async move { assert_eq!(*guard, 1) }).await.unwrap();
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn read_owned(
+        self: Arc<Self>,
+    ) -> impl Future<Output = OwnedLoggingRwLockReadGuard<T>> + Send {
+        let location = Location::caller();
+        async move {
+            let (guard, permit, state) = {
+                let acquire = acquisition(
+                    &self.config,
+                    &self.entry,
+                    self.read_permits.as_ref(),
+                    self.inner.clone().read_owned(),
+                );
+                let mut state = State::new(
+                    acquire,
+                    &self.config,
+                    &self.entry,
+                    AccessKind::Read,
+                    location,
+                    (),
+                );
+                std::future::poll_fn(|cx| state.poll_guard(cx)).await
+            };
+            OwnedLoggingRwLockReadGuard {
+                state,
+                guard,
+                _permit: permit,
+                lock: self,
+            }
+        }
+    }
+
+    /// `write` 와 같지만 가드가 `Arc` 를 쥐므로 락을 빌리지 않는다. (`read_owned` 참고)
+    #[track_caller]
+    pub fn write_owned(
+        self: Arc<Self>,
+    ) -> impl Future<Output = OwnedLoggingRwLockWriteGuard<T>> + Send {
+        let location = Location::caller();
+        async move {
+            let (guard, permit, state) = {
+                let acquire = acquisition(
+                    &self.config,
+                    &self.entry,
+                    self.read_permits.as_ref(),
+                    self.inner.clone().write_owned(),
+                );
+                let mut state = State::new(
+                    acquire,
+                    &self.config,
+                    &self.entry,
+                    AccessKind::Write,
+                    location,
+                    (),
+                );
+                std::future::poll_fn(|cx| state.poll_guard(cx)).await
+            };
+            OwnedLoggingRwLockWriteGuard {
+                state,
+                guard,
+                _permit: permit,
+                lock: self,
+            }
+        }
+    }
+}
+
+/// `read_owned` 가 반환하는 읽기 가드. `LoggingRwLockReadGuard` 와 같이 드롭 시 점유 시간을 남긴다.
+pub struct OwnedLoggingRwLockReadGuard<T> {
+    // 빌린 가드처럼 해제 기록, 락, 허가 순으로 드롭된다.
+    state: GuardState<()>,
+    guard: OwnedRwLockReadGuard<T>,
+    _permit: Option<ReadPermit>,
+    lock: Arc<TokioRwLockTrace<T>>,
+}
+
+/// `write_owned` 가 반환하는 쓰기 가드. `LoggingRwLockWriteGuard` 와 같이 드롭 시 점유 시간을 남긴다.
+pub struct OwnedLoggingRwLockWriteGuard<T> {
+    state: GuardState<()>,
+    guard: OwnedRwLockWriteGuard<T>,
+    _permit: Option<ReadPermit>,
+    lock: Arc<TokioRwLockTrace<T>>,
+}
+
+impl<T> OwnedLoggingRwLockReadGuard<T> {
+    /// 이 가드가 쥔 락
+    pub fn rwlock(&self) -> &Arc<TokioRwLockTrace<T>> {
+        &self.lock
+    }
+
+    /// 명시적 해제로 표시된 해제 이벤트를 남긴다. (`LoggingRwLockReadGuard::release`)
+    pub fn release(mut self) {
+        self.state.reason = ReleaseReason::Explicit;
+    }
+
+    /// 임계 구역이 끝까지 진행됐다고 표시한다. (`LoggingRwLockReadGuard::complete`)
+    pub fn complete(&mut self) {
+        self.state.completed = true;
+    }
+}
+
+impl<T> OwnedLoggingRwLockWriteGuard<T> {
+    /// 이 가드가 쥔 락
+    pub fn rwlock(&self) -> &Arc<TokioRwLockTrace<T>> {
+        &self.lock
+    }
+
+    /// 명시적 해제로 표시된 해제 이벤트를 남긴다. (`LoggingRwLockWriteGuard::release`)
+    pub fn release(mut self) {
+        self.state.reason = ReleaseReason::Explicit;
+    }
+
+    /// 임계 구역이 끝까지 진행됐다고 표시한다. (`LoggingRwLockWriteGuard::complete`)
+    pub fn complete(&mut self) {
+        self.state.completed = true;
+    }
+
+    /// 쓰기 락을 놓지 않고 읽기 가드로 바꾼다. (`LoggingRwLockWriteGuard::downgrade`)
+    #[track_caller]
+    pub fn downgrade(self) -> OwnedLoggingRwLockReadGuard<T> {
+        let Self {
+            mut state,
+            guard,
+            _permit,
+            lock,
+        } = self;
+        state.downgrade();
+        OwnedLoggingRwLockReadGuard {
+            state,
+            guard: guard.downgrade(),
+            _permit,
+            lock,
+        }
+    }
+}
+
+impl<T> Deref for OwnedLoggingRwLockReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.state.touch();
+        &self.guard
+    }
+}
+
+impl<T> Deref for OwnedLoggingRwLockWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.state.touch();
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for OwnedLoggingRwLockWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.state.touch();
+        &mut self.guard
+    }
+}
+
+impl<T> AsRef<T> for OwnedLoggingRwLockReadGuard<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsRef<T> for OwnedLoggingRwLockWriteGuard<T> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for OwnedLoggingRwLockWriteGuard<T> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig};
+
+    #[tokio::test]
+    async fn owned_guards_log_like_borrowed_ones() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(
+                LockConfig::new()
+                    .name("owned")
+                    .silent()
+                    .observer(events.clone()),
+            ),
+        );
+
+        let line = line!() + 1;
+        let mut writer = lock.clone().write_owned().await;
+        *writer += 1;
+        let reader = tokio::spawn(async move {
+            let reader = writer.downgrade();
+            assert_eq!(*reader, 1);
+            reader
+        })
+        .await
+        .unwrap();
+        assert!(lock.try_write().is_err());
+        drop(reader);
+        drop(lock.clone().read_owned().await);
+
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes, stats.downgrades), (1, 1, 1));
+        assert_eq!(Arc::strong_count(&lock), 1);
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Transition,
+                EventKind::Busy,
+                EventKind::Release,
+                EventKind::Acquire,
+                EventKind::Release,
+            ]
+        );
+        assert_eq!(events[1].location.line(), line);
+        assert_eq!(events[4].acquired_at.map(|at| at.line()), Some(line));
+        assert!(events[4].held.is_some());
+    }
+
+    #[tokio::test]
+    async fn dropping_a_pending_owned_future_keeps_the_lock_alive() {
+        let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().silent()));
+        let writer = lock.write().await;
+        let mut pending = Box::pin(lock.clone().read_owned());
+        assert!(futures::poll!(pending.as_mut()).is_pending());
+        drop(pending);
+        drop(writer);
+        assert_eq!(Arc::strong_count(&lock), 1);
+        assert_eq!(*lock.clone().read_owned().await, 0);
+    }
+
+    #[tokio::test]
+    async fn owned_guards_hold_borrowed_values() {
+        // 값이 `'static` 이 아니어도 owned 가드를 얻을 수 있다.
+        let name = String::from("local");
+        let lock =
+            Arc::new(TokioRwLockTrace::new(name.as_str()).with_config(LockConfig::new().silent()));
+        let writer = lock.clone().write_owned().await;
+        let reader = writer.downgrade();
+        assert_eq!(*reader, "local");
+        drop(reader);
+        assert_eq!(lock.stats().downgrades, 1);
+    }
+}
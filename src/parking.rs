@@ -0,0 +1,224 @@
+//! 계측된 `parking_lot::Mutex`/`RwLock`. (`parking-lot` 기능)
+//!
+//! `std-sync` 래퍼와 같지만 오염이 없으므로 가드를 바로 돌려준다.
+
+use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::probe::{Probe, TracedGuard};
+use crate::stats::LockStatsSnapshot;
+
+/// `TracedParkingLotMutex::lock` 이 돌려주는 가드
+pub type TracedParkingLotMutexGuard<'a, T> = TracedGuard<'a, MutexGuard<'a, T>>;
+/// `TracedParkingLotRwLock::read` 가 돌려주는 가드
+pub type TracedParkingLotReadGuard<'a, T> = TracedGuard<'a, RwLockReadGuard<'a, T>>;
+/// `TracedParkingLotRwLock::write` 가 돌려주는 가드
+pub type TracedParkingLotWriteGuard<'a, T> = TracedGuard<'a, RwLockWriteGuard<'a, T>>;
+
+// 먼저 기다리지 않고 시도해서 경합 여부를 가린다.
+#[track_caller]
+fn acquire<'a, G>(
+    probe: &'a Probe,
+    access: AccessKind,
+    attempt: impl FnOnce() -> Option<G>,
+    blocking: impl FnOnce() -> G,
+) -> TracedGuard<'a, G> {
+    let waiting = probe.wait(access);
+    let (guard, contended) = match attempt() {
+        Some(guard) => (guard, false),
+        None => {
+            let _blocked = probe.blocked(&waiting, access);
+            (blocking(), true)
+        }
+    };
+    probe.acquired(waiting, guard, access, contended)
+}
+
+#[track_caller]
+fn try_acquire<G>(
+    probe: &Probe,
+    access: AccessKind,
+    attempt: Option<G>,
+) -> Option<TracedGuard<'_, G>> {
+    match attempt {
+        Some(guard) => Some(probe.acquired(probe.wait(access), guard, access, false)),
+        None => {
+            probe.busy(access);
+            None
+        }
+    }
+}
+
+/// 획득/해제 시점과 점유 시간을 기록하는 `parking_lot::Mutex`.
+///
+/// ```
+/// use tracing_lock::TracedParkingLotMutex;
+///
+/// let hits = TracedParkingLotMutex::new(0);
+/// *hits.lock() += 1;
+/// assert_eq!(hits.stats().writes, 1);
+/// ```
+pub struct TracedParkingLotMutex<T> {
+    probe: Probe,
+    inner: Mutex<T>,
+}
+
+impl<T> TracedParkingLotMutex<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        TracedParkingLotMutex {
+            probe: Probe::new(),
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    #[track_caller]
+    pub fn lock(&self) -> TracedParkingLotMutexGuard<'_, T> {
+        acquire(
+            &self.probe,
+            AccessKind::Write,
+            || self.inner.try_lock(),
+            || self.inner.lock(),
+        )
+    }
+
+    /// 기다리지 않고 시도한다. 이미 잡혀 있으면 `Busy` 이벤트를 남기고 `None` 이다.
+    #[track_caller]
+    pub fn try_lock(&self) -> Option<TracedParkingLotMutexGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Write, self.inner.try_lock())
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+/// 획득/해제 시점과 점유 시간을 기록하는 `parking_lot::RwLock`.
+///
+/// ```
+/// use tracing_lock::TracedParkingLotRwLock;
+///
+/// let config = TracedParkingLotRwLock::new("v1");
+/// *config.write() = "v2";
+/// assert_eq!(*config.read(), "v2");
+/// ```
+pub struct TracedParkingLotRwLock<T> {
+    probe: Probe,
+    inner: RwLock<T>,
+}
+
+impl<T> TracedParkingLotRwLock<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        TracedParkingLotRwLock {
+            probe: Probe::new(),
+            inner: RwLock::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    #[track_caller]
+    pub fn read(&self) -> TracedParkingLotReadGuard<'_, T> {
+        acquire(
+            &self.probe,
+            AccessKind::Read,
+            || self.inner.try_read(),
+            || self.inner.read(),
+        )
+    }
+
+    #[track_caller]
+    pub fn write(&self) -> TracedParkingLotWriteGuard<'_, T> {
+        acquire(
+            &self.probe,
+            AccessKind::Write,
+            || self.inner.try_write(),
+            || self.inner.write(),
+        )
+    }
+
+    #[track_caller]
+    pub fn try_read(&self) -> Option<TracedParkingLotReadGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Read, self.inner.try_read())
+    }
+
+    #[track_caller]
+    pub fn try_write(&self) -> Option<TracedParkingLotWriteGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Write, self.inner.try_write())
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::EventKind;
+
+    #[test]
+    fn readers_and_writers_share_the_instrumentation() {
+        let events = CapturingObserver::new();
+        let lock = TracedParkingLotRwLock::new(vec![1]).with_config(
+            LockConfig::new()
+                .name("table")
+                .silent()
+                .observer(events.clone()),
+        );
+        let (a, b) = (lock.read(), lock.read());
+        assert_eq!(lock.stats().readers, 2);
+        assert!(lock.try_write().is_none());
+        drop((a, b));
+        lock.write().push(2);
+        assert_eq!(lock.read().len(), 2);
+
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes, stats.peak_readers), (3, 1, 2));
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert_eq!(kinds.iter().filter(|k| **k == EventKind::Busy).count(), 1);
+        assert_eq!(
+            kinds.iter().filter(|k| **k == EventKind::Acquire).count(),
+            kinds.iter().filter(|k| **k == EventKind::Release).count()
+        );
+    }
+}
@@ -0,0 +1,1330 @@
+//! 계측하지 않는 래퍼들. (`trace` 기능을 끈 빌드)
+//!
+//! 원래 primitive 를 그대로 감싸고 가드도 원래 가드를 얇게 감쌀 뿐이므로, 시계를 읽지 않고 이벤트도
+//! 통계도 없다. `trace` 를 끄면 `TracedRwLock`, `TracedMutex`, `TracedSemaphore`, `std-sync`/
+//! `parking-lot` 래퍼와 그 가드의 이름이 이 타입들을 가리킨다.
+//!
+//! 호출부를 고치지 않아도 되도록 계측 쪽의 공개 API 를 모두 같은 이름으로 둔다. 설정 메서드는 받기만
+//! 하고 버리고, `stats`/`report`/`slowest`/`lock_span` 같은 진단 메서드는 빈 값을, `read_ctx`/
+//! `write_prio` 처럼 계측할 거리만 더하는 획득은 그것을 버린 획득을 돌려준다. 가드의 `release`/
+//! `complete` 는 드롭과 같고, `downgrade`, `wait_while`, `read_fresh`, `write_backoff` 처럼 동작이
+//! 있는 API 는 계측 없이 같은 동작을 한다.
+
+use std::{
+    fmt::Display,
+    future::Future,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::{
+        atomic::{fence, AtomicUsize, Ordering},
+        Arc, PoisonError, Weak,
+    },
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{
+    AcquireError, Mutex, MutexGuard, Notify, OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock,
+    RwLockReadGuard, RwLockWriteGuard, Semaphore, SemaphorePermit, TryAcquireError, TryLockError,
+};
+
+use crate::backoff::{Contended, DEFAULT_BASE, DEFAULT_CAP};
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::lock::TryReadError;
+use crate::maybe::MaybeTraced;
+use crate::slowest::{SlowAcquisition, SlowBy};
+use crate::stats::LockStatsSnapshot;
+
+// 모든 래퍼가 같은 모양으로 받아서 버리는 설정 메서드
+macro_rules! ignored_config {
+    () => {
+        /// 설정을 버린다.
+        #[inline]
+        pub fn with_config(self, _config: LockConfig) -> Self {
+            self
+        }
+
+        /// 이름을 두지 않으므로 언제나 `None` 이다.
+        #[inline]
+        pub fn name(&self) -> Option<&str> {
+            None
+        }
+
+        /// 세지 않으므로 언제나 빈 스냅샷이다.
+        #[inline]
+        pub fn stats(&self) -> LockStatsSnapshot {
+            LockStatsSnapshot::default()
+        }
+    };
+}
+
+// 한 락의 핸들(`downgrade` 한 약한 참조 포함)과 쓰기 가드가 나눠 쓰는 상태
+#[derive(Default)]
+struct Shared {
+    // 다음 쓰기를 기다리는 태스크 수. 0 이면 쓰기 가드가 `written` 을 건드리지 않는다.
+    waiting: AtomicUsize,
+    written: Notify,
+    // `read_fresh` 가 마지막으로 값을 채운 때
+    refreshed: std::sync::Mutex<Option<Instant>>,
+}
+
+impl Shared {
+    // 쓰기 락을 놓은 뒤에 부른다.
+    fn wrote(&self) {
+        // `Waiting::new` 의 fence 와 짝을 이뤄, 기다리는 쪽이 이 쓰기 전의 락을 봤다면 여기서
+        // 그 등록이 보인다.
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) > 0 {
+            self.written.notify_waiters();
+        }
+    }
+
+    fn refreshed_within(&self, max_age: Duration) -> bool {
+        self.refreshed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some_and(|at| at.elapsed() <= max_age)
+    }
+}
+
+// 다음 쓰기를 기다리는 동안 살려 둔다. 만든 뒤에 락을 확인해야 그 사이의 쓰기를 놓치지 않는다.
+struct Waiting<'a>(&'a Shared);
+
+impl<'a> Waiting<'a> {
+    fn new(shared: &'a Shared) -> Self {
+        shared.waiting.fetch_add(1, Ordering::SeqCst);
+        fence(Ordering::SeqCst);
+        Waiting(shared)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.waiting.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+// 쓰기 가드가 풀릴 때 기다리는 태스크를 깨운다. 가드에는 `Drop` 이 없어야 `downgrade` 가 필드를
+// 옮길 수 있으므로 여기에 둔다. `None` 이면 깨울 곳이 없는 락(`borrow`, 더미 락)이다.
+struct Written<S: Deref<Target = Shared>>(Option<S>);
+
+impl<S: Deref<Target = Shared>> Drop for Written<S> {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.0 {
+            shared.wrote();
+        }
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedRwLock` 이 가리키는 타입
+pub struct PassthroughRwLock<T> {
+    inner: Arc<RwLock<T>>,
+    shared: Arc<Shared>,
+    #[cfg(feature = "dry-run")]
+    dry_run: RwLock<()>,
+}
+
+impl<T> PassthroughRwLock<T> {
+    /// 기존의 RwLock 을 감싼다.
+    #[inline]
+    pub fn from(inner: Arc<RwLock<T>>) -> Self {
+        Self::with_shared(inner, Arc::default())
+    }
+
+    fn with_shared(inner: Arc<RwLock<T>>, shared: Arc<Shared>) -> Self {
+        PassthroughRwLock {
+            inner,
+            shared,
+            #[cfg(feature = "dry-run")]
+            dry_run: RwLock::new(()),
+        }
+    }
+
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self::from(Arc::new(RwLock::new(value)))
+    }
+
+    ignored_config!();
+
+    /// 설정을 버린다.
+    #[inline]
+    pub fn with_timing(self, _enabled: bool) -> Self {
+        self
+    }
+
+    /// 설정을 버린다.
+    #[inline]
+    pub fn with_min_logged_hold(self, _floor: Duration) -> Self {
+        self
+    }
+
+    /// 설정을 버린다.
+    #[inline]
+    pub fn with_group(self, _group: impl Into<String>) -> Self {
+        self
+    }
+
+    /// 설정을 버린다. tokio 의 `RwLock` 은 이미 기다리는 순서대로 락을 넘겨주므로 나중에 온
+    /// reader 가 먼저 온 writer 를 앞지르지 않는다.
+    #[inline]
+    pub fn with_strict_fifo(self) -> Self {
+        self
+    }
+
+    /// 설정을 버린다.
+    #[cfg(feature = "chaos")]
+    #[inline]
+    pub fn with_chaos(self, _seed: u64) -> Self {
+        self
+    }
+
+    /// 동시 읽기 상한은 동작에 영향을 주므로 버리지 않고 tokio 의 상한으로 옮긴다. tokio 가 받는
+    /// 가장 큰 상한(`u32::MAX >> 3`)보다 크면 그 값으로 줄인다.
+    ///
+    /// # Panics
+    ///
+    /// `max` 가 0 이거나, `from` 으로 감싼 `RwLock` 을 다른 곳에서도 쥐고 있으면 패닉한다.
+    pub fn with_max_readers(self, max: usize) -> Self {
+        assert!(max > 0, "max_readers must be at least 1");
+        let max = u32::try_from(max).unwrap_or(u32::MAX).min(u32::MAX >> 3);
+        Self::from(Arc::new(RwLock::with_max_readers(self.into_inner(), max)))
+    }
+
+    /// `#[derive(TracedLocks)]` 전용. 이름을 두지 않으므로 아무것도 하지 않는다.
+    #[doc(hidden)]
+    #[inline]
+    pub fn __name_if_unnamed(&mut self, _name: &str) {}
+
+    /// 세지 않으므로 언제나 빈 문자열이다.
+    #[inline]
+    pub fn report(&self) -> String {
+        String::new()
+    }
+
+    /// 빌린 `RwLock` 을 감싼다.
+    #[inline]
+    pub fn borrow(inner: &RwLock<T>) -> PassthroughBorrowedRwLock<'_, T> {
+        PassthroughBorrowedRwLock { inner }
+    }
+
+    #[inline]
+    pub fn raw(&self) -> &RwLock<T> {
+        &self.inner
+    }
+
+    /// # Panics
+    ///
+    /// `from` 으로 감싼 `Arc<RwLock<T>>` 의 다른 복제본이 남아 있으면 패닉한다.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        Arc::get_mut(&mut self.inner)
+            .expect("get_mut on a lock whose RwLock is still shared")
+            .get_mut()
+    }
+
+    /// # Panics
+    ///
+    /// `get_mut` 과 같이, 감싼 `RwLock` 을 다른 곳에서도 쥐고 있으면 패닉한다.
+    pub fn into_inner(self) -> T {
+        match Arc::try_unwrap(self.inner) {
+            Ok(lock) => lock.into_inner(),
+            Err(_) => panic!("into_inner on a lock whose RwLock is still shared"),
+        }
+    }
+
+    #[inline]
+    pub async fn read(&self) -> PassthroughReadGuard<'_, T> {
+        PassthroughReadGuard::new(self.inner.read().await)
+    }
+
+    /// 읽기 락을 잡아 `f` 에 값을 빌려주고, `f` 가 끝나는 즉시 놓는다.
+    pub async fn peek<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&*self.inner.read().await)
+    }
+
+    #[inline]
+    pub async fn write(&self) -> PassthroughWriteGuard<'_, T> {
+        self.write_guard(self.inner.write().await)
+    }
+
+    #[inline]
+    pub fn blocking_read(&self) -> PassthroughReadGuard<'_, T> {
+        PassthroughReadGuard::new(self.inner.blocking_read())
+    }
+
+    #[inline]
+    pub fn blocking_write(&self) -> PassthroughWriteGuard<'_, T> {
+        self.write_guard(self.inner.blocking_write())
+    }
+
+    /// 늘 비어 있는 더미 락의 읽기 락을 잡는다.
+    #[cfg(feature = "dry-run")]
+    #[inline]
+    pub async fn read_dry_run(&self) -> PassthroughReadGuard<'_, ()> {
+        PassthroughReadGuard::new(self.dry_run.read().await)
+    }
+
+    /// 늘 비어 있는 더미 락의 쓰기 락을 잡는다. 쓰기를 기다리는 태스크를 깨우지 않는다.
+    #[cfg(feature = "dry-run")]
+    #[inline]
+    pub async fn write_dry_run(&self) -> PassthroughWriteGuard<'_, ()> {
+        PassthroughWriteGuard::new(self.dry_run.write().await, None)
+    }
+
+    /// `ctx` 를 버리고 읽기 락을 획득한다.
+    #[inline]
+    pub async fn read_ctx<C: Display>(&self, _ctx: C) -> PassthroughReadGuard<'_, T> {
+        self.read().await
+    }
+
+    /// `ctx` 를 버리고 쓰기 락을 획득한다.
+    #[inline]
+    pub async fn write_ctx<C: Display>(&self, _ctx: C) -> PassthroughWriteGuard<'_, T> {
+        self.write().await
+    }
+
+    /// 우선순위를 버리고 쓰기 락을 획득한다.
+    #[inline]
+    pub async fn write_prio(&self, _prio: u8) -> PassthroughWriteGuard<'_, T> {
+        self.write().await
+    }
+
+    /// 기록할 곳이 없으므로 언제나 `Span::none()` 이다.
+    #[inline]
+    pub fn lock_span(&self, _access: AccessKind) -> tracing::Span {
+        tracing::Span::none()
+    }
+
+    /// 재지 않으므로 언제나 비어 있다.
+    #[inline]
+    pub fn slowest(&self) -> Vec<SlowAcquisition> {
+        Vec::new()
+    }
+
+    /// 재지 않으므로 언제나 비어 있다.
+    #[inline]
+    pub fn slowest_by(&self, _by: SlowBy) -> Vec<SlowAcquisition> {
+        Vec::new()
+    }
+
+    /// 재지 않으므로 모든 락의 키가 같다.
+    #[inline]
+    pub fn by_hold_time(&self) -> impl Ord + Copy {
+        0u64
+    }
+
+    /// 재지 않으므로 언제나 0 이다.
+    #[inline]
+    pub fn contention_ratio(&self) -> f64 {
+        0.0
+    }
+
+    /// 아무것도 기록하지 않는 필드 값
+    #[inline]
+    pub fn field(&self) -> impl tracing::Value {
+        tracing::field::Empty
+    }
+
+    #[inline]
+    pub fn try_write(&self) -> Result<PassthroughWriteGuard<'_, T>, TryLockError> {
+        self.inner.try_write().map(|guard| self.write_guard(guard))
+    }
+
+    /// 지금 읽기 락을 기다리지 않고 얻을 수 있어야 한다. 아니면 패닉한다. (`test-util` 기능)
+    #[cfg(any(test, feature = "test-util"))]
+    #[track_caller]
+    pub fn assert_read_uncontended(&self) {
+        if self.inner.try_read().is_err() {
+            panic!("read on an untraced lock would block: it is write-held");
+        }
+    }
+
+    #[inline]
+    pub fn try_read(&self) -> Result<PassthroughReadGuard<'_, T>, TryLockError> {
+        self.inner.try_read().map(PassthroughReadGuard::new)
+    }
+
+    /// 원래 락은 까닭을 가리지 않으므로 `with_max_readers` 의 상한에 걸려도 `TryReadError::Locked`
+    /// 로 실패한다.
+    #[inline]
+    pub fn try_read_capped(&self) -> Result<PassthroughReadGuard<'_, T>, TryReadError> {
+        self.try_read().map_err(TryReadError::from)
+    }
+
+    /// `try_lock_all!` 전용
+    #[doc(hidden)]
+    #[inline]
+    pub fn __try_write_in_bundle(&self) -> Option<PassthroughWriteGuard<'_, T>> {
+        self.try_write().ok()
+    }
+
+    /// 쓰기 락을 잡고 값을 바꾼 뒤 이전 값을 돌려준다.
+    pub async fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.write().await, value)
+    }
+
+    /// 기다리지 않고 값을 바꾼다. 쓰기 락을 얻지 못하면 `value` 를 그대로 돌려준다.
+    pub fn try_replace(&self, value: T) -> Result<T, T> {
+        match self.try_write() {
+            Ok(mut guard) => Ok(std::mem::replace(&mut *guard, value)),
+            Err(_) => Err(value),
+        }
+    }
+
+    /// 대기열에 서지 않고 `try_write` 를 최대 `max_attempts` 번 되풀이한다. (0 이면 한 번)
+    ///
+    /// 설정을 버리므로 쉬는 시간은 언제나 기본값(1ms 에서 두 배씩, 100ms 까지)이다.
+    pub async fn write_backoff(
+        &self,
+        max_attempts: u32,
+    ) -> Result<PassthroughWriteGuard<'_, T>, Contended> {
+        let attempts = max_attempts.max(1);
+        let mut delay = DEFAULT_BASE;
+        for attempt in 1..=attempts {
+            if let Ok(guard) = self.try_write() {
+                return Ok(guard);
+            }
+            if attempt < attempts {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2).min(DEFAULT_CAP);
+            }
+        }
+        Err(Contended { attempts })
+    }
+
+    /// 값을 살려 두지 않는 약한 참조를 만든다.
+    pub fn downgrade(&self) -> PassthroughWeakRwLock<T> {
+        PassthroughWeakRwLock {
+            inner: Arc::downgrade(&self.inner),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// `pred` 가 참인 동안 기다렸다가, 거짓이 된 값을 쥔 읽기 가드를 돌려준다.
+    ///
+    /// 읽기 락을 쥔 채 `pred` 를 확인하고, 참이면 락을 놓고 다음 쓰기 해제까지 잠든다.
+    pub async fn wait_while(&self, pred: impl Fn(&T) -> bool) -> PassthroughReadGuard<'_, T> {
+        loop {
+            let _waiting = Waiting::new(&self.shared);
+            let notified = self.shared.written.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let guard = self.read().await;
+                if !pred(&guard) {
+                    return guard;
+                }
+            }
+            notified.await;
+        }
+    }
+
+    /// 진행 중이거나 기다리는 쓰기가 끝나기를 기다렸다가 읽기 가드를 돌려준다.
+    pub async fn read_after_next_write(&self) -> PassthroughReadGuard<'_, T> {
+        let _waiting = Waiting::new(&self.shared);
+        let notified = self.shared.written.notified();
+        tokio::pin!(notified);
+        notified.as_mut().enable();
+        // tokio 의 `RwLock` 은 공정하므로 writer 가 쥐고 있거나 기다리는 동안 `try_read` 가 실패한다.
+        if self.inner.try_read().is_err() {
+            notified.await;
+        }
+        self.read().await
+    }
+
+    /// `read_fresh` 로 채운 값이 `max_age` 안이면 바로 읽기 가드를 돌려주고, 아니면 쓰기 락을 잡고
+    /// `refresh` 로 값을 새로 채운 뒤 그 가드를 읽기로 바꿔 돌려준다.
+    ///
+    /// 쓰기 시각을 재지 않으므로 다른 쓰기는 값을 새롭게 만들지 않는다. 나이는 이 메서드가 마지막으로
+    /// 값을 채운 때부터 잰다.
+    pub async fn read_fresh<F, Fut>(
+        &self,
+        max_age: Duration,
+        refresh: F,
+    ) -> PassthroughReadGuard<'_, T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        {
+            let guard = self.read().await;
+            if self.shared.refreshed_within(max_age) {
+                return guard;
+            }
+        }
+        let mut guard = self.write().await;
+        if !self.shared.refreshed_within(max_age) {
+            *guard = refresh().await;
+            *self
+                .shared
+                .refreshed
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(Instant::now());
+        }
+        guard.downgrade()
+    }
+
+    /// 쓰기 시각을 재지 않으므로 언제나 `None` 이다.
+    #[inline]
+    pub fn since_last_write(&self) -> Option<Duration> {
+        None
+    }
+
+    /// 읽기 락을 기다리다가 `token` 이 먼저 취소되면 `Err(Cancelled)` 를 돌려준다.
+    #[cfg(feature = "cancel")]
+    pub async fn read_with_cancel(
+        &self,
+        token: &tokio_util::sync::CancellationToken,
+    ) -> Result<PassthroughReadGuard<'_, T>, crate::cancel::Cancelled> {
+        tokio::select! {
+            biased;
+            guard = self.read() => Ok(guard),
+            _ = token.cancelled() => Err(crate::cancel::Cancelled),
+        }
+    }
+
+    /// 쓰기 락을 기다리다가 `token` 이 먼저 취소되면 `Err(Cancelled)` 를 돌려준다.
+    #[cfg(feature = "cancel")]
+    pub async fn write_with_cancel(
+        &self,
+        token: &tokio_util::sync::CancellationToken,
+    ) -> Result<PassthroughWriteGuard<'_, T>, crate::cancel::Cancelled> {
+        tokio::select! {
+            biased;
+            guard = self.write() => Ok(guard),
+            _ = token.cancelled() => Err(crate::cancel::Cancelled),
+        }
+    }
+
+    /// 읽기 락을 얻은 뒤 `f` 를 부르고 곧바로 해제한다.
+    pub async fn with_read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&*self.read().await)
+    }
+
+    /// 쓰기 락을 얻은 뒤 `f` 를 부르고 곧바로 해제한다.
+    pub async fn with_write<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut *self.write().await)
+    }
+
+    /// 계측이 없으므로 아무것도 하지 않는다.
+    #[inline]
+    pub fn set_instrumented(&self, _enabled: bool) {}
+
+    /// 언제나 계측 없는 가드(`MaybeTraced::Raw`)를 돌려준다.
+    #[inline]
+    pub async fn write_maybe(&self) -> MaybeTraced<'_, T> {
+        MaybeTraced::Raw(self.inner.write().await)
+    }
+
+    fn write_guard<'a>(&'a self, guard: RwLockWriteGuard<'a, T>) -> PassthroughWriteGuard<'a, T> {
+        PassthroughWriteGuard::new(guard, Some(&self.shared))
+    }
+}
+
+impl<T> PassthroughRwLock<T> {
+    /// 가드가 감싼 `RwLock` 의 `Arc` 를 쥔다.
+    pub async fn read_owned(self: Arc<Self>) -> PassthroughOwnedReadGuard<T> {
+        PassthroughOwnedReadGuard {
+            guard: self.inner.clone().read_owned().await,
+            lock: self,
+        }
+    }
+
+    /// 가드가 감싼 `RwLock` 의 `Arc` 를 쥔다.
+    pub async fn write_owned(self: Arc<Self>) -> PassthroughOwnedWriteGuard<T> {
+        PassthroughOwnedWriteGuard {
+            guard: self.inner.clone().write_owned().await,
+            written: Written(Some(self.shared.clone())),
+            lock: self,
+        }
+    }
+}
+
+/// `PassthroughRwLock::borrow` 로 만든, 빌린 `RwLock` 의 래퍼
+pub struct PassthroughBorrowedRwLock<'a, T> {
+    inner: &'a RwLock<T>,
+}
+
+impl<T> PassthroughBorrowedRwLock<'_, T> {
+    ignored_config!();
+
+    #[inline]
+    pub async fn read(&self) -> PassthroughReadGuard<'_, T> {
+        PassthroughReadGuard::new(self.inner.read().await)
+    }
+
+    #[inline]
+    pub async fn write(&self) -> PassthroughWriteGuard<'_, T> {
+        PassthroughWriteGuard::new(self.inner.write().await, None)
+    }
+}
+
+/// `PassthroughRwLock::downgrade` 로 만든 약한 참조
+pub struct PassthroughWeakRwLock<T> {
+    inner: Weak<RwLock<T>>,
+    shared: Arc<Shared>,
+}
+
+impl<T> PassthroughWeakRwLock<T> {
+    /// 락이 아직 살아 있으면 같은 값을 공유하는 핸들을 돌려준다.
+    pub fn upgrade(&self) -> Option<PassthroughRwLock<T>> {
+        let inner = self.inner.upgrade()?;
+        Some(PassthroughRwLock::with_shared(inner, self.shared.clone()))
+    }
+}
+
+impl<T> Clone for PassthroughWeakRwLock<T> {
+    fn clone(&self) -> Self {
+        PassthroughWeakRwLock {
+            inner: self.inner.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedRwLockReadGuard` 가 가리키는 타입
+pub struct PassthroughReadGuard<'a, T> {
+    guard: RwLockReadGuard<'a, T>,
+}
+
+impl<'a, T> PassthroughReadGuard<'a, T> {
+    #[inline]
+    fn new(guard: RwLockReadGuard<'a, T>) -> Self {
+        PassthroughReadGuard { guard }
+    }
+
+    /// 가드를 놓는다. `drop(guard)` 과 같다.
+    #[inline]
+    pub fn release(self) {}
+
+    /// 훅이 없으므로 가드를 바로 놓는다.
+    #[inline]
+    pub async fn release_async(self) {}
+
+    /// 기록하지 않으므로 아무것도 하지 않는다.
+    #[inline]
+    pub fn complete(&mut self) {}
+
+    /// 가드를 넘기지 않고 값만 빌려준다. `&*guard` 와 같다.
+    #[inline]
+    pub fn reborrow(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> Deref for PassthroughReadGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> AsRef<T> for PassthroughReadGuard<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedRwLockWriteGuard` 가 가리키는 타입
+pub struct PassthroughWriteGuard<'a, T> {
+    // 락을 먼저 놓은 뒤에 기다리는 태스크를 깨운다.
+    guard: RwLockWriteGuard<'a, T>,
+    written: Written<&'a Shared>,
+}
+
+impl<'a, T> PassthroughWriteGuard<'a, T> {
+    #[inline]
+    fn new(guard: RwLockWriteGuard<'a, T>, shared: Option<&'a Shared>) -> Self {
+        PassthroughWriteGuard {
+            guard,
+            written: Written(shared),
+        }
+    }
+
+    /// 가드를 놓는다. `drop(guard)` 과 같다.
+    #[inline]
+    pub fn release(self) {}
+
+    /// 훅이 없으므로 가드를 바로 놓는다.
+    #[inline]
+    pub async fn release_async(self) {}
+
+    /// 기록하지 않으므로 아무것도 하지 않는다.
+    #[inline]
+    pub fn complete(&mut self) {}
+
+    /// 가드를 넘기지 않고 값만 가변으로 빌려준다. `&mut *guard` 와 같다.
+    #[inline]
+    pub fn reborrow(&mut self) -> &mut T {
+        &mut self.guard
+    }
+
+    /// 쓰기 락을 놓지 않고 읽기 가드로 바꾼다. 다른 writer 가 끼어들 틈이 없다.
+    pub fn downgrade(self) -> PassthroughReadGuard<'a, T> {
+        let Self { guard, written } = self;
+        let guard = guard.downgrade();
+        drop(written);
+        PassthroughReadGuard::new(guard)
+    }
+}
+
+impl<T> Deref for PassthroughWriteGuard<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for PassthroughWriteGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<T> AsRef<T> for PassthroughWriteGuard<'_, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for PassthroughWriteGuard<'_, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+/// `PassthroughRwLock::read_owned` 가 돌려주는 가드
+pub struct PassthroughOwnedReadGuard<T> {
+    guard: OwnedRwLockReadGuard<T>,
+    lock: Arc<PassthroughRwLock<T>>,
+}
+
+impl<T> PassthroughOwnedReadGuard<T> {
+    /// 이 가드가 쥔 락
+    #[inline]
+    pub fn rwlock(&self) -> &Arc<PassthroughRwLock<T>> {
+        &self.lock
+    }
+
+    /// 가드를 놓는다. `drop(guard)` 과 같다.
+    #[inline]
+    pub fn release(self) {}
+
+    /// 기록하지 않으므로 아무것도 하지 않는다.
+    #[inline]
+    pub fn complete(&mut self) {}
+}
+
+impl<T> Deref for PassthroughOwnedReadGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> AsRef<T> for PassthroughOwnedReadGuard<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+/// `PassthroughRwLock::write_owned` 가 돌려주는 가드
+pub struct PassthroughOwnedWriteGuard<T> {
+    // 빌린 쓰기 가드와 같은 순서로 드롭된다.
+    guard: OwnedRwLockWriteGuard<T>,
+    written: Written<Arc<Shared>>,
+    lock: Arc<PassthroughRwLock<T>>,
+}
+
+impl<T> PassthroughOwnedWriteGuard<T> {
+    /// 이 가드가 쥔 락
+    #[inline]
+    pub fn rwlock(&self) -> &Arc<PassthroughRwLock<T>> {
+        &self.lock
+    }
+
+    /// 가드를 놓는다. `drop(guard)` 과 같다.
+    #[inline]
+    pub fn release(self) {}
+
+    /// 기록하지 않으므로 아무것도 하지 않는다.
+    #[inline]
+    pub fn complete(&mut self) {}
+
+    /// 쓰기 락을 놓지 않고 읽기 가드로 바꾼다.
+    pub fn downgrade(self) -> PassthroughOwnedReadGuard<T> {
+        let Self {
+            guard,
+            written,
+            lock,
+        } = self;
+        let guard = guard.downgrade();
+        drop(written);
+        PassthroughOwnedReadGuard { guard, lock }
+    }
+}
+
+impl<T> Deref for PassthroughOwnedWriteGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for PassthroughOwnedWriteGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<T> AsRef<T> for PassthroughOwnedWriteGuard<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T> AsMut<T> for PassthroughOwnedWriteGuard<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedMutex`, `TracedSemaphore` 와 `sync` 래퍼들이 돌려주는 가드.
+///
+/// `TracedGuard` 처럼 `G` 가 역참조되면 그대로 역참조되고, 얻은 위치만 기억한다.
+pub struct PassthroughGuard<G> {
+    guard: G,
+    location: &'static Location<'static>,
+}
+
+impl<G> PassthroughGuard<G> {
+    #[inline]
+    fn new(guard: G, location: &'static Location<'static>) -> Self {
+        PassthroughGuard { guard, location }
+    }
+
+    /// 가드를 얻은 `lock()`/`acquire()` 호출 위치
+    #[inline]
+    pub fn acquired_at(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl<G: Deref> Deref for PassthroughGuard<G> {
+    type Target = G::Target;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for PassthroughGuard<G> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedMutex` 가 가리키는 타입
+pub struct PassthroughMutex<T> {
+    inner: Mutex<T>,
+}
+
+impl<T> PassthroughMutex<T> {
+    #[inline]
+    pub fn new(value: T) -> Self {
+        PassthroughMutex {
+            inner: Mutex::new(value),
+        }
+    }
+
+    ignored_config!();
+
+    #[track_caller]
+    #[inline]
+    pub fn lock(&self) -> impl Future<Output = PassthroughGuard<MutexGuard<'_, T>>> + '_ {
+        let location = Location::caller();
+        let lock = self.inner.lock();
+        async move { PassthroughGuard::new(lock.await, location) }
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn try_lock(&self) -> Result<PassthroughGuard<MutexGuard<'_, T>>, TryLockError> {
+        let location = Location::caller();
+        self.inner
+            .try_lock()
+            .map(|guard| PassthroughGuard::new(guard, location))
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+/// `trace` 기능을 끄면 `TracedSemaphore` 가 가리키는 타입
+pub struct PassthroughSemaphore {
+    inner: Semaphore,
+}
+
+impl PassthroughSemaphore {
+    #[inline]
+    pub fn new(permits: usize) -> Self {
+        PassthroughSemaphore {
+            inner: Semaphore::new(permits),
+        }
+    }
+
+    ignored_config!();
+
+    #[inline]
+    pub fn available_permits(&self) -> usize {
+        self.inner.available_permits()
+    }
+
+    #[inline]
+    pub fn add_permits(&self, n: usize) {
+        self.inner.add_permits(n);
+    }
+
+    #[inline]
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn acquire(
+        &self,
+    ) -> impl Future<Output = Result<PassthroughGuard<SemaphorePermit<'_>>, AcquireError>> + '_
+    {
+        let location = Location::caller();
+        let acquire = self.inner.acquire();
+        async move { Ok(PassthroughGuard::new(acquire.await?, location)) }
+    }
+
+    #[track_caller]
+    #[inline]
+    pub fn try_acquire(&self) -> Result<PassthroughGuard<SemaphorePermit<'_>>, TryAcquireError> {
+        let location = Location::caller();
+        self.inner
+            .try_acquire()
+            .map(|permit| PassthroughGuard::new(permit, location))
+    }
+}
+
+#[cfg(feature = "std-sync")]
+mod std_sync {
+    use std::panic::Location;
+    use std::sync::{
+        LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        TryLockError, TryLockResult,
+    };
+
+    use super::PassthroughGuard;
+    use crate::config::LockConfig;
+    use crate::stats::LockStatsSnapshot;
+
+    // 오염된 락도 가드를 감싸서 돌려준다.
+    #[track_caller]
+    fn wrap<G>(result: LockResult<G>) -> LockResult<PassthroughGuard<G>> {
+        let location = Location::caller();
+        match result {
+            Ok(guard) => Ok(PassthroughGuard::new(guard, location)),
+            Err(poisoned) => Err(PoisonError::new(PassthroughGuard::new(
+                poisoned.into_inner(),
+                location,
+            ))),
+        }
+    }
+
+    #[track_caller]
+    fn try_wrap<G>(result: TryLockResult<G>) -> TryLockResult<PassthroughGuard<G>> {
+        let location = Location::caller();
+        match result {
+            Ok(guard) => Ok(PassthroughGuard::new(guard, location)),
+            Err(TryLockError::Poisoned(poisoned)) => Err(TryLockError::Poisoned(PoisonError::new(
+                PassthroughGuard::new(poisoned.into_inner(), location),
+            ))),
+            Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+        }
+    }
+
+    /// `trace` 기능을 끄면 `TracedStdMutex` 가 가리키는 타입
+    pub struct PassthroughStdMutex<T> {
+        inner: Mutex<T>,
+    }
+
+    impl<T> PassthroughStdMutex<T> {
+        #[inline]
+        pub fn new(value: T) -> Self {
+            PassthroughStdMutex {
+                inner: Mutex::new(value),
+            }
+        }
+
+        ignored_config!();
+
+        #[track_caller]
+        #[inline]
+        pub fn lock(&self) -> LockResult<PassthroughGuard<MutexGuard<'_, T>>> {
+            wrap(self.inner.lock())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_lock(&self) -> TryLockResult<PassthroughGuard<MutexGuard<'_, T>>> {
+            try_wrap(self.inner.try_lock())
+        }
+
+        #[inline]
+        pub fn get_mut(&mut self) -> LockResult<&mut T> {
+            self.inner.get_mut()
+        }
+
+        #[inline]
+        pub fn into_inner(self) -> LockResult<T> {
+            self.inner.into_inner()
+        }
+    }
+
+    /// `trace` 기능을 끄면 `TracedStdRwLock` 이 가리키는 타입
+    pub struct PassthroughStdRwLock<T> {
+        inner: RwLock<T>,
+    }
+
+    impl<T> PassthroughStdRwLock<T> {
+        #[inline]
+        pub fn new(value: T) -> Self {
+            PassthroughStdRwLock {
+                inner: RwLock::new(value),
+            }
+        }
+
+        ignored_config!();
+
+        #[track_caller]
+        #[inline]
+        pub fn read(&self) -> LockResult<PassthroughGuard<RwLockReadGuard<'_, T>>> {
+            wrap(self.inner.read())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn write(&self) -> LockResult<PassthroughGuard<RwLockWriteGuard<'_, T>>> {
+            wrap(self.inner.write())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_read(&self) -> TryLockResult<PassthroughGuard<RwLockReadGuard<'_, T>>> {
+            try_wrap(self.inner.try_read())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_write(&self) -> TryLockResult<PassthroughGuard<RwLockWriteGuard<'_, T>>> {
+            try_wrap(self.inner.try_write())
+        }
+
+        #[inline]
+        pub fn get_mut(&mut self) -> LockResult<&mut T> {
+            self.inner.get_mut()
+        }
+
+        #[inline]
+        pub fn into_inner(self) -> LockResult<T> {
+            self.inner.into_inner()
+        }
+    }
+
+    /// `trace` 기능을 끄면 `TracedStdMutexGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedStdMutexGuard<'a, T> = PassthroughGuard<MutexGuard<'a, T>>;
+    /// `trace` 기능을 끄면 `TracedStdReadGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedStdReadGuard<'a, T> = PassthroughGuard<RwLockReadGuard<'a, T>>;
+    /// `trace` 기능을 끄면 `TracedStdWriteGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedStdWriteGuard<'a, T> = PassthroughGuard<RwLockWriteGuard<'a, T>>;
+    #[cfg(not(feature = "trace"))]
+    pub type TracedStdMutex<T> = PassthroughStdMutex<T>;
+    #[cfg(not(feature = "trace"))]
+    pub type TracedStdRwLock<T> = PassthroughStdRwLock<T>;
+}
+
+#[cfg(feature = "std-sync")]
+pub use std_sync::*;
+
+#[cfg(feature = "parking-lot")]
+mod parking {
+    use std::panic::Location;
+
+    use parking_lot::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+    use super::PassthroughGuard;
+    use crate::config::LockConfig;
+    use crate::stats::LockStatsSnapshot;
+
+    /// `trace` 기능을 끄면 `TracedParkingLotMutex` 가 가리키는 타입
+    pub struct PassthroughParkingLotMutex<T> {
+        inner: Mutex<T>,
+    }
+
+    impl<T> PassthroughParkingLotMutex<T> {
+        #[inline]
+        pub fn new(value: T) -> Self {
+            PassthroughParkingLotMutex {
+                inner: Mutex::new(value),
+            }
+        }
+
+        ignored_config!();
+
+        #[track_caller]
+        #[inline]
+        pub fn lock(&self) -> PassthroughGuard<MutexGuard<'_, T>> {
+            PassthroughGuard::new(self.inner.lock(), Location::caller())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_lock(&self) -> Option<PassthroughGuard<MutexGuard<'_, T>>> {
+            let location = Location::caller();
+            self.inner
+                .try_lock()
+                .map(|guard| PassthroughGuard::new(guard, location))
+        }
+
+        #[inline]
+        pub fn get_mut(&mut self) -> &mut T {
+            self.inner.get_mut()
+        }
+
+        #[inline]
+        pub fn into_inner(self) -> T {
+            self.inner.into_inner()
+        }
+    }
+
+    /// `trace` 기능을 끄면 `TracedParkingLotRwLock` 이 가리키는 타입
+    pub struct PassthroughParkingLotRwLock<T> {
+        inner: RwLock<T>,
+    }
+
+    impl<T> PassthroughParkingLotRwLock<T> {
+        #[inline]
+        pub fn new(value: T) -> Self {
+            PassthroughParkingLotRwLock {
+                inner: RwLock::new(value),
+            }
+        }
+
+        ignored_config!();
+
+        #[track_caller]
+        #[inline]
+        pub fn read(&self) -> PassthroughGuard<RwLockReadGuard<'_, T>> {
+            PassthroughGuard::new(self.inner.read(), Location::caller())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn write(&self) -> PassthroughGuard<RwLockWriteGuard<'_, T>> {
+            PassthroughGuard::new(self.inner.write(), Location::caller())
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_read(&self) -> Option<PassthroughGuard<RwLockReadGuard<'_, T>>> {
+            let location = Location::caller();
+            self.inner
+                .try_read()
+                .map(|guard| PassthroughGuard::new(guard, location))
+        }
+
+        #[track_caller]
+        #[inline]
+        pub fn try_write(&self) -> Option<PassthroughGuard<RwLockWriteGuard<'_, T>>> {
+            let location = Location::caller();
+            self.inner
+                .try_write()
+                .map(|guard| PassthroughGuard::new(guard, location))
+        }
+
+        #[inline]
+        pub fn get_mut(&mut self) -> &mut T {
+            self.inner.get_mut()
+        }
+
+        #[inline]
+        pub fn into_inner(self) -> T {
+            self.inner.into_inner()
+        }
+    }
+
+    /// `trace` 기능을 끄면 `TracedParkingLotMutexGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedParkingLotMutexGuard<'a, T> = PassthroughGuard<MutexGuard<'a, T>>;
+    /// `trace` 기능을 끄면 `TracedParkingLotReadGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedParkingLotReadGuard<'a, T> = PassthroughGuard<RwLockReadGuard<'a, T>>;
+    /// `trace` 기능을 끄면 `TracedParkingLotWriteGuard` 가 가리키는 타입
+    #[cfg(not(feature = "trace"))]
+    pub type TracedParkingLotWriteGuard<'a, T> = PassthroughGuard<RwLockWriteGuard<'a, T>>;
+    #[cfg(not(feature = "trace"))]
+    pub type TracedParkingLotMutex<T> = PassthroughParkingLotMutex<T>;
+    #[cfg(not(feature = "trace"))]
+    pub type TracedParkingLotRwLock<T> = PassthroughParkingLotRwLock<T>;
+}
+
+#[cfg(feature = "parking-lot")]
+pub use parking::*;
+
+/// `trace` 기능을 끄면 `TracedMutex` 가 가리키는 이름
+#[cfg(not(feature = "trace"))]
+pub type TracedMutex<T> = PassthroughMutex<T>;
+/// `trace` 기능을 끄면 `TracedMutexGuard` 가 가리키는 타입
+#[cfg(not(feature = "trace"))]
+pub type TracedMutexGuard<'a, T> = PassthroughGuard<MutexGuard<'a, T>>;
+/// `trace` 기능을 끄면 `TracedSemaphore` 가 가리키는 이름
+#[cfg(not(feature = "trace"))]
+pub type TracedSemaphore = PassthroughSemaphore;
+/// `trace` 기능을 끄면 `TracedSemaphorePermit` 이 가리키는 타입
+#[cfg(not(feature = "trace"))]
+pub type TracedSemaphorePermit<'a> = PassthroughGuard<SemaphorePermit<'a>>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn passthrough_behaves_like_the_tokio_lock() {
+        let lock = PassthroughRwLock::new(1)
+            .with_config(LockConfig::new().name("ignored"))
+            .with_max_readers(1);
+        assert_eq!(lock.name(), None);
+        let reader = lock.read_ctx("ignored").await;
+        // 상한은 그대로 지켜지지만 까닭은 가리지 못한다.
+        assert!(lock.try_read().is_err());
+        assert_eq!(lock.try_read_capped().err(), Some(TryReadError::Locked));
+        assert!(lock.try_write().is_err());
+        drop(reader);
+        *lock.write().await += 1;
+        assert_eq!(lock.replace(5).await, 2);
+        assert_eq!(lock.stats(), LockStatsSnapshot::default());
+
+        let lock = Arc::new(lock);
+        let guard = lock.clone().write_owned().await;
+        assert_eq!(lock.try_replace(6), Err(6));
+        drop(guard);
+        assert_eq!(Arc::try_unwrap(lock).ok().unwrap().into_inner(), 5);
+    }
+
+    #[tokio::test]
+    async fn passthrough_downgrade_keeps_writers_out() {
+        let lock = Arc::new(PassthroughRwLock::new(1));
+        let mut guard = lock.write().await;
+        *guard += 1;
+        let reader = guard.downgrade();
+        assert_eq!(*reader, 2);
+        assert!(lock.try_read().is_ok());
+        assert!(lock.try_write().is_err());
+        drop(reader);
+
+        let reader = lock.clone().write_owned().await.downgrade();
+        assert!(Arc::ptr_eq(reader.rwlock(), &lock));
+        assert!(lock.try_write().is_err());
+    }
+
+    #[tokio::test]
+    async fn passthrough_waiters_wake_on_writes() {
+        let lock = Arc::new(PassthroughRwLock::new(Vec::<u32>::new()));
+        let producer = tokio::spawn({
+            let lock = lock.clone();
+            async move {
+                for item in 0..3 {
+                    lock.write().await.push(item);
+                    tokio::task::yield_now().await;
+                }
+            }
+        });
+        let items = lock.wait_while(|items| items.len() < 3).await;
+        assert_eq!(*items, [0, 1, 2]);
+        drop(items);
+        producer.await.unwrap();
+
+        let writer = lock.clone().write_owned().await;
+        let reader = tokio::spawn({
+            let lock = lock.clone();
+            async move { lock.read_after_next_write().await.len() }
+        });
+        tokio::task::yield_now().await;
+        let mut writer = writer;
+        writer.push(3);
+        writer.release();
+        assert_eq!(reader.await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn passthrough_read_fresh_refreshes_once() {
+        let lock = PassthroughRwLock::new(0);
+        let value = lock
+            .read_fresh(Duration::from_secs(60), || async { 1 })
+            .await;
+        assert_eq!(*value, 1);
+        drop(value);
+        // 다른 쓰기를 재지 않으므로 나이는 `read_fresh` 로 채운 때부터다.
+        *lock.write().await = 5;
+        let value = lock
+            .read_fresh(Duration::from_secs(60), || async { 2 })
+            .await;
+        assert_eq!(*value, 5);
+        drop(value);
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        let value = lock
+            .read_fresh(Duration::from_millis(1), || async { 3 })
+            .await;
+        assert_eq!(*value, 3);
+    }
+
+    #[tokio::test]
+    async fn passthrough_mutex_and_semaphore_keep_their_semantics() {
+        let mutex = PassthroughMutex::new(0).with_config(LockConfig::new().name("ignored"));
+        let guard = mutex.lock().await;
+        assert_eq!(guard.acquired_at().line(), line!() - 1);
+        assert!(mutex.try_lock().is_err());
+        drop(guard);
+        assert_eq!(mutex.stats().reads + mutex.stats().writes, 0);
+
+        let semaphore = PassthroughSemaphore::new(1);
+        let permit = semaphore.acquire().await.unwrap();
+        assert!(semaphore.try_acquire().is_err());
+        drop(permit);
+        assert_eq!(semaphore.available_permits(), 1);
+    }
+}
@@ -0,0 +1,262 @@
+//! `TracedMutex`, `TracedSemaphore`, `std`/`parking_lot` 래퍼가 함께 쓰는 계측 계층.
+//!
+//! 락 하나의 설정과 레지스트리 항목을 들고, 기다림과 획득/해제를 `TokioRwLockTrace` 와 같은 통계와
+//! 이벤트로 남긴다. 래퍼는 안쪽 primitive 를 잡은 가드를 `TracedGuard` 로 감싸기만 하면 된다.
+
+use std::{
+    num::NonZeroU64,
+    ops::{Deref, DerefMut},
+    panic::Location,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::registry::{self, Entry, HolderToken};
+use crate::sites::Site;
+use crate::slowest::SlowBy;
+use crate::stats::{LockStatsSnapshot, WaitGuard};
+use crate::timeline;
+
+/// 락 하나의 계측 상태
+pub(crate) struct Probe {
+    pub(crate) config: LockConfig,
+    pub(crate) entry: Arc<Entry>,
+}
+
+/// `Probe::wait` 가 돌려주는, 기다리기 시작한 획득 하나
+pub(crate) struct Waiting<'a> {
+    _wait: WaitGuard<'a>,
+    // `warn_if_wait_exceeds` 가 있을 때만 있다.
+    _waiter: Option<HolderToken>,
+    timeline: Option<timeline::Pending>,
+    location: &'static Location<'static>,
+    started: Option<Instant>,
+}
+
+impl Probe {
+    #[track_caller]
+    pub(crate) fn new() -> Self {
+        let config = LockConfig::inherited();
+        let entry = registry::register();
+        entry.configure(&config);
+        Probe { config, entry }
+    }
+
+    pub(crate) fn configure(&mut self, config: LockConfig) {
+        let config = config.overlay(&self.config);
+        self.entry.configure(&config);
+        self.config = config;
+    }
+
+    pub(crate) fn name(&self) -> Option<&str> {
+        self.config.name.as_deref()
+    }
+
+    pub(crate) fn stats(&self) -> LockStatsSnapshot {
+        self.entry.stats.snapshot()
+    }
+
+    /// 안쪽 primitive 를 기다리기 직전에 부른다. 대기자 수를 올리고 시작 시각을 잰다.
+    #[track_caller]
+    pub(crate) fn wait(&self, access: AccessKind) -> Waiting<'_> {
+        self.entry.announce(&self.config);
+        let location = Location::caller();
+        Waiting {
+            _wait: self.entry.stats.start_wait(),
+            _waiter: self
+                .entry
+                .track_waiter(access, location, &self.config, Instant::now()),
+            timeline: timeline::begin(&self.config, self.entry.id, access, location),
+            location,
+            started: self.config.timed().then(|| self.config.now()),
+        }
+    }
+
+    /// 곧바로 얻지 못해 정말로 막히기 직전에 부른다. 돌려준 값을 막히는 동안 쥐고 있는다.
+    ///
+    /// 기다리지 않는 시도는 교착을 만들 수 없으므로 `wait` 와 따로 둔다.
+    pub(crate) fn blocked(
+        &self,
+        waiting: &Waiting<'_>,
+        access: AccessKind,
+    ) -> Option<crate::deadlock::WaitToken> {
+        crate::deadlock::waiting(&self.config, self.entry.id, access, waiting.location)
+    }
+
+    /// 기다리지 않는 획득이 실패했음을 남긴다.
+    #[track_caller]
+    pub(crate) fn busy(&self, access: AccessKind) {
+        self.entry.announce(&self.config);
+        log_call_info!(&self.config, EventKind::Busy, access);
+    }
+
+    /// 기다리던 획득이 `guard` 를 얻었음을 기록하고 계측 가드로 감싼다.
+    ///
+    /// `contended` 는 곧바로 얻지 못하고 기다려야 했는지다.
+    pub(crate) fn acquired<G>(
+        &self,
+        waiting: Waiting<'_>,
+        guard: G,
+        access: AccessKind,
+        contended: bool,
+    ) -> TracedGuard<'_, G> {
+        let Waiting {
+            _wait: wait,
+            _waiter: waiter,
+            timeline,
+            location,
+            started,
+        } = waiting;
+        drop((wait, waiter));
+        let now = self.config.timed().then(|| self.config.now());
+        // 곧바로 얻었다면 기다리지 않은 것으로 본다.
+        let waited = started.zip(now).map(|(started, now)| {
+            if contended {
+                now.saturating_duration_since(started)
+            } else {
+                Duration::ZERO
+            }
+        });
+        match access {
+            AccessKind::Read => self.entry.stats.record_read(),
+            AccessKind::Write => self.entry.stats.record_write(),
+        }
+        self.entry.stats.record_contention(contended);
+        if let Some(waited) = waited {
+            self.entry
+                .slowest
+                .record(SlowBy::Wait, access, location, waited);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::acquired(&self.entry, access, waited);
+        let site = crate::sites::acquired(&self.config, &self.entry, access, location, waited);
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let span = crate::trace::guard_span(&self.config, access, location, waited);
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let _entered = span.enter();
+        log_call_info!(
+            &self.config,
+            EventKind::Acquire,
+            access,
+            location: location,
+            waited: waited,
+        );
+        TracedGuard {
+            guard,
+            probe: self,
+            access,
+            location,
+            start_time: now,
+            site,
+            deadlock: crate::deadlock::held(self.entry.id, access, location),
+            timeline: timeline.map(timeline::Pending::acquired),
+            _holder: self.entry.track_holder(access, location, &self.config),
+            #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+            span: span.clone(),
+        }
+    }
+}
+
+impl Drop for Probe {
+    fn drop(&mut self) {
+        self.entry.announce(&self.config);
+        let stats = self.entry.stats.snapshot();
+        log_call_info!(
+            &self.config,
+            EventKind::Destroyed,
+            AccessKind::Write,
+            location: self.entry.created_at,
+            message: Some(format!("{} acquisitions in total", stats.reads + stats.writes)),
+        );
+    }
+}
+
+/// `TracedMutex`, `TracedSemaphore` 와 `sync` 래퍼들이 돌려주는 가드. 드롭 시 점유 시간을 남긴다.
+///
+/// `G` 는 안쪽 primitive 의 가드(또는 허가)이며, `G` 가 역참조되면 이 가드도 그대로 역참조된다.
+pub struct TracedGuard<'a, G> {
+    guard: G,
+    probe: &'a Probe,
+    access: AccessKind,
+    location: &'static Location<'static>,
+    start_time: Option<Instant>,
+    site: Option<Arc<Site>>,
+    // `deadlock::set_enabled` 가 켜져 있을 때만 있다.
+    deadlock: Option<crate::deadlock::HoldToken>,
+    // `timeline::start` 로 기록 중에 얻었으면 그 획득 번호
+    timeline: Option<NonZeroU64>,
+    // 보유자 기록이 켜져 있을 때만 있다.
+    _holder: Option<HolderToken>,
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    span: tracing::Span,
+}
+
+impl<G> TracedGuard<'_, G> {
+    /// 가드를 얻은 `lock()`/`acquire()` 호출 위치
+    pub fn acquired_at(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl<G: Deref> Deref for TracedGuard<'_, G> {
+    type Target = G::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<G: DerefMut> DerefMut for TracedGuard<'_, G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<G> Drop for TracedGuard<'_, G> {
+    fn drop(&mut self) {
+        // 안쪽 가드가 풀리기 전에 대기 그래프에서 내린다.
+        self.deadlock = None;
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        let _entered = self.span.enter();
+        let probe = self.probe;
+        let held = self
+            .start_time
+            .map(|start| probe.config.now().saturating_duration_since(start));
+        probe
+            .entry
+            .stats
+            .record_release(self.access, held.unwrap_or_default());
+        #[cfg(feature = "metrics")]
+        crate::metrics::released(&probe.entry, self.access, held);
+        if let Some(site) = &self.site {
+            site.released(self.access, held);
+        }
+        if let Some(acquisition) = self.timeline {
+            timeline::released(
+                &probe.config,
+                probe.entry.id,
+                self.access,
+                self.location,
+                acquisition,
+            );
+        }
+        let Some(held) = held else {
+            return;
+        };
+        probe
+            .entry
+            .slowest
+            .record(SlowBy::Hold, self.access, self.location, held);
+        #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+        crate::trace::record_held(&self.span, held);
+        log_call_info!(
+            &probe.config,
+            EventKind::Release,
+            self.access,
+            held: Some(held),
+            acquired_at: Some(self.location),
+        );
+    }
+}
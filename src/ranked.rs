@@ -0,0 +1,126 @@
+//! 타입 수준 순위(rank)로 락 획득 순서를 컴파일 타임에 검사하는 계층.
+//!
+//! 각 `RankedLock<T, RANK>` 은 고정된 순위를 가지며, 획득하려면 현재 보유 중인 순위를 나타내는
+//! `LockToken` 을 넘겨야 한다. 보유 순위보다 엄격히 높은 순위의 락만 획득할 수 있고, 순서를 어기면
+//! 컴파일(빌드)에 실패한다. 모든 태스크가 같은 순서로 락을 잡으므로 교착 상태가 생기지 않는다.
+//!
+//! 순위 비교는 단형화(monomorphization) 시점의 상수 평가로 이뤄지므로 `cargo check` 가 아니라
+//! `cargo build` 에서 오류가 보고된다.
+//!
+//! 루트 토큰(`LockToken::new`)은 태스크 진입점에서 한 번만 만들어야 한다. 루트 토큰을 여러 개
+//! 만들면 순서 보장이 깨진다.
+//!
+//! ```
+//! use tracing_lock::ranked::{LockToken, RankedLock};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let accounts: RankedLock<u32, 1> = RankedLock::new(10);
+//! let journal: RankedLock<Vec<u32>, 2> = RankedLock::new(Vec::new());
+//!
+//! let mut token = LockToken::new();
+//! let (mut balance, mut held) = accounts.write(&mut token).await;
+//! let (mut entries, _) = journal.write(&mut held).await;
+//! *balance -= 1;
+//! entries.push(*balance);
+//! # }
+//! ```
+//!
+//! 순서를 거꾸로 잡으면 빌드되지 않는다.
+//!
+//! ```compile_fail
+//! use tracing_lock::ranked::{LockToken, RankedLock};
+//!
+//! async fn wrong(low: &RankedLock<u32, 1>, high: &RankedLock<u32, 2>) {
+//!     let mut token = LockToken::new();
+//!     let (_high, mut held) = high.write(&mut token).await;
+//!     let _low = low.write(&mut held).await;
+//! }
+//! # fn main() {
+//! #     let (low, high) = (RankedLock::new(1), RankedLock::new(2));
+//! #     let _future = wrong(&low, &high);
+//! # }
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::config::LockConfig;
+use crate::guard::{LoggingRwLockReadGuard, LoggingRwLockWriteGuard};
+use crate::lock::TokioRwLockTrace;
+use crate::stats::LockStatsSnapshot;
+
+/// 현재 보유 중인 최고 순위 `HELD` 를 나타내는 토큰.
+///
+/// 다음 토큰은 이전 토큰을 가변으로 빌리므로, 상위 락을 잡은 동안 하위 토큰을 재사용할 수 없다.
+pub struct LockToken<'a, const HELD: u32> {
+    _marker: PhantomData<&'a mut ()>,
+}
+
+impl LockToken<'static, 0> {
+    /// 아무 락도 보유하지 않은 루트 토큰
+    pub fn new() -> Self {
+        LockToken {
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for LockToken<'static, 0> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 순위 `RANK`(1 이상)를 가진 계측 락.
+pub struct RankedLock<T, const RANK: u32> {
+    inner: TokioRwLockTrace<T>,
+}
+
+impl<T, const RANK: u32> RankedLock<T, RANK> {
+    pub fn new(value: T) -> Self {
+        RankedLock {
+            inner: TokioRwLockTrace::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.inner = self.inner.with_config(config);
+        self
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.inner.stats()
+    }
+
+    pub async fn read<'a, 't, const HELD: u32>(
+        &'a self,
+        _token: &'t mut LockToken<'_, HELD>,
+    ) -> (LoggingRwLockReadGuard<'a, T>, LockToken<'t, RANK>)
+    where
+        T: Send + Sync,
+    {
+        const { assert!(RANK > HELD, "lock acquired out of rank order") };
+        let guard = self.inner.read().await;
+        (guard, child_token())
+    }
+
+    pub async fn write<'a, 't, const HELD: u32>(
+        &'a self,
+        _token: &'t mut LockToken<'_, HELD>,
+    ) -> (LoggingRwLockWriteGuard<'a, T>, LockToken<'t, RANK>)
+    where
+        T: Send + Sync,
+    {
+        const { assert!(RANK > HELD, "lock acquired out of rank order") };
+        let guard = self.inner.write().await;
+        (guard, child_token())
+    }
+}
+
+fn child_token<'t, const RANK: u32>() -> LockToken<'t, RANK> {
+    LockToken {
+        _marker: PhantomData,
+    }
+}
@@ -0,0 +1,261 @@
+//! 출력으로 내보내는 이벤트의 초당 한도. (`LockConfig::rate_limit`)
+//!
+//! 한도를 넘어 버린 이벤트는 "suppressed N events" 경고 하나로 요약한다. 요약은 한도가 다시 차서
+//! 다음 이벤트가 나갈 때 그 앞에, 그런 이벤트가 없으면 `WINDOW` 뒤에 요약 스레드가 내보낸다.
+//! 스레드는 처음 이벤트를 버릴 때 하나만 띄운다.
+
+use std::{
+    fmt,
+    panic::Location,
+    sync::{Arc, Condvar, Mutex, Once, PoisonError, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::config::LockConfig;
+use crate::event::{self, AccessKind, EventKind, LockEvent};
+
+/// 설정하지 않은 락의 출력 한도
+pub(crate) const DEFAULT_EVENTS_PER_SEC: u32 = 10_000;
+
+// 버린 이벤트의 요약을 늦어도 이만큼 뒤에는 내보낸다.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// `LockConfig::rate_limit` 의 상태. `None` 이면 제한하지 않는다.
+#[derive(Clone, Debug)]
+pub(crate) struct RateLimit(pub(crate) Option<Arc<RateLimiter>>);
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self(Some(Arc::new(RateLimiter::new(DEFAULT_EVENTS_PER_SEC))))
+    }
+}
+
+/// 초당 `rate` 개까지 채워지는 토큰 버킷. 토큰이 없을 때 버린 이벤트 수를 세어 둔다.
+pub(crate) struct RateLimiter {
+    rate: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+    pending: Option<Suppressed>,
+}
+
+// 아직 요약하지 않은 버린 이벤트들
+struct Suppressed {
+    count: u64,
+    since: Instant,
+    // 요약을 내보낼 설정. 한도를 뺀 복사본이라 제한기를 다시 가리키지 않는다.
+    config: LockConfig,
+    access: AccessKind,
+    location: &'static Location<'static>,
+}
+
+static PENDING: Mutex<Vec<Weak<RateLimiter>>> = Mutex::new(Vec::new());
+static WAKE: Condvar = Condvar::new();
+static STARTED: Once = Once::new();
+
+impl RateLimiter {
+    pub(crate) fn new(rate: u32) -> Self {
+        Self {
+            rate: f64::from(rate),
+            bucket: Mutex::new(Bucket {
+                tokens: f64::from(rate),
+                refilled: Instant::now(),
+                pending: None,
+            }),
+        }
+    }
+
+    /// `event` 를 출력해도 되는지 정한다. 내보낼 수 있으면 그동안 버린 이벤트의 요약을 먼저
+    /// 내보낸다.
+    pub(crate) fn admit(self: &Arc<Self>, config: &LockConfig, event: &LockEvent) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.rate);
+        bucket.refilled = now;
+        if bucket.tokens < 1.0 {
+            match &mut bucket.pending {
+                Some(pending) => pending.count += 1,
+                None => {
+                    bucket.pending = Some(Suppressed {
+                        count: 1,
+                        since: now,
+                        config: LockConfig {
+                            rate_limit: RateLimit(None),
+                            ..config.clone()
+                        },
+                        access: event.access,
+                        location: event.location,
+                    });
+                    drop(bucket);
+                    self.schedule();
+                }
+            }
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        let pending = bucket.pending.take();
+        drop(bucket);
+        if let Some(pending) = pending {
+            pending.flush();
+        }
+        true
+    }
+
+    // 요약 스레드가 이 제한기를 살펴보게 한다.
+    fn schedule(self: &Arc<Self>) {
+        {
+            let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+            if !pending
+                .iter()
+                .any(|weak| std::ptr::eq(weak.as_ptr(), Arc::as_ptr(self)))
+            {
+                pending.push(Arc::downgrade(self));
+            }
+        }
+        STARTED.call_once(|| {
+            std::thread::Builder::new()
+                .name("tracing-lock-rate-limit".into())
+                .spawn(run)
+                .expect("failed to spawn the rate limit thread");
+        });
+        WAKE.notify_one();
+    }
+
+    // `WINDOW` 가 지난 요약을 내보낸다. 아직 이르면 내보낼 시각을 돌려준다.
+    fn flush_due(&self, now: Instant) -> Option<Instant> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let due = bucket.pending.as_ref()?.since + WINDOW;
+        if now < due {
+            return Some(due);
+        }
+        let pending = bucket.pending.take();
+        drop(bucket);
+        pending?.flush();
+        None
+    }
+}
+
+impl Suppressed {
+    // 요약 경고를 다른 이벤트와 같은 길로 출력과 관찰자에게 보낸다.
+    fn flush(self) {
+        let config = &self.config;
+        if let Some(summary) = lock_event!(
+            config,
+            EventKind::Warning,
+            self.access,
+            location: self.location,
+            message: Some(format!(
+                "suppressed {} events over the rate limit",
+                self.count
+            )),
+        ) {
+            event::emit(config, summary);
+        }
+    }
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("rate", &self.rate)
+            .finish_non_exhaustive()
+    }
+}
+
+fn run() {
+    let mut pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+    loop {
+        let limiters: Vec<_> = pending.iter().filter_map(Weak::upgrade).collect();
+        // 요약을 내보내는 동안 다른 제한기가 목록에 오를 수 있도록 목록을 놓는다.
+        drop(pending);
+        let now = Instant::now();
+        let wake_at = limiters
+            .iter()
+            .filter_map(|limiter| limiter.flush_due(now))
+            .min();
+        pending = PENDING.lock().unwrap_or_else(PoisonError::into_inner);
+        pending.retain(|weak| {
+            weak.upgrade().is_some_and(|limiter| {
+                limiter
+                    .bucket
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .pending
+                    .is_some()
+            })
+        });
+        drop(limiters);
+        pending = match wake_at {
+            Some(at) => {
+                let timeout = at.saturating_duration_since(Instant::now());
+                WAKE.wait_timeout(pending, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => WAKE
+                .wait_while(pending, |pending| pending.is_empty())
+                .unwrap_or_else(PoisonError::into_inner),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+
+    fn summaries(events: &CapturingObserver) -> Vec<String> {
+        events
+            .events()
+            .iter()
+            .filter(|e| e.kind == EventKind::Warning)
+            .filter_map(|e| e.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn summary_comes_before_the_next_admitted_event() {
+        let events = CapturingObserver::new();
+        let config = LockConfig::new().observer(events.clone());
+        let limiter = Arc::new(RateLimiter::new(2));
+        let event = || lock_event!(&config, EventKind::Acquire, AccessKind::Read).unwrap();
+        assert!(limiter.admit(&config, &event()));
+        assert!(limiter.admit(&config, &event()));
+        assert!(!limiter.admit(&config, &event()));
+        assert!(!limiter.admit(&config, &event()));
+        std::thread::sleep(Duration::from_millis(600));
+        // 다시 찬 뒤 첫 이벤트가 나가기 전에 요약이 관찰자에게도 간다.
+        assert!(limiter.admit(&config, &event()));
+        assert_eq!(
+            summaries(&events),
+            ["suppressed 2 events over the rate limit"]
+        );
+    }
+
+    #[test]
+    fn summary_is_flushed_when_the_storm_stops() {
+        let events = CapturingObserver::new();
+        let config = LockConfig::new().observer(events.clone());
+        let limiter = Arc::new(RateLimiter::new(1));
+        let event = || lock_event!(&config, EventKind::Acquire, AccessKind::Read).unwrap();
+        assert!(limiter.admit(&config, &event()));
+        for _ in 0..3 {
+            assert!(!limiter.admit(&config, &event()));
+        }
+        // 다음 이벤트가 없어도 요약 스레드가 내보낸다.
+        for _ in 0..300 {
+            if !summaries(&events).is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            summaries(&events),
+            ["suppressed 3 events over the rate limit"]
+        );
+    }
+}
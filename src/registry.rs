@@ -0,0 +1,1145 @@
+//! 프로세스 전역 락 레지스트리.
+//!
+//! 생성된 모든 계측 락은 여기에 약한 참조로 등록되며, 드롭된 락은 조회 시 정리된다.
+
+use std::{
+    cmp::Reverse,
+    fmt::Write as _,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, Once, OnceLock, PoisonError, TryLockError, Weak,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::sync::Notify;
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::slowest::SlowLog;
+use crate::stats::{LockStats, LockStatsSnapshot};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static LOCKS: Mutex<Vec<Weak<Entry>>> = Mutex::new(Vec::new());
+// `install_panic_hook` 이후에만 보유자를 기록한다.
+pub(crate) static TRACK_HOLDERS: AtomicBool = AtomicBool::new(false);
+static NEXT_HOLDER: AtomicU64 = AtomicU64::new(1);
+// `set_unnamed_locks(UnnamedLocks::Id)`
+static UNNAMED_BY_ID: AtomicBool = AtomicBool::new(false);
+#[cfg(debug_assertions)]
+static NEXT_GUARD: AtomicU64 = AtomicU64::new(1);
+// 살아 있는 가드를 남긴 채 드롭된 락의 가드들. `MAX_LEAKED` 개까지만 기억한다.
+#[cfg(debug_assertions)]
+static LEAKED: Mutex<Vec<GuardLeak>> = Mutex::new(Vec::new());
+#[cfg(debug_assertions)]
+const MAX_LEAKED: usize = 1024;
+// 락마다 위치까지 기억하는 살아 있는 가드 수. 넘치는 가드는 수만 센다.
+#[cfg(debug_assertions)]
+const GUARD_SLOTS: usize = 16;
+
+/// 레지스트리에 등록된 락 하나의 공유 상태
+#[derive(Debug)]
+pub(crate) struct Entry {
+    pub(crate) id: u64,
+    name: Mutex<Option<String>>,
+    group: Mutex<Option<String>>,
+    pub(crate) stats: Arc<LockStats>,
+    /// 가장 오래 기다린/쥔 획득들
+    pub(crate) slowest: SlowLog,
+    holders: Mutex<Vec<Holder>>,
+    /// `warn_if_wait_exceeds` 가 있으면 획득을 기다리는 쪽. 항목은 `Holder` 와 같다.
+    waiters: Mutex<Vec<Holder>>,
+    /// 쓰기 가드가 해제될 때마다 깨운다.
+    pub(crate) written: Notify,
+    /// `set_instrumented(false)` 로 계측을 끔
+    pub(crate) uninstrumented: AtomicBool,
+    /// 만들어졌지만 아직 드롭되지 않은 가드 (디버그 빌드 전용)
+    #[cfg(debug_assertions)]
+    live_guards: LiveGuards,
+    /// 락을 만든 위치와 시각
+    pub(crate) created_at: &'static Location<'static>,
+    created: SystemTime,
+    announced: Once,
+    /// `from` 으로 감쌀 때 이미 쥐어져 있던 락이면 그 접근 종류
+    pub(crate) attached_in_use: OnceLock<AccessKind>,
+    /// `tokio-console` 이 보는 리소스 span
+    #[cfg(feature = "tokio-console")]
+    pub(crate) resource: tracing::Span,
+    /// `metrics` 로 보낼 때 쓰는 키
+    #[cfg(feature = "metrics")]
+    pub(crate) metric_keys: Mutex<Arc<crate::metrics::Keys>>,
+    /// `track_sites` 로 이 락이 쓴 호출 위치 기록
+    pub(crate) sites: Mutex<Vec<Arc<crate::sites::Site>>>,
+    /// `warn_if_wait_exceeds`/`warn_if_held_exceeds` 가 있으면 감시 스레드가 쓰는 설정
+    pub(crate) watch: Mutex<Option<Arc<crate::watchdog::Watch>>>,
+    /// 살아 있는 `TokioRwLockTrace` 핸들 수. 스냅샷이나 감시 스레드가 잠깐 `upgrade` 한 `Arc` 는
+    /// 세지 않으므로 `Arc::strong_count` 대신 이 값으로 마지막 핸들을 가린다.
+    handles: AtomicUsize,
+}
+
+/// 지금 가드를 쥐고 있는 쪽
+#[derive(Debug)]
+struct Holder {
+    token: u64,
+    access: AccessKind,
+    thread: String,
+    task: Option<tokio::task::Id>,
+    location: &'static Location<'static>,
+    since: Instant,
+    // 감시 스레드가 이미 알림
+    alerted: bool,
+}
+
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone)]
+struct LiveGuard {
+    seq: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+}
+
+/// 락 하나의 살아 있는 가드 기록 (디버그 빌드 전용)
+///
+/// 가드는 빈 칸 하나를 원자적으로 차지하고 그 칸에만 쓰므로, 같은 락의 가드끼리 서로 기다리지
+/// 않는다.
+#[cfg(debug_assertions)]
+#[derive(Debug)]
+struct LiveGuards {
+    count: AtomicUsize,
+    slots: [GuardSlot; GUARD_SLOTS],
+}
+
+#[cfg(debug_assertions)]
+#[derive(Debug, Default)]
+struct GuardSlot {
+    // 칸을 차지한 가드의 일련번호. 0 이면 빈 칸이다.
+    seq: AtomicU64,
+    guard: Mutex<Option<LiveGuard>>,
+}
+
+/// `Entry::guard_created` 가 돌려주는 가드의 자리 (디버그 빌드 전용)
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LiveSlot {
+    seq: u64,
+    // 칸이 모자라 수만 셌으면 `None`
+    slot: Option<usize>,
+}
+
+#[cfg(debug_assertions)]
+impl LiveGuards {
+    fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            slots: std::array::from_fn(|_| GuardSlot::default()),
+        }
+    }
+
+    // 칸에 기록된 가드들. 만들어진 순서대로 돌려준다.
+    fn recorded(&self) -> Vec<LiveGuard> {
+        let mut guards: Vec<_> = self
+            .slots
+            .iter()
+            .filter_map(|slot| {
+                slot.guard
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .clone()
+            })
+            .collect();
+        guards.sort_by_key(|guard| guard.seq);
+        guards
+    }
+}
+
+/// 가드(또는 기다리는 획득)와 함께 드롭되면서 보유자(대기자) 목록에서 빠진다.
+pub(crate) struct HolderToken {
+    entry: Arc<Entry>,
+    // 대기자 목록에 올랐음
+    waiting: bool,
+    token: u64,
+}
+
+impl HolderToken {
+    fn list(&self) -> &Mutex<Vec<Holder>> {
+        match self.waiting {
+            true => &self.entry.waiters,
+            false => &self.entry.holders,
+        }
+    }
+
+    /// 가드가 다른 접근 종류로 바뀌었음을 보유자 목록에 반영한다.
+    pub(crate) fn set_access(&self, access: AccessKind) {
+        let mut holders = self.list().lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(holder) = holders.iter_mut().find(|h| h.token == self.token) {
+            holder.access = access;
+        }
+    }
+}
+
+impl Drop for HolderToken {
+    fn drop(&mut self) {
+        let mut holders = self.list().lock().unwrap_or_else(PoisonError::into_inner);
+        holders.retain(|holder| holder.token != self.token);
+    }
+}
+
+/// 감시 스레드가 찾은, 문턱을 넘긴 대기나 점유 하나
+pub(crate) struct Overdue {
+    pub(crate) access: AccessKind,
+    pub(crate) thread: String,
+    pub(crate) task: Option<tokio::task::Id>,
+    pub(crate) location: &'static Location<'static>,
+    pub(crate) elapsed: Duration,
+}
+
+impl Entry {
+    /// `upgrade` 로 핸들을 하나 더 만든다. 마지막 핸들이 이미 드롭됐으면 `false`.
+    pub(crate) fn add_handle(&self) -> bool {
+        self.handles
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n > 0).then_some(n + 1)
+            })
+            .is_ok()
+    }
+
+    /// 핸들 하나를 드롭한다. 마지막 핸들이었으면 `true`.
+    pub(crate) fn drop_handle(&self) -> bool {
+        self.handles.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// 보유자 기록이 켜져 있으면 가드 하나를 보유자 목록에 올린다.
+    ///
+    /// `config` 가 보유자를 써야 하면(`escalate_writer_starvation`, `warn_if_held_exceeds`)
+    /// `install_panic_hook` 을 부르지 않았어도 올린다.
+    pub(crate) fn track_holder(
+        self: &Arc<Self>,
+        access: AccessKind,
+        location: &'static Location<'static>,
+        config: &LockConfig,
+    ) -> Option<HolderToken> {
+        let always = config.starvation_escalation.is_some() || config.held_watchdog.is_some();
+        if !always && !TRACK_HOLDERS.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(self.track(false, access, location, Instant::now()))
+    }
+
+    /// `warn_if_wait_exceeds` 가 있으면 `since` 부터 기다린 획득 하나를 대기자 목록에 올린다.
+    pub(crate) fn track_waiter(
+        self: &Arc<Self>,
+        access: AccessKind,
+        location: &'static Location<'static>,
+        config: &LockConfig,
+        since: Instant,
+    ) -> Option<HolderToken> {
+        config.wait_watchdog?;
+        Some(self.track(true, access, location, since))
+    }
+
+    fn track(
+        self: &Arc<Self>,
+        waiting: bool,
+        access: AccessKind,
+        location: &'static Location<'static>,
+        since: Instant,
+    ) -> HolderToken {
+        let token = NEXT_HOLDER.fetch_add(1, Ordering::Relaxed);
+        let holder = Holder {
+            token,
+            access,
+            thread: crate::task::thread_name(),
+            task: tokio::task::try_id(),
+            location,
+            since,
+            alerted: false,
+        };
+        let token = HolderToken {
+            entry: self.clone(),
+            waiting,
+            token,
+        };
+        token
+            .list()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(holder);
+        token
+    }
+
+    /// 대기자(`waiting`) 또는 보유자 중 `now` 에 `threshold` 를 넘겼고 아직 알리지 않은 것을 알린
+    /// 것으로 표시하고 돌려준다. 함께 돌려주는 시각은 남은 것 중 가장 먼저 문턱을 넘을 때다.
+    pub(crate) fn take_overdue(
+        &self,
+        waiting: bool,
+        threshold: Duration,
+        now: Instant,
+    ) -> (Vec<Overdue>, Option<Instant>) {
+        let list = if waiting {
+            &self.waiters
+        } else {
+            &self.holders
+        };
+        let mut list = list.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut overdue = Vec::new();
+        let mut next = None::<Instant>;
+        for holder in list.iter_mut().filter(|holder| !holder.alerted) {
+            let elapsed = now.saturating_duration_since(holder.since);
+            if elapsed < threshold {
+                let due = holder.since + threshold;
+                next = Some(next.map_or(due, |next| next.min(due)));
+                continue;
+            }
+            holder.alerted = true;
+            overdue.push(Overdue {
+                access: holder.access,
+                thread: holder.thread.clone(),
+                task: holder.task,
+                location: holder.location,
+                elapsed,
+            });
+        }
+        (overdue, next)
+    }
+
+    /// 보유자 목록에서 `access` 로 쥐고 있는 가드들의 획득 위치 (오래 쥔 순서)
+    pub(crate) fn holder_sites(&self, access: AccessKind) -> Vec<&'static Location<'static>> {
+        let holders = self.holders.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut held: Vec<_> = holders
+            .iter()
+            .filter(|holder| holder.access == access)
+            .collect();
+        held.sort_by_key(|holder| holder.since);
+        held.iter().map(|holder| holder.location).collect()
+    }
+
+    /// 가드 하나가 만들어졌음을 기록하고 그 자리를 돌려준다. 드롭될 때 `guard_dropped` 로 지운다.
+    #[cfg(debug_assertions)]
+    pub(crate) fn guard_created(
+        &self,
+        access: AccessKind,
+        location: &'static Location<'static>,
+    ) -> LiveSlot {
+        let seq = NEXT_GUARD.fetch_add(1, Ordering::Relaxed);
+        let live = &self.live_guards;
+        live.count.fetch_add(1, Ordering::Relaxed);
+        let start = seq as usize % GUARD_SLOTS;
+        let slot = (0..GUARD_SLOTS)
+            .map(|i| (start + i) % GUARD_SLOTS)
+            .find(|&i| {
+                live.slots[i]
+                    .seq
+                    .compare_exchange(0, seq, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            });
+        if let Some(i) = slot {
+            *live.slots[i]
+                .guard
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner) = Some(LiveGuard {
+                seq,
+                access,
+                location,
+            });
+        }
+        LiveSlot { seq, slot }
+    }
+
+    #[cfg(debug_assertions)]
+    pub(crate) fn guard_dropped(&self, live: LiveSlot) {
+        self.live_guards.count.fetch_sub(1, Ordering::Relaxed);
+        let Some(i) = live.slot else {
+            return;
+        };
+        let slot = &self.live_guards.slots[i];
+        let mut guard = slot.guard.lock().unwrap_or_else(PoisonError::into_inner);
+        // `retire` 가 이미 비운 칸은 다른 가드가 차지했을 수 있다.
+        if guard.as_ref().is_some_and(|guard| guard.seq == live.seq) {
+            *guard = None;
+            drop(guard);
+            slot.seq.store(0, Ordering::Release);
+        }
+    }
+
+    /// 아직 드롭되지 않은 가드 수와 그중 가장 먼저 얻은 위치. 위치를 기록하지 못한 가드뿐이면
+    /// 락을 만든 위치를 쓴다.
+    #[cfg(debug_assertions)]
+    pub(crate) fn live_guards(&self) -> Option<(usize, &'static Location<'static>)> {
+        let count = self.live_guards.count.load(Ordering::Relaxed);
+        if count == 0 {
+            return None;
+        }
+        let first = self
+            .live_guards
+            .recorded()
+            .first()
+            .map_or(self.created_at, |guard| guard.location);
+        Some((count, first))
+    }
+
+    #[cfg(debug_assertions)]
+    fn leaks(&self, lock_dropped: bool) -> Vec<GuardLeak> {
+        let name = self
+            .name
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        self.live_guards
+            .recorded()
+            .into_iter()
+            .map(|guard| GuardLeak {
+                seq: guard.seq,
+                lock_id: self.id,
+                name: name.clone(),
+                access: guard.access,
+                location: guard.location,
+                lock_dropped,
+            })
+            .collect()
+    }
+
+    /// 락이 사라질 때 남은 가드를 새어 나간 것으로 옮긴다. 잊힌 가드도 항목을 쥐고 있으므로 항목이
+    /// 드롭되기를 기다리지 않고 마지막 핸들이 드롭될 때 부른다.
+    #[cfg(debug_assertions)]
+    pub(crate) fn retire(&self) {
+        let leaks = self.leaks(true);
+        for slot in &self.live_guards.slots {
+            *slot.guard.lock().unwrap_or_else(PoisonError::into_inner) = None;
+            slot.seq.store(0, Ordering::Release);
+        }
+        self.live_guards.count.store(0, Ordering::Relaxed);
+        if !leaks.is_empty() {
+            let mut leaked = LEAKED.lock().unwrap_or_else(PoisonError::into_inner);
+            let room = MAX_LEAKED.saturating_sub(leaked.len());
+            leaked.extend(leaks.into_iter().take(room));
+        }
+    }
+
+    /// 처음 불릴 때만 생성 이벤트를 내보낸다.
+    ///
+    /// 생성자 바로 뒤에 `with_config` 가 붙으므로, 설정이 정해진 첫 획득(또는 드롭) 때 부른다.
+    pub(crate) fn announce(&self, config: &LockConfig) {
+        // 계측이 꺼진 동안에는 생성 이벤트를 다시 켤 때까지 미룬다.
+        if !crate::config::enabled() {
+            return;
+        }
+        self.announced.call_once(|| {
+            log_call_info!(
+                config,
+                EventKind::Created,
+                AccessKind::Write,
+                location: self.created_at,
+                timestamp: self.created,
+                message: self.attached_in_use.get().map(|access| {
+                    let access = match access {
+                        AccessKind::Read => "read",
+                        AccessKind::Write => "write",
+                    };
+                    format!(
+                        "attached to a lock that was already {}-held; stats only cover activity from here",
+                        access
+                    )
+                }),
+            );
+        });
+    }
+
+    /// `with_config` 로 정해진 설정 중 락 단위 상태에 둘 것을 반영한다.
+    pub(crate) fn configure(self: &Arc<Self>, config: &LockConfig) {
+        self.set_name(config.name.clone());
+        *self.group.lock().unwrap_or_else(PoisonError::into_inner) = config.group.clone();
+        self.stats.set_sample_every(config.stats_sample);
+        crate::watchdog::configure(self, config);
+    }
+
+    pub(crate) fn set_name(&self, name: Option<String>) {
+        #[cfg(feature = "tokio-console")]
+        crate::console::set_name(self, name.as_deref());
+        #[cfg(feature = "metrics")]
+        crate::metrics::set_name(self, name.as_deref());
+        *self.name.lock().unwrap_or_else(PoisonError::into_inner) = name;
+    }
+
+    fn info(&self) -> LockInfo {
+        LockInfo {
+            id: self.id,
+            name: self
+                .name
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            group: self
+                .group
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+            created_at: self.created_at,
+            stats: self.stats.snapshot(),
+        }
+    }
+}
+
+// 락이 사라질 때까지 드롭되지 않은 가드는 `mem::forget` 등으로 새어 나간 것이다.
+#[cfg(debug_assertions)]
+impl Drop for Entry {
+    fn drop(&mut self) {
+        self.retire();
+    }
+}
+
+#[track_caller]
+pub(crate) fn register() -> Arc<Entry> {
+    let entry = Arc::new(Entry {
+        id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        name: Mutex::new(None),
+        group: Mutex::new(None),
+        stats: Arc::default(),
+        slowest: SlowLog::default(),
+        holders: Mutex::new(Vec::new()),
+        waiters: Mutex::new(Vec::new()),
+        written: Notify::new(),
+        uninstrumented: AtomicBool::new(false),
+        #[cfg(debug_assertions)]
+        live_guards: LiveGuards::new(),
+        created_at: Location::caller(),
+        created: SystemTime::now(),
+        announced: Once::new(),
+        attached_in_use: OnceLock::new(),
+        #[cfg(feature = "tokio-console")]
+        resource: crate::console::resource_span(Location::caller()),
+        #[cfg(feature = "metrics")]
+        metric_keys: Mutex::new(crate::metrics::Keys::new(None)),
+        sites: Mutex::new(Vec::new()),
+        watch: Mutex::new(None),
+        handles: AtomicUsize::new(1),
+    });
+    let mut locks = LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+    locks.retain(|weak| weak.strong_count() > 0);
+    locks.push(Arc::downgrade(&entry));
+    entry
+}
+
+// 살아 있는 모든 락의 정보
+fn live() -> Vec<LockInfo> {
+    let mut locks = LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+    locks.retain(|weak| weak.strong_count() > 0);
+    locks
+        .iter()
+        .filter_map(Weak::upgrade)
+        .map(|entry| entry.info())
+        .collect()
+}
+
+// 패닉 훅 안에서도 막히지 않도록 기다리지 않고 잠근다.
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<std::sync::MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// 지금 가드가 잡혀 있는 락들과 그 보유자(접근 종류, 스레드/태스크, 획득 위치, 점유 시간)를
+/// 사람이 읽는 형식으로 만든다.
+///
+/// 레지스트리 내부 잠금을 기다리지 않으므로 패닉 훅에서 불러도 안전하다. 다른 스레드가
+/// 레지스트리를 쓰는 중이면 그 부분은 건너뛴다. 보유자는 `install_panic_hook` 이후에 잡힌
+/// 가드만 기록된다.
+pub fn dump_state() -> String {
+    let mut out = String::new();
+    let Some(locks) = try_lock(&LOCKS) else {
+        out.push_str("tracing-lock: registry busy, lock state unavailable\n");
+        return out;
+    };
+    let entries: Vec<_> = locks.iter().filter_map(Weak::upgrade).collect();
+    drop(locks);
+
+    let _ = writeln!(out, "tracing-lock: held locks");
+    let mut any = false;
+    for entry in &entries {
+        let Some(holders) = try_lock(&entry.holders) else {
+            let _ = writeln!(out, "  lock #{}: busy", entry.id);
+            continue;
+        };
+        if holders.is_empty() {
+            continue;
+        }
+        any = true;
+        let name = try_lock(&entry.name).and_then(|name| name.clone());
+        let waiters = entry.stats.snapshot().waiters;
+        match (name, unnamed_site(entry.created_at)) {
+            (Some(name), _) => {
+                let _ = write!(out, "  lock #{} '{}'", entry.id, name);
+            }
+            (None, Some(site)) => {
+                let _ = write!(out, "  lock #{} {}", entry.id, site);
+            }
+            (None, None) => {
+                let _ = write!(out, "  lock #{}", entry.id);
+            }
+        }
+        let _ = writeln!(out, " ({} waiting)", waiters);
+        for holder in holders.iter() {
+            let access = match holder.access {
+                AccessKind::Read => "read",
+                AccessKind::Write => "write",
+            };
+            let _ = write!(
+                out,
+                "    {} held for {:?} by thread {}",
+                access,
+                holder.since.elapsed(),
+                holder.thread
+            );
+            if let Some(task) = holder.task {
+                let _ = write!(out, " (task {})", task);
+            }
+            let _ = writeln!(
+                out,
+                ", acquired at {}:{}",
+                holder.location.file(),
+                holder.location.line()
+            );
+        }
+    }
+    if !any {
+        let _ = writeln!(out, "  (none)");
+    }
+    out
+}
+
+/// 패닉이 나면 기존 훅을 부른 뒤 `dump_state` 를 stderr 에 출력하는 훅을 설치한다.
+///
+/// 이 함수를 부른 뒤에 잡힌 가드부터 보유자가 기록된다.
+///
+/// ```
+/// tracing_lock::registry::install_panic_hook();
+/// ```
+pub fn install_panic_hook() {
+    install_panic_hook_with(|dump| eprint!("{}", dump));
+}
+
+/// `install_panic_hook` 과 같지만 덤프를 stderr 대신 `sink` 로 넘긴다. 로거로 보내거나 파일에 남길 때
+/// 쓴다.
+///
+/// ```
+/// tracing_lock::registry::install_panic_hook_with(|dump| tracing::error!("{dump}"));
+/// ```
+pub fn install_panic_hook_with(sink: impl Fn(&str) + Send + Sync + 'static) {
+    TRACK_HOLDERS.store(true, Ordering::Relaxed);
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        previous(info);
+        sink(&dump_state());
+    }));
+}
+
+/// 이름 없는 락을 `dump_state` 와 `report` 에 어떻게 보일지. `set_unnamed_locks` 로 고른다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnnamedLocks {
+    /// id 뒤에 락을 만든 위치를 붙인다. (`lock #3 created at src/cache.rs:42`)
+    #[default]
+    CreationSite,
+    /// id 만 보인다.
+    Id,
+}
+
+/// 이름 없는 락을 출력에서 가리키는 방식을 프로세스 전역으로 정한다.
+///
+/// 이름을 붙이지 않은 락이 많으면 id 만으로는 어느 락인지 알 수 없으므로, 기본으로는 생성자를
+/// 부른 위치를 함께 보인다. 위치가 로그에 드러나는 것이 싫으면 `UnnamedLocks::Id` 로 끈다.
+pub fn set_unnamed_locks(mode: UnnamedLocks) {
+    UNNAMED_BY_ID.store(mode == UnnamedLocks::Id, Ordering::Relaxed);
+}
+
+/// 이름 없는 락의 id 뒤에 붙일 생성 위치. 꺼져 있으면 `None`.
+pub(crate) fn unnamed_site(created_at: &Location<'_>) -> Option<String> {
+    (!UNNAMED_BY_ID.load(Ordering::Relaxed))
+        .then(|| format!("created at {}:{}", created_at.file(), created_at.line()))
+}
+
+/// 레지스트리 조회 결과
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockInfo {
+    /// 프로세스 안에서 고유한 락 id
+    pub id: u64,
+    pub name: Option<String>,
+    /// `with_group` 으로 붙인 묶음 이름
+    pub group: Option<String>,
+    /// 락을 만든 생성자 호출 위치
+    pub created_at: &'static Location<'static>,
+    pub stats: LockStatsSnapshot,
+}
+
+impl LockInfo {
+    /// 사람에게 보일 이름. 이름이 없으면 `UnnamedLocks` 설정에 따라 생성 위치나 `#id` 가 된다.
+    pub fn display_name(&self) -> String {
+        match (&self.name, unnamed_site(self.created_at)) {
+            (Some(name), _) => name.clone(),
+            (None, Some(_)) => {
+                format!("{}:{}", self.created_at.file(), self.created_at.line())
+            }
+            (None, None) => format!("#{}", self.id),
+        }
+    }
+}
+
+/// 만들어졌지만 `Drop` 이 실행되지 않은 가드. (`leaked_guards`, 디버그 빌드 전용)
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuardLeak {
+    /// 프로세스 전역 가드 일련번호. 만들어진 순서대로 증가한다.
+    pub seq: u64,
+    pub lock_id: u64,
+    pub name: Option<String>,
+    pub access: AccessKind,
+    /// 가드를 얻은 호출 위치
+    pub location: &'static Location<'static>,
+    /// 가드보다 락이 먼저 사라짐. 그렇다면 `mem::forget` 이나 새어 나간 `Box` 가 확실하다.
+    /// `false` 면 아직 쥐고 있는 가드일 수도 있다.
+    pub lock_dropped: bool,
+}
+
+/// `group_report` 가 돌려주는 묶음 단위 합계
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupReport {
+    pub group: String,
+    /// 묶음에 속한 살아 있는 락의 id (오름차순)
+    pub ids: Vec<u64>,
+    pub reads: u64,
+    pub writes: u64,
+    /// 모든 락의 누적 점유 시간 합
+    pub total_hold: Duration,
+    /// 락들 중 가장 길었던 점유 시간
+    pub max_hold: Duration,
+    /// 지금 묶음 안의 어느 락이든 기다리는 태스크 수
+    pub waiters: u64,
+    /// 지금 살아 있는 읽기 가드 수
+    pub readers: u64,
+}
+
+/// `top_contended_by` 의 정렬 기준
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContentionOrder {
+    /// 누적 점유 시간
+    #[default]
+    HoldTime,
+    /// 현재 대기자 수
+    Waiters,
+}
+
+/// `group` 에 속한 살아 있는 락들의 통계를 합산한다. 그런 락이 없으면 `None`.
+pub fn group_report(group: &str) -> Option<GroupReport> {
+    let mut members: Vec<_> = live()
+        .into_iter()
+        .filter(|info| info.group.as_deref() == Some(group))
+        .collect();
+    if members.is_empty() {
+        return None;
+    }
+    members.sort_by_key(|info| info.id);
+    let mut report = GroupReport {
+        group: group.to_string(),
+        ids: Vec::with_capacity(members.len()),
+        reads: 0,
+        writes: 0,
+        total_hold: Duration::ZERO,
+        max_hold: Duration::ZERO,
+        waiters: 0,
+        readers: 0,
+    };
+    for info in members {
+        report.ids.push(info.id);
+        report.reads += info.stats.reads;
+        report.writes += info.stats.writes;
+        report.total_hold = report.total_hold.saturating_add(info.stats.total_hold);
+        report.max_hold = report.max_hold.max(info.stats.max_hold);
+        report.waiters += info.stats.waiters;
+        report.readers += info.stats.readers;
+    }
+    Some(report)
+}
+
+/// 만들어졌지만 드롭되지 않은 가드를 일련번호 순으로 돌려준다. (디버그 빌드 전용)
+///
+/// 프로세스가 끝날 때나 테스트 끝에서 부르면 잊힌 가드가 락을 영원히 붙잡는 경우를 찾아낸다.
+/// 이미 사라진 락의 가드(`lock_dropped`)는 확실한 누수이고, 살아 있는 락의 가드는 지금 쥐고 있는
+/// 것일 수도 있다.
+///
+/// 기록에는 한도가 있다. 락마다 동시에 살아 있는 가드는 16개까지만 위치를 기억하고, 사라진 락의
+/// 누수는 프로세스 전체에서 처음 1024개만 남긴다. 넘친 가드도 락이 드롭될 때 나가는 `Misuse`
+/// 이벤트의 개수에는 들어간다.
+///
+/// ```
+/// use tracing_lock::{registry, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let lock = TokioRwLockTrace::new(0);
+/// drop(lock.read().await);
+/// std::mem::forget(lock.read().await);
+/// # #[cfg(debug_assertions)]
+/// assert_eq!(registry::leaked_guards().len(), 1);
+/// # }
+/// ```
+#[cfg(debug_assertions)]
+pub fn leaked_guards() -> Vec<GuardLeak> {
+    let mut leaks = LEAKED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    let locks: Vec<_> = {
+        let mut locks = LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+        locks.retain(|weak| weak.strong_count() > 0);
+        locks.iter().filter_map(Weak::upgrade).collect()
+    };
+    for entry in locks {
+        leaks.extend(entry.leaks(false));
+    }
+    leaks.sort_by_key(|leak| leak.seq);
+    leaks
+}
+
+/// 누적 점유 시간이 가장 긴 락 `n` 개
+pub fn top_contended(n: usize) -> Vec<LockInfo> {
+    top_contended_by(n, ContentionOrder::HoldTime)
+}
+
+/// `order` 기준으로 가장 붐비는 락 `n` 개
+///
+/// ```
+/// use tracing_lock::registry::{self, ContentionOrder};
+///
+/// for info in registry::top_contended_by(5, ContentionOrder::Waiters) {
+///     println!("{:?}: {} waiters", info.name, info.stats.waiters);
+/// }
+/// ```
+pub fn top_contended_by(n: usize, order: ContentionOrder) -> Vec<LockInfo> {
+    let mut locks = live();
+    match order {
+        ContentionOrder::HoldTime => locks.sort_by_key(|info| Reverse(info.stats.total_hold)),
+        ContentionOrder::Waiters => locks.sort_by_key(|info| Reverse(info.stats.waiters)),
+    }
+    locks.truncate(n);
+    locks
+}
+
+/// 살아 있는 모든 락의 지표를 Prometheus 텍스트 형식으로 만든다. (`prometheus` 기능)
+///
+/// 각 시계열은 `lock`(이름, 없으면 빈 문자열)과 `id` 레이블을 가진다. `track_sites` 로 모은
+/// 호출 위치별 기록이 있으면 `id` 대신 `site` 레이블을 가진 `tracing_lock_site_*` 카운터, 게이지,
+/// 히스토그램이 뒤에 붙는다.
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use tracing_lock::{LockConfig, TokioRwLockTrace};
+///
+/// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("sessions"));
+/// drop(lock.read().await);
+/// let text = tracing_lock::registry::prometheus_encode();
+/// assert!(text.contains(r#"tracing_lock_acquisitions_total{lock="sessions""#));
+/// # }
+/// ```
+#[cfg(feature = "prometheus")]
+pub fn prometheus_encode() -> String {
+    use std::fmt::Write;
+
+    let mut locks = live();
+    locks.sort_by_key(|info| info.id);
+    let mut out = String::new();
+    let mut family =
+        |name: &str, kind: &str, help: &str, value: &dyn Fn(&LockInfo, &mut String)| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for info in &locks {
+                value(info, &mut out);
+            }
+        };
+    let labels = |info: &LockInfo| {
+        let name = info.name.as_deref().unwrap_or("");
+        format!("lock=\"{}\",id=\"{}\"", escape_label(name), info.id)
+    };
+    family(
+        "tracing_lock_acquisitions_total",
+        "counter",
+        "Lock acquisitions by access kind.",
+        &|info, out| {
+            let labels = labels(info);
+            let _ = writeln!(
+                out,
+                "tracing_lock_acquisitions_total{{{},kind=\"read\"}} {}",
+                labels, info.stats.reads
+            );
+            let _ = writeln!(
+                out,
+                "tracing_lock_acquisitions_total{{{},kind=\"write\"}} {}",
+                labels, info.stats.writes
+            );
+        },
+    );
+    family(
+        "tracing_lock_waiters",
+        "gauge",
+        "Tasks currently waiting to acquire the lock.",
+        &|info, out| {
+            let _ = writeln!(
+                out,
+                "tracing_lock_waiters{{{}}} {}",
+                labels(info),
+                info.stats.waiters
+            );
+        },
+    );
+    family(
+        "tracing_lock_hold_seconds_total",
+        "counter",
+        "Cumulative time guards were held.",
+        &|info, out| {
+            let _ = writeln!(
+                out,
+                "tracing_lock_hold_seconds_total{{{}}} {}",
+                labels(info),
+                info.stats.total_hold.as_secs_f64()
+            );
+        },
+    );
+    family(
+        "tracing_lock_peak_readers",
+        "gauge",
+        "Highest number of simultaneous read guards.",
+        &|info, out| {
+            let _ = writeln!(
+                out,
+                "tracing_lock_peak_readers{{{}}} {}",
+                labels(info),
+                info.stats.peak_readers
+            );
+        },
+    );
+    crate::sites::prometheus_encode(&mut out);
+    out
+}
+
+/// Prometheus 레이블 값 안의 `\`, `"`, 줄바꿈을 이스케이프한다.
+#[cfg(feature = "prometheus")]
+pub(crate) fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(debug_assertions)]
+    use crate::testing::CapturingObserver;
+    use crate::{LockConfig, TokioRwLockTrace};
+
+    // 다른 테스트의 락이 섞이지 않도록 이름 접두사로 거른다.
+    fn ranked(prefix: &str, order: ContentionOrder) -> Vec<String> {
+        top_contended_by(usize::MAX, order)
+            .into_iter()
+            .filter_map(|info| info.name)
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn ranks_by_hold_time_and_waiters() {
+        let named = |name: &str| {
+            Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().name(name)))
+        };
+        let (cold, warm, hot) = (named("rank-cold"), named("rank-warm"), named("rank-hot"));
+
+        drop(cold.read().await);
+        for (lock, ms) in [(&warm, 10), (&hot, 40)] {
+            let _guard = lock.write().await;
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+        assert_eq!(
+            ranked("rank-", ContentionOrder::HoldTime),
+            ["rank-hot", "rank-warm", "rank-cold"]
+        );
+
+        // cold 에 대기자 둘을 세운다.
+        let held = cold.write().await;
+        let waiting: Vec<_> = (0..2)
+            .map(|_| {
+                let cold = cold.clone();
+                tokio::spawn(async move { drop(cold.read().await) })
+            })
+            .collect();
+        while cold.stats().waiters < 2 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(ranked("rank-", ContentionOrder::Waiters)[0], "rank-cold");
+
+        drop(held);
+        for task in waiting {
+            task.await.unwrap();
+        }
+        assert_eq!(cold.stats().waiters, 0);
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn forgotten_guard_is_reported_as_leaked() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        let id = lock.entry.id;
+        let leaks_of = |id| -> Vec<_> {
+            leaked_guards()
+                .into_iter()
+                .filter(|leak| leak.lock_id == id)
+                .collect()
+        };
+
+        drop(lock.write().await);
+        let kept = lock.read().await;
+        std::mem::forget(lock.read().await);
+        let line = line!() - 1;
+        drop(kept);
+
+        let leaks = leaks_of(id);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].access, AccessKind::Read);
+        assert_eq!(
+            (leaks[0].location.file(), leaks[0].location.line()),
+            (file!(), line)
+        );
+        assert!(!leaks[0].lock_dropped);
+
+        drop(lock);
+        let leaks = leaks_of(id);
+        assert_eq!(leaks.len(), 1);
+        assert!(leaks[0].lock_dropped);
+        let events = events.events();
+        let misuse = events.iter().find(|e| e.kind == EventKind::Misuse).unwrap();
+        assert!(misuse
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with("1 guard(s) were never dropped"));
+    }
+
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    async fn guards_beyond_the_slots_are_still_counted() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().silent().observer(events.clone()));
+        let id = lock.entry.id;
+
+        let readers: Vec<_> = (0..GUARD_SLOTS + 4)
+            .map(|_| lock.try_read().unwrap())
+            .collect();
+        assert_eq!(lock.entry.live_guards().unwrap().0, GUARD_SLOTS + 4);
+        drop(readers);
+        assert!(lock.entry.live_guards().is_none());
+        // 비워진 칸은 다시 쓴다.
+        for _ in 0..GUARD_SLOTS + 4 {
+            std::mem::forget(lock.try_read().unwrap());
+        }
+
+        drop(lock);
+        let leaks = leaked_guards()
+            .into_iter()
+            .filter(|leak| leak.lock_id == id)
+            .count();
+        assert_eq!(leaks, GUARD_SLOTS);
+        let events = events.events();
+        let misuse = events.iter().find(|e| e.kind == EventKind::Misuse).unwrap();
+        assert!(misuse
+            .message
+            .as_deref()
+            .unwrap()
+            .starts_with(&format!("{} guard(s) were never dropped", GUARD_SLOTS + 4)));
+    }
+
+    #[tokio::test]
+    async fn group_report_sums_shards() {
+        let shards: Vec<_> = (0..4)
+            .map(|i| {
+                TokioRwLockTrace::new(0)
+                    .with_config(LockConfig::new().name(format!("shard-{i}")))
+                    .with_group("group-shards")
+            })
+            .collect();
+        let outsider = TokioRwLockTrace::new(0).with_group("group-other");
+        for (i, shard) in shards.iter().enumerate() {
+            for _ in 0..=i {
+                drop(shard.read().await);
+            }
+            *shard.write().await += 1;
+        }
+        drop(outsider.read().await);
+        let _reader = shards[0].read().await;
+
+        let report = group_report("group-shards").unwrap();
+        let mut ids: Vec<_> = shards.iter().map(|shard| shard.entry.id).collect();
+        ids.sort();
+        assert_eq!(report.ids, ids);
+        assert_eq!((report.reads, report.writes), (1 + 2 + 3 + 4 + 1, 4));
+        assert_eq!(report.readers, 1);
+        assert!(report.max_hold <= report.total_hold);
+        assert_eq!(group_report("group-missing"), None);
+
+        let info = top_contended(usize::MAX)
+            .into_iter()
+            .find(|info| info.id == shards[2].entry.id)
+            .unwrap();
+        assert_eq!(info.group.as_deref(), Some("group-shards"));
+        assert_eq!(info.name.as_deref(), Some("shard-2"));
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[tokio::test]
+    async fn prometheus_text_contains_named_locks() {
+        let named = |name: &str| TokioRwLockTrace::new(0).with_config(LockConfig::new().name(name));
+        let (users, orders) = (named("prom-users"), named("prom-\"orders\""));
+        let (_a, _b) = (users.read().await, users.read().await);
+        *orders.write().await += 1;
+
+        let text = prometheus_encode();
+        let series = |metric: &str, lock: &TokioRwLockTrace<i32>, name: &str| {
+            let id = lock.entry.id;
+            format!("{metric}{{lock=\"{name}\",id=\"{id}\"")
+        };
+        for line in [
+            format!(
+                "{},kind=\"read\"}} 2",
+                series("tracing_lock_acquisitions_total", &users, "prom-users")
+            ),
+            format!(
+                "{},kind=\"write\"}} 0",
+                series("tracing_lock_acquisitions_total", &users, "prom-users")
+            ),
+            format!(
+                "{}}} 2",
+                series("tracing_lock_peak_readers", &users, "prom-users")
+            ),
+            format!(
+                "{}}} 0",
+                series("tracing_lock_waiters", &users, "prom-users")
+            ),
+            format!(
+                "{},kind=\"write\"}} 1",
+                series(
+                    "tracing_lock_acquisitions_total",
+                    &orders,
+                    r#"prom-\"orders\""#
+                )
+            ),
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {line:?} in\n{text}"
+            );
+        }
+        assert!(text.contains("# TYPE tracing_lock_hold_seconds_total counter"));
+    }
+}
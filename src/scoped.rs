@@ -0,0 +1,104 @@
+//! 가드를 밖으로 내주지 않고 클로저 안에서만 쓰는 획득.
+
+use std::future::Future;
+
+use crate::lock::TokioRwLockTrace;
+
+impl<T: Send + Sync> TokioRwLockTrace<T> {
+    /// 읽기 락을 얻은 뒤 `f` 를 부르고 곧바로 해제한다.
+    ///
+    /// `f` 는 락을 얻은 뒤에만 동기적으로 실행된다. `select!` 나 `timeout` 으로 획득 중에
+    /// 취소되면 취소 이벤트만 남고 `f` 는 불리지 않는다.
+    #[track_caller]
+    pub fn with_read<'a, R, F>(&'a self, f: F) -> impl Future<Output = R> + 'a
+    where
+        F: FnOnce(&T) -> R + 'a,
+    {
+        // 호출 위치를 남기도록 future 는 여기서 만든다.
+        let acquire = self.read();
+        async move { f(&*acquire.await) }
+    }
+
+    /// 쓰기 락을 얻은 뒤 `f` 를 부르고 곧바로 해제한다.
+    ///
+    /// 획득 중에 취소되면 `f` 는 불리지 않으므로, 값이 반쯤 바뀐 채로 남는 일이 없다.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(vec![1]);
+    /// let len = lock.with_write(|items| { items.push(2); items.len() }).await;
+    /// assert_eq!(len, 2);
+    ///
+    /// let held = lock.read().await;
+    /// let timed_out = tokio::time::timeout(
+    ///     Duration::from_millis(5),
+    ///     lock.with_write(|items| items.clear()),
+    /// );
+    /// assert!(timed_out.await.is_err());
+    /// drop(held);
+    /// assert_eq!(lock.with_read(Vec::len).await, 2);
+    /// # }
+    /// ```
+    #[track_caller]
+    pub fn with_write<'a, R, F>(&'a self, f: F) -> impl Future<Output = R> + 'a
+    where
+        F: FnOnce(&mut T) -> R + 'a,
+    {
+        let acquire = self.write();
+        async move { f(&mut *acquire.await) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    use crate::testing::CapturingObserver;
+    use crate::{EventKind, LockConfig, TokioRwLockTrace};
+
+    #[tokio::test]
+    async fn cancelled_with_write_never_runs_closure() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(events.clone()));
+        let ran = AtomicBool::new(false);
+
+        let held = lock.read().await;
+        let cancelled_line = line!() + 3;
+        let result = tokio::time::timeout(
+            Duration::from_millis(10),
+            lock.with_write(|value| {
+                ran.store(true, Ordering::Relaxed);
+                *value += 1;
+            }),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!ran.load(Ordering::Relaxed));
+        assert_eq!(lock.stats().waiters, 0);
+        drop(held);
+
+        assert_eq!(*lock.raw().read().await, 0);
+        assert_eq!(
+            lock.with_write(|value| std::mem::replace(value, 5)).await,
+            0
+        );
+        assert_eq!(lock.with_read(|value| *value).await, 5);
+
+        let events = events.events();
+        let cancelled: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Cancelled)
+            .collect();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].location.line(), cancelled_line);
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes), (2, 1));
+    }
+}
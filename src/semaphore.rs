@@ -0,0 +1,146 @@
+//! 계측된 `tokio::sync::Semaphore`.
+
+use std::future::Future;
+
+use tokio::sync::{AcquireError, Semaphore, SemaphorePermit, TryAcquireError};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::probe::{Probe, TracedGuard};
+use crate::stats::LockStatsSnapshot;
+
+/// `TracedSemaphore::acquire` 가 돌려주는 허가
+pub type TracedSemaphorePermit<'a> = TracedGuard<'a, SemaphorePermit<'a>>;
+
+/// 허가를 얻고 돌려준 시점과 쥔 시간을 기록하는 `tokio::sync::Semaphore`.
+///
+/// 허가는 여럿이 함께 쥘 수 있으므로 `AccessKind::Read` 로 남는다. 통계의 `reads` 는 허가를 얻은
+/// 횟수, `readers`/`peak_readers` 는 지금/최대 동시에 쥔 허가 수다.
+///
+/// ```
+/// use tracing_lock::TracedSemaphore;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let connections = TracedSemaphore::new(2);
+/// let permit = connections.acquire().await.unwrap();
+/// assert_eq!(connections.available_permits(), 1);
+/// drop(permit);
+/// assert_eq!(connections.stats().reads, 1);
+/// # }
+/// ```
+pub struct TracedSemaphore {
+    probe: Probe,
+    inner: Semaphore,
+}
+
+impl TracedSemaphore {
+    #[track_caller]
+    pub fn new(permits: usize) -> Self {
+        TracedSemaphore {
+            probe: Probe::new(),
+            inner: Semaphore::new(permits),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    pub fn available_permits(&self) -> usize {
+        self.inner.available_permits()
+    }
+
+    pub fn add_permits(&self, n: usize) {
+        self.inner.add_permits(n);
+    }
+
+    /// 세마포어를 닫는다. 기다리던 `acquire` 는 `AcquireError` 로 끝난다.
+    pub fn close(&self) {
+        self.inner.close();
+    }
+
+    /// 허가 하나를 기다린다. 호출 위치는 future 를 만든 곳이다.
+    #[track_caller]
+    pub fn acquire(
+        &self,
+    ) -> impl Future<Output = Result<TracedSemaphorePermit<'_>, AcquireError>> + '_ {
+        let waiting = self.probe.wait(AccessKind::Read);
+        async move {
+            let (permit, contended) = match self.inner.try_acquire() {
+                Ok(permit) => (permit, false),
+                Err(_) => (self.inner.acquire().await?, true),
+            };
+            Ok(self
+                .probe
+                .acquired(waiting, permit, AccessKind::Read, contended))
+        }
+    }
+
+    /// 기다리지 않고 허가를 시도한다. 남은 허가가 없으면 `Busy` 이벤트를 남기고 실패한다.
+    #[track_caller]
+    pub fn try_acquire(&self) -> Result<TracedSemaphorePermit<'_>, TryAcquireError> {
+        match self.inner.try_acquire() {
+            Ok(permit) => Ok(self.probe.acquired(
+                self.probe.wait(AccessKind::Read),
+                permit,
+                AccessKind::Read,
+                false,
+            )),
+            Err(err) => {
+                if err == TryAcquireError::NoPermits {
+                    self.probe.busy(AccessKind::Read);
+                }
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn permits_are_counted_as_shared_holders() {
+        let semaphore = Arc::new(TracedSemaphore::new(2).with_config(LockConfig::new().silent()));
+        let first = semaphore.acquire().await.unwrap();
+        let second = semaphore.try_acquire().unwrap();
+        assert!(matches!(
+            semaphore.try_acquire(),
+            Err(TryAcquireError::NoPermits)
+        ));
+        assert_eq!(semaphore.stats().readers, 2);
+
+        let third = {
+            let semaphore = semaphore.clone();
+            tokio::spawn(async move { drop(semaphore.acquire().await.unwrap()) })
+        };
+        while semaphore.stats().waiters < 1 {
+            tokio::task::yield_now().await;
+        }
+        drop(first);
+        third.await.unwrap();
+        drop(second);
+
+        let stats = semaphore.stats();
+        assert_eq!((stats.reads, stats.readers, stats.peak_readers), (3, 0, 2));
+        assert!(stats.first_contended.is_some());
+
+        semaphore.close();
+        assert!(semaphore.acquire().await.is_err());
+    }
+}
@@ -0,0 +1,473 @@
+//! 락 이름과 획득 호출 위치마다 모은 통계. (`LockConfig::track_sites`)
+//!
+//! 같은 이름을 가진 락이 여럿이면 같은 호출 위치의 통계는 하나로 합쳐진다. 기록은 락이 사라져도
+//! 프로세스가 끝날 때까지 남으므로, 요청마다 이름이 달라지는 락에는 켜지 말 것.
+//!
+//! ```
+//! use tracing_lock::{sites, LockConfig, TokioRwLockTrace};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs").track_sites());
+//! *lock.write().await += 1;
+//!
+//! let site = sites::snapshot()
+//!     .into_iter()
+//!     .find(|site| site.name.as_deref() == Some("jobs"))
+//!     .unwrap();
+//! assert_eq!(site.writes, 1);
+//! assert_eq!(site.hold.count(), 1);
+//! # }
+//! ```
+
+use std::{
+    panic::Location,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Duration,
+};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::registry::Entry;
+
+/// 히스토그램 칸의 상한. 마지막 칸은 가장 큰 상한보다 긴 값을 모두 받는다.
+pub const HISTOGRAM_BOUNDS: [Duration; 8] = [
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+    Duration::from_secs(1),
+    Duration::from_secs(10),
+];
+
+const BUCKETS: usize = HISTOGRAM_BOUNDS.len() + 1;
+
+static SITES: Mutex<Vec<Arc<Site>>> = Mutex::new(Vec::new());
+
+/// 락 이름과 호출 위치 하나의 누적 기록
+#[derive(Debug)]
+pub(crate) struct Site {
+    name: Option<String>,
+    location: &'static Location<'static>,
+    // [read, write]
+    acquisitions: [AtomicU64; 2],
+    holders: [AtomicU64; 2],
+    wait: Buckets,
+    hold: Buckets,
+    #[cfg(feature = "metrics")]
+    keys: crate::metrics::SiteKeys,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    counts: [AtomicU64; BUCKETS],
+    sum_nanos: AtomicU64,
+}
+
+impl Buckets {
+    fn record(&self, value: Duration) {
+        let index = HISTOGRAM_BOUNDS.partition_point(|bound| *bound < value);
+        self.counts[index].fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(value.as_nanos()).unwrap_or(u64::MAX);
+        self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Histogram {
+        Histogram {
+            counts: std::array::from_fn(|i| self.counts[i].load(Ordering::Relaxed)),
+            sum: Duration::from_nanos(self.sum_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+fn index(access: AccessKind) -> usize {
+    match access {
+        AccessKind::Read => 0,
+        AccessKind::Write => 1,
+    }
+}
+
+impl Site {
+    fn new(name: Option<&str>, location: &'static Location<'static>) -> Self {
+        Site {
+            name: name.map(str::to_owned),
+            location,
+            acquisitions: Default::default(),
+            holders: Default::default(),
+            wait: Buckets::default(),
+            hold: Buckets::default(),
+            #[cfg(feature = "metrics")]
+            keys: crate::metrics::SiteKeys::new(name, location),
+        }
+    }
+
+    fn is(&self, name: Option<&str>, location: &Location<'_>) -> bool {
+        self.name.as_deref() == name && *self.location == *location
+    }
+
+    /// 가드를 놓음. 쥔 시간을 쟀을 때만 점유 히스토그램에 넣는다.
+    pub(crate) fn released(&self, access: AccessKind, held: Option<Duration>) {
+        let _holders = self.holders[index(access)].fetch_sub(1, Ordering::Relaxed) - 1;
+        if let Some(held) = held {
+            self.hold.record(held);
+        }
+        #[cfg(feature = "metrics")]
+        crate::metrics::site_released(&self.keys, access, held, _holders);
+    }
+
+    /// 가드가 놓이지 않고 `from` 에서 `to` 로 바뀜. 획득 수는 그대로다.
+    pub(crate) fn transition(&self, from: AccessKind, to: AccessKind) {
+        let _from = self.holders[index(from)].fetch_sub(1, Ordering::Relaxed) - 1;
+        let _to = self.holders[index(to)].fetch_add(1, Ordering::Relaxed) + 1;
+        #[cfg(feature = "metrics")]
+        {
+            crate::metrics::site_holders(&self.keys, from, _from);
+            crate::metrics::site_holders(&self.keys, to, _to);
+        }
+    }
+
+    fn snapshot(&self) -> SiteSnapshot {
+        let load =
+            |counters: &[AtomicU64; 2], access| counters[index(access)].load(Ordering::Relaxed);
+        SiteSnapshot {
+            name: self.name.clone(),
+            location: self.location,
+            reads: load(&self.acquisitions, AccessKind::Read),
+            writes: load(&self.acquisitions, AccessKind::Write),
+            readers: load(&self.holders, AccessKind::Read),
+            writers: load(&self.holders, AccessKind::Write),
+            wait: self.wait.snapshot(),
+            hold: self.hold.snapshot(),
+        }
+    }
+}
+
+/// `track_sites` 가 켜져 있으면 획득 한 번을 그 호출 위치에 더하고, 해제 때 쓸 기록을 돌려준다.
+pub(crate) fn acquired(
+    config: &LockConfig,
+    entry: &Entry,
+    access: AccessKind,
+    location: &'static Location<'static>,
+    waited: Option<Duration>,
+) -> Option<Arc<Site>> {
+    if !config.track_sites {
+        return None;
+    }
+    let site = site(entry, config.name.as_deref(), location);
+    site.acquisitions[index(access)].fetch_add(1, Ordering::Relaxed);
+    let _holders = site.holders[index(access)].fetch_add(1, Ordering::Relaxed) + 1;
+    if let Some(waited) = waited {
+        site.wait.record(waited);
+    }
+    #[cfg(feature = "metrics")]
+    crate::metrics::site_acquired(&site.keys, access, waited, _holders);
+    Some(site)
+}
+
+// 락마다 쓴 호출 위치를 들고 있어서, 전역 목록은 처음 보는 위치에서만 잠근다.
+fn site(entry: &Entry, name: Option<&str>, location: &'static Location<'static>) -> Arc<Site> {
+    let mut cached = entry.sites.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(site) = cached.iter().find(|site| site.is(name, location)) {
+        return site.clone();
+    }
+    let site = {
+        let mut sites = SITES.lock().unwrap_or_else(PoisonError::into_inner);
+        match sites.iter().find(|site| site.is(name, location)) {
+            Some(site) => site.clone(),
+            None => {
+                let site = Arc::new(Site::new(name, location));
+                sites.push(site.clone());
+                site
+            }
+        }
+    };
+    cached.push(site.clone());
+    site
+}
+
+/// `snapshot` 이 돌려주는, 락 이름과 호출 위치 하나의 누적 통계
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiteSnapshot {
+    pub name: Option<String>,
+    /// `read()`/`write()` 를 부른 위치
+    pub location: &'static Location<'static>,
+    pub reads: u64,
+    pub writes: u64,
+    /// 이 위치에서 얻어 지금 살아 있는 읽기/쓰기 가드 수. 쓰기 가드를 `downgrade` 하면 읽기로 옮겨
+    /// 센다.
+    pub readers: u64,
+    pub writers: u64,
+    /// 기다린 시간. 시간을 재지 않는 락(`with_timing(false)`)의 획득은 빠진다.
+    pub wait: Histogram,
+    /// 가드를 쥐고 있던 시간
+    pub hold: Histogram,
+}
+
+/// `HISTOGRAM_BOUNDS` 의 칸으로 나눈 시간 분포
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    /// 칸마다 든 개수. `counts[i]` 는 `HISTOGRAM_BOUNDS[i]` 이하이고 바로 앞 상한보다 긴 값이며,
+    /// 마지막 칸은 가장 큰 상한보다 긴 값이다.
+    pub counts: [u64; BUCKETS],
+    pub sum: Duration,
+}
+
+impl Histogram {
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// 평균. 기록이 없으면 `None`.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.count()).unwrap_or(u32::MAX);
+        (count > 0).then(|| self.sum / count)
+    }
+}
+
+/// 지금까지 기록된 모든 호출 위치의 통계. 이름, 파일, 줄 순서다.
+pub fn snapshot() -> Vec<SiteSnapshot> {
+    let mut sites: Vec<_> = SITES
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|site| site.snapshot())
+        .collect();
+    sites.sort_by(|a, b| {
+        (
+            &a.name,
+            a.location.file(),
+            a.location.line(),
+            a.location.column(),
+        )
+            .cmp(&(
+                &b.name,
+                b.location.file(),
+                b.location.line(),
+                b.location.column(),
+            ))
+    });
+    sites
+}
+
+/// `registry::prometheus_encode` 뒤에 호출 위치별 시계열을 붙인다. 기록이 없으면 아무것도 쓰지 않는다.
+#[cfg(feature = "prometheus")]
+pub(crate) fn prometheus_encode(out: &mut String) {
+    use std::fmt::Write;
+
+    let sites = snapshot();
+    if sites.is_empty() {
+        return;
+    }
+    let labels = |site: &SiteSnapshot| {
+        format!(
+            "lock=\"{}\",site=\"{}\"",
+            crate::registry::escape_label(site.name.as_deref().unwrap_or("")),
+            crate::registry::escape_label(&format!(
+                "{}:{}",
+                site.location.file(),
+                site.location.line()
+            )),
+        )
+    };
+    let header = |out: &mut String, name: &str, kind: &str, help: &str| {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    };
+    header(
+        out,
+        "tracing_lock_site_acquisitions_total",
+        "counter",
+        "Lock acquisitions by call site and access kind.",
+    );
+    for site in &sites {
+        for (kind, value) in [("read", site.reads), ("write", site.writes)] {
+            let _ = writeln!(
+                out,
+                "tracing_lock_site_acquisitions_total{{{},kind=\"{}\"}} {}",
+                labels(site),
+                kind,
+                value
+            );
+        }
+    }
+    header(
+        out,
+        "tracing_lock_site_holders",
+        "gauge",
+        "Guards currently held that were acquired at the call site.",
+    );
+    for site in &sites {
+        for (kind, value) in [("read", site.readers), ("write", site.writers)] {
+            let _ = writeln!(
+                out,
+                "tracing_lock_site_holders{{{},kind=\"{}\"}} {}",
+                labels(site),
+                kind,
+                value
+            );
+        }
+    }
+    for (name, help, histogram) in [
+        (
+            "tracing_lock_site_wait_seconds",
+            "Time spent waiting to acquire the lock at the call site.",
+            (|site: &SiteSnapshot| &site.wait) as fn(&SiteSnapshot) -> &Histogram,
+        ),
+        (
+            "tracing_lock_site_hold_seconds",
+            "Time guards acquired at the call site were held.",
+            |site: &SiteSnapshot| &site.hold,
+        ),
+    ] {
+        header(out, name, "histogram", help);
+        for site in &sites {
+            let labels = labels(site);
+            let histogram = histogram(site);
+            let mut cumulative = 0;
+            for (i, count) in histogram.counts.iter().enumerate() {
+                cumulative += count;
+                let le = match HISTOGRAM_BOUNDS.get(i) {
+                    Some(bound) => bound.as_secs_f64().to_string(),
+                    None => "+Inf".to_string(),
+                };
+                let _ = writeln!(
+                    out,
+                    "{}_bucket{{{},le=\"{}\"}} {}",
+                    name, labels, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{}_sum{{{}}} {}",
+                name,
+                labels,
+                histogram.sum.as_secs_f64()
+            );
+            let _ = writeln!(out, "{}_count{{{}}} {}", name, labels, cumulative);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokioRwLockTrace;
+
+    // 다른 테스트의 기록이 섞이지 않도록 이름으로 거른다.
+    fn sites_of(name: &str) -> Vec<SiteSnapshot> {
+        snapshot()
+            .into_iter()
+            .filter(|site| site.name.as_deref() == Some(name))
+            .collect()
+    }
+
+    #[test]
+    fn histogram_buckets_are_inclusive_upper_bounds() {
+        let buckets = Buckets::default();
+        for value in [
+            Duration::ZERO,
+            Duration::from_micros(1),
+            Duration::from_micros(2),
+            Duration::from_secs(60),
+        ] {
+            buckets.record(value);
+        }
+        let histogram = buckets.snapshot();
+        assert_eq!(histogram.counts, [2, 1, 0, 0, 0, 0, 0, 0, 1]);
+        assert_eq!(histogram.count(), 4);
+        assert_eq!(histogram.sum, Duration::from_micros(60_000_003));
+    }
+
+    #[tokio::test]
+    async fn aggregates_by_name_and_call_site() {
+        let config = LockConfig::new().name("sites-jobs").silent().track_sites();
+        let first = TokioRwLockTrace::new(0).with_config(config.clone());
+        let second = TokioRwLockTrace::new(0).with_config(config);
+        let untracked = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("sites-off"));
+
+        let read_at = Location::caller();
+        for lock in [&first, &second] {
+            drop(lock.read().await);
+        }
+        let writer = first.write().await;
+        drop(untracked.write().await);
+
+        let sites = sites_of("sites-jobs");
+        assert_eq!(sites.len(), 2);
+        let reads = sites
+            .iter()
+            .find(|site| site.location.line() == read_at.line() + 2)
+            .unwrap();
+        assert_eq!((reads.reads, reads.writes, reads.readers), (2, 0, 0));
+        assert_eq!(reads.hold.count(), 2);
+        assert_eq!(reads.wait.count(), 2);
+        let writes = sites.iter().find(|site| site.writes == 1).unwrap();
+        assert_eq!((writes.writers, writes.hold.count()), (1, 0));
+
+        let reader = writer.downgrade();
+        let writes = sites_of("sites-jobs")
+            .into_iter()
+            .find(|site| site.writes == 1)
+            .unwrap();
+        assert_eq!((writes.readers, writes.writers), (1, 0));
+        drop(reader);
+        let writes = sites_of("sites-jobs")
+            .into_iter()
+            .find(|site| site.writes == 1)
+            .unwrap();
+        assert_eq!((writes.readers, writes.hold.count()), (0, 1));
+        assert!(sites_of("sites-off").is_empty());
+    }
+
+    #[tokio::test]
+    async fn renaming_starts_a_new_site() {
+        let mut lock =
+            TokioRwLockTrace::new(0).with_config(LockConfig::new().silent().track_sites());
+        let acquire = |lock: &TokioRwLockTrace<i32>| drop(lock.try_read().unwrap());
+        acquire(&lock);
+        lock.__name_if_unnamed("sites-renamed");
+        acquire(&lock);
+        acquire(&lock);
+
+        let after = sites_of("sites-renamed");
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].reads, 2);
+        let before: Vec<_> = snapshot()
+            .into_iter()
+            .filter(|site| site.name.is_none() && site.location == after[0].location)
+            .collect();
+        assert_eq!(before.len(), 1);
+        assert_eq!(before[0].reads, 1);
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[tokio::test]
+    async fn prometheus_text_has_site_histograms() {
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().name("sites-prom").silent().track_sites());
+        let at = Location::caller();
+        drop(lock.write().await);
+
+        let text = crate::registry::prometheus_encode();
+        let labels = format!(
+            "lock=\"sites-prom\",site=\"{}:{}\"",
+            at.file(),
+            at.line() + 1
+        );
+        for line in [
+            format!("tracing_lock_site_acquisitions_total{{{labels},kind=\"write\"}} 1"),
+            format!("tracing_lock_site_holders{{{labels},kind=\"write\"}} 0"),
+            format!("tracing_lock_site_hold_seconds_bucket{{{labels},le=\"+Inf\"}} 1"),
+            format!("tracing_lock_site_hold_seconds_count{{{labels}}} 1"),
+            format!("tracing_lock_site_wait_seconds_bucket{{{labels},le=\"0.000001\"}} 1"),
+        ] {
+            assert!(text.contains(&line), "missing {line} in\n{text}");
+        }
+        assert!(text.contains("# TYPE tracing_lock_site_wait_seconds histogram"));
+    }
+}
@@ -0,0 +1,133 @@
+//! 락마다 가장 오래 기다린/쥔 획득 몇 개를 남겨 두는 기록.
+
+use std::{
+    panic::Location,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{Duration, SystemTime},
+};
+
+use crate::event::AccessKind;
+
+/// 기준마다 남겨 두는 획득 수
+pub const SLOWEST_KEPT: usize = 8;
+
+/// `slowest_by` 의 기준
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SlowBy {
+    /// 락을 얻기까지 기다린 시간
+    #[default]
+    Wait,
+    /// 가드를 쥐고 있던 시간
+    Hold,
+}
+
+/// `TokioRwLockTrace::slowest` 가 돌려주는, 오래 걸린 획득 하나
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowAcquisition {
+    pub by: SlowBy,
+    pub access: AccessKind,
+    /// `read()`/`write()` 를 부른 위치
+    pub location: &'static Location<'static>,
+    /// 기다렸거나 쥐고 있던 시간
+    pub duration: Duration,
+    /// 기록된 시각. 대기는 락을 얻은 때, 점유는 놓은 때다.
+    pub at: SystemTime,
+}
+
+/// 기준마다 가장 긴 `SLOWEST_KEPT` 개. 목록이 차면 가장 짧은 것보다 긴 것만 잠금을 잡는다.
+#[derive(Debug, Default)]
+pub(crate) struct SlowLog {
+    wait: Ranked,
+    hold: Ranked,
+}
+
+#[derive(Debug, Default)]
+struct Ranked {
+    // 목록이 찼을 때 들어오려면 넘어야 하는 길이(ns). 차기 전에는 0.
+    floor: AtomicU64,
+    // 긴 순서
+    kept: Mutex<Vec<SlowAcquisition>>,
+}
+
+impl SlowLog {
+    pub(crate) fn record(
+        &self,
+        by: SlowBy,
+        access: AccessKind,
+        location: &'static Location<'static>,
+        duration: Duration,
+    ) {
+        let ranked = match by {
+            SlowBy::Wait => &self.wait,
+            SlowBy::Hold => &self.hold,
+        };
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        if nanos == 0 || nanos <= ranked.floor.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut kept = ranked.kept.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = kept.partition_point(|slow| slow.duration >= duration);
+        if index >= SLOWEST_KEPT {
+            return;
+        }
+        kept.insert(
+            index,
+            SlowAcquisition {
+                by,
+                access,
+                location,
+                duration,
+                at: SystemTime::now(),
+            },
+        );
+        kept.truncate(SLOWEST_KEPT);
+        if kept.len() == SLOWEST_KEPT {
+            let shortest = kept[SLOWEST_KEPT - 1].duration.as_nanos();
+            ranked.floor.store(
+                u64::try_from(shortest).unwrap_or(u64::MAX),
+                Ordering::Relaxed,
+            );
+        }
+    }
+
+    pub(crate) fn get(&self, by: SlowBy) -> Vec<SlowAcquisition> {
+        let ranked = match by {
+            SlowBy::Wait => &self.wait,
+            SlowBy::Hold => &self.hold,
+        };
+        ranked
+            .kept
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_longest() {
+        let log = SlowLog::default();
+        let here = Location::caller();
+        for ms in [3, 1, 9, 0, 4, 7, 2, 8, 6, 5, 10] {
+            log.record(
+                SlowBy::Hold,
+                AccessKind::Read,
+                here,
+                Duration::from_millis(ms),
+            );
+        }
+        let kept: Vec<_> = log
+            .get(SlowBy::Hold)
+            .iter()
+            .map(|slow| slow.duration.as_millis())
+            .collect();
+        assert_eq!(kept, [10, 9, 8, 7, 6, 5, 4, 3]);
+        assert!(log.get(SlowBy::Wait).is_empty());
+    }
+}
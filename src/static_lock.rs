@@ -0,0 +1,159 @@
+//! `static` 에 둘 수 있는 계측 락.
+
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use tokio::sync::RwLock;
+
+use crate::config::LockConfig;
+use crate::future::{ReadFuture, WriteFuture};
+use crate::registry::{self, Entry};
+use crate::stats::LockStatsSnapshot;
+
+/// `const_new` 로 만들어 `static` 에 둘 수 있는 `TokioRwLockTrace`.
+///
+/// 값은 `RwLock::const_new` 로 바로 들어가고, 이름이나 관찰자처럼 `const` 로 만들 수 없는 계측
+/// 상태는 처음 쓸 때 만든다. 그 전에 `configure` 로 설정을 정할 수 있으며, 정한 값은
+/// `LockConfig::inherited()`(전역 기본 설정) 위에 얹힌다. 레지스트리에는 처음 쓸 때 등록되며 생성
+/// 위치도 그 호출 위치가 된다. `Arc` 로 값을 나누지 않으므로 `from`/`downgrade` 는 없다.
+///
+/// ```
+/// use tracing_lock::{LockConfig, StaticRwLockTrace};
+///
+/// static JOBS: StaticRwLockTrace<Vec<u32>> = StaticRwLockTrace::const_new(Vec::new());
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// assert!(JOBS.configure(LockConfig::new().name("jobs")));
+/// JOBS.write().await.push(1);
+/// assert_eq!(JOBS.read().await.len(), 1);
+/// # }
+/// ```
+pub struct StaticRwLockTrace<T> {
+    inner: RwLock<T>,
+    pending: Mutex<Pending>,
+    state: OnceLock<(Arc<LockConfig>, Arc<Entry>)>,
+}
+
+struct Pending {
+    config: Option<LockConfig>,
+    // 계측 상태를 만들기 시작함. 이후의 `configure` 는 거절한다.
+    started: bool,
+}
+
+impl<T> StaticRwLockTrace<T> {
+    pub const fn const_new(value: T) -> Self {
+        StaticRwLockTrace {
+            inner: RwLock::const_new(value),
+            pending: Mutex::new(Pending {
+                config: None,
+                started: false,
+            }),
+            state: OnceLock::new(),
+        }
+    }
+
+    /// 처음 쓰기 전에 설정을 정하고 `true` 를 돌려준다. 이미 한 번이라도 썼으면 설정을 버리고
+    /// `false` 를 돌려준다.
+    ///
+    /// 여러 번 부르면 마지막 설정이 쓰인다.
+    pub fn configure(&self, config: LockConfig) -> bool {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        if pending.started {
+            return false;
+        }
+        pending.config = Some(config);
+        true
+    }
+
+    #[track_caller]
+    fn state(&self) -> (&Arc<LockConfig>, &Arc<Entry>) {
+        let (config, entry) = self.state.get_or_init(|| {
+            let config = {
+                let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+                pending.started = true;
+                pending.config.take()
+            };
+            let config = match config {
+                Some(config) => config.overlay(&LockConfig::inherited()),
+                None => LockConfig::inherited(),
+            };
+            let entry = registry::register();
+            entry.configure(&config);
+            (Arc::new(config), entry)
+        });
+        (config, entry)
+    }
+
+    #[track_caller]
+    pub fn read(&self) -> ReadFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        let (config, entry) = self.state();
+        ReadFuture::new(&self.inner, config, entry, None, ())
+    }
+
+    #[track_caller]
+    pub fn write(&self) -> WriteFuture<'_, T>
+    where
+        T: Send + Sync,
+    {
+        let (config, entry) = self.state();
+        WriteFuture::new(&self.inner, config, entry, None, ())
+    }
+
+    /// `configure` 로 정한 이름. 아직 쓰지 않았으면 `None`.
+    pub fn name(&self) -> Option<&str> {
+        self.state.get()?.0.name.as_deref()
+    }
+
+    /// 현재까지의 통계 스냅샷. 아직 쓰지 않았으면 빈 통계다.
+    pub fn stats(&self) -> LockStatsSnapshot {
+        match self.state.get() {
+            Some((_, entry)) => entry.stats.snapshot(),
+            None => crate::stats::LockStats::default().snapshot(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::EventKind;
+
+    static COUNTER: StaticRwLockTrace<u32> = StaticRwLockTrace::const_new(0);
+
+    #[tokio::test]
+    async fn static_lock_is_instrumented() {
+        let events = CapturingObserver::new();
+        assert_eq!(COUNTER.stats().writes, 0);
+        assert!(COUNTER.configure(
+            LockConfig::new()
+                .name("counter")
+                .silent()
+                .observer(events.clone()),
+        ));
+
+        *COUNTER.write().await += 1;
+        assert_eq!(*COUNTER.read().await, 1);
+        assert_eq!(COUNTER.name(), Some("counter"));
+        assert_eq!(COUNTER.stats().writes, 1);
+        assert_eq!(COUNTER.stats().reads, 1);
+        assert!(!COUNTER.configure(LockConfig::new()));
+
+        let events = events.events();
+        let kinds: Vec<_> = events.iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                EventKind::Created,
+                EventKind::Acquire,
+                EventKind::Release,
+                EventKind::Acquire,
+                EventKind::Release,
+            ]
+        );
+        assert!(events.iter().all(|e| e.name.as_deref() == Some("counter")));
+    }
+}
@@ -0,0 +1,594 @@
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::time::Instant;
+
+use crate::event::AccessKind;
+
+/// 락 단위 누적 통계. 모든 카운터는 원자적으로 갱신된다.
+#[derive(Debug, Default)]
+pub(crate) struct LockStats {
+    reads: AtomicU64,
+    writes: AtomicU64,
+    // 마지막 쓰기 이후의 읽기 횟수
+    reads_since_write: AtomicU64,
+    // 쓰기 사이 구간(window)마다 샘플링한 읽기 횟수의 합과 구간 수
+    window_reads: AtomicU64,
+    windows: AtomicU64,
+    // 누적 점유 시간(ns) = hold_wraps * 2^64 + hold_nanos. 하위 카운터는 wrapping 으로 더하고
+    // 넘칠 때마다 상위 카운터를 올리므로 평소 경로는 `fetch_add` 하나다.
+    hold_nanos: AtomicU64,
+    hold_wraps: AtomicU64,
+    // 현재 획득을 기다리는 태스크 수
+    waiters: AtomicU64,
+    // 현재/최대 동시 읽기 가드 수
+    readers: AtomicU64,
+    peak_readers: AtomicU64,
+    // `with_max_readers` 세마포어에서 가져간 허가 수
+    read_permits: AtomicU64,
+    // 해제 횟수와 가장 길었던 점유 시간
+    releases: AtomicU64,
+    max_hold_nanos: AtomicU64,
+    downgrades: AtomicU64,
+    // 대기 중인 writer 수와 그중 첫 writer 가 대기를 시작한 시각(`now_nanos`, 0 이면 없음)
+    waiting_writers: AtomicU64,
+    writer_wait_start: AtomicU64,
+    // 기다리는 writer 중 가장 높은 우선순위 + 1. 0 은 없음.
+    waiting_priority: AtomicU16,
+    // `LockConfig::sample_stats`. 0 이면 샘플링하지 않는다.
+    sample_every: AtomicU32,
+    // 경합률 창: 현재 창의 시작 시각(`now_nanos`)과 현재/직전 창의 (획득, 기다린 획득) 수
+    window_start: AtomicU64,
+    window_acquired: AtomicU64,
+    window_contended: AtomicU64,
+    prev_acquired: AtomicU64,
+    prev_contended: AtomicU64,
+    // 경합률이 문턱을 넘어 경고를 낸 상태. 다시 내려가면 풀린다.
+    contention_alarm: AtomicBool,
+    // 마지막으로 쓰기 가드를 놓거나 읽기로 바꾼 시각(`now_nanos`). 0 이면 아직 없다.
+    last_write: AtomicU64,
+    // 처음으로 기다려야 했던 획득의 시각(`now_nanos`). 0 이면 아직 경합이 없었다.
+    first_contended: AtomicU64,
+}
+
+/// 경합률을 재는 창 하나의 길이. 직전 창과 현재 창을 합쳐서 계산한다.
+pub(crate) const CONTENTION_WINDOW: Duration = Duration::from_secs(10);
+// 표본이 이보다 적으면 경합률 경고를 내지 않는다.
+const CONTENTION_MIN_SAMPLES: u64 = 10;
+
+impl LockStats {
+    pub(crate) fn set_sample_every(&self, every: Option<u32>) {
+        self.sample_every
+            .store(every.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    // 이번 갱신을 몇 번으로 셀지. 샘플링에서 빠지면 0.
+    fn weight(&self) -> u64 {
+        match self.sample_every.load(Ordering::Relaxed) {
+            0 => 1,
+            every if next_random() % u64::from(every) == 0 => u64::from(every),
+            _ => 0,
+        }
+    }
+
+    pub(crate) fn record_read(&self) {
+        let weight = self.weight();
+        if weight > 0 {
+            self.reads.fetch_add(weight, Ordering::Relaxed);
+            self.reads_since_write.fetch_add(weight, Ordering::Relaxed);
+        }
+        let readers = self.readers.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_readers.fetch_max(readers, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_write(&self) {
+        let weight = self.weight();
+        if weight == 0 {
+            return;
+        }
+        let previous_writes = self.writes.fetch_add(weight, Ordering::Relaxed);
+        let reads = self.reads_since_write.swap(0, Ordering::Relaxed);
+        // 첫 쓰기 이전의 읽기는 "쓰기 사이" 구간이 아니므로 샘플에서 제외한다.
+        if previous_writes > 0 {
+            self.window_reads.fetch_add(reads, Ordering::Relaxed);
+            self.windows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 쓰기 가드가 읽기 가드로 바뀜. 획득 횟수는 그대로이고 reader 만 하나 는다.
+    pub(crate) fn record_downgrade(&self) {
+        self.downgrades.fetch_add(1, Ordering::Relaxed);
+        let readers = self.readers.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_readers.fetch_max(readers, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_release(&self, access: AccessKind, held: Duration) {
+        if access == AccessKind::Read {
+            self.readers.fetch_sub(1, Ordering::Relaxed);
+        }
+        let weight = self.weight();
+        if weight == 0 {
+            return;
+        }
+        let nanos = u64::try_from(held.as_nanos()).unwrap_or(u64::MAX);
+        self.add_hold_nanos(nanos.saturating_mul(weight));
+        self.max_hold_nanos.fetch_max(nanos, Ordering::Relaxed);
+        self.releases.fetch_add(weight, Ordering::Relaxed);
+    }
+
+    fn add_hold_nanos(&self, nanos: u64) {
+        let previous = self.hold_nanos.fetch_add(nanos, Ordering::Relaxed);
+        if previous.checked_add(nanos).is_none() {
+            self.hold_wraps.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // 두 카운터를 따로 읽으므로, 넘치는 순간과 겹친 스냅샷은 2^64 ns(약 584년)만큼 어긋날 수 있다.
+    pub(crate) fn hold_nanos(&self) -> u128 {
+        let wraps = self.hold_wraps.load(Ordering::Relaxed);
+        let nanos = self.hold_nanos.load(Ordering::Relaxed);
+        (u128::from(wraps) << 64) | u128::from(nanos)
+    }
+
+    /// 쓰기 가드가 끝난 시각을 남긴다.
+    pub(crate) fn mark_written(&self) {
+        self.last_write.store(now_nanos(), Ordering::Relaxed);
+    }
+
+    /// 마지막 쓰기 뒤로 지난 시간. 쓰기가 없었으면 `None`.
+    pub(crate) fn since_last_write(&self) -> Option<Duration> {
+        match self.last_write.load(Ordering::Relaxed) {
+            0 => None,
+            at => Some(Duration::from_nanos(now_nanos().saturating_sub(at))),
+        }
+    }
+
+    /// 획득 하나가 기다려야 했는지 경합률 창에 더한다.
+    pub(crate) fn record_contention(&self, contended: bool) {
+        self.rotate_window();
+        self.window_acquired.fetch_add(1, Ordering::Relaxed);
+        if contended {
+            self.window_contended.fetch_add(1, Ordering::Relaxed);
+            if self.first_contended.load(Ordering::Relaxed) == 0 {
+                let _ = self.first_contended.compare_exchange(
+                    0,
+                    now_nanos(),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+            }
+        }
+    }
+
+    // 창이 지났으면 현재 창을 직전 창으로 돌린다. 동시에 돌리는 경우의 오차는 무시한다.
+    fn rotate_window(&self) {
+        let window = CONTENTION_WINDOW.as_nanos() as u64;
+        let now = now_nanos();
+        let start = self.window_start.load(Ordering::Relaxed);
+        if start != 0 && now.saturating_sub(start) < window {
+            return;
+        }
+        if self
+            .window_start
+            .compare_exchange(start, now, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        let acquired = self.window_acquired.swap(0, Ordering::Relaxed);
+        let contended = self.window_contended.swap(0, Ordering::Relaxed);
+        // 두 창 넘게 비어 있었다면 직전 창도 낡았다.
+        let stale = start == 0 || now.saturating_sub(start) >= 2 * window;
+        self.prev_acquired
+            .store(if stale { 0 } else { acquired }, Ordering::Relaxed);
+        self.prev_contended
+            .store(if stale { 0 } else { contended }, Ordering::Relaxed);
+    }
+
+    /// 직전 창과 현재 창의 획득 중 기다려야 했던 비율과 표본 수
+    pub(crate) fn contention(&self) -> (f64, u64) {
+        self.rotate_window();
+        let acquired = self.window_acquired.load(Ordering::Relaxed)
+            + self.prev_acquired.load(Ordering::Relaxed);
+        let contended = self.window_contended.load(Ordering::Relaxed)
+            + self.prev_contended.load(Ordering::Relaxed);
+        match acquired {
+            0 => (0.0, 0),
+            _ => (contended.min(acquired) as f64 / acquired as f64, acquired),
+        }
+    }
+
+    /// 경합률이 `threshold` 를 막 넘었으면 그 값을 돌려준다. 넘은 상태가 이어지는 동안은 한 번만.
+    pub(crate) fn contention_crossed(&self, threshold: f64) -> Option<f64> {
+        let (ratio, samples) = self.contention();
+        if samples < CONTENTION_MIN_SAMPLES {
+            return None;
+        }
+        let above = ratio > threshold;
+        let was_above = self.contention_alarm.swap(above, Ordering::Relaxed);
+        (above && !was_above).then_some(ratio)
+    }
+
+    /// 대기자 수를 올리고, 반환된 가드가 드롭될 때(획득 또는 취소) 내린다.
+    pub(crate) fn start_wait(&self) -> WaitGuard<'_> {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
+        WaitGuard(self)
+    }
+
+    /// 읽기 허가를 쥐었음을 표시하고, 반환된 가드가 드롭될 때 해제한다.
+    pub(crate) fn hold_read_permit(self: &Arc<Self>, permit: OwnedSemaphorePermit) -> ReadPermit {
+        self.read_permits.fetch_add(1, Ordering::Relaxed);
+        ReadPermit {
+            stats: self.clone(),
+            _permit: permit,
+        }
+    }
+
+    /// writer 대기를 표시하고, 반환된 가드가 드롭될 때 해제한다.
+    pub(crate) fn start_write_wait(&self) -> WriterWaitGuard<'_> {
+        if self.waiting_writers.fetch_add(1, Ordering::AcqRel) == 0 {
+            self.writer_wait_start.store(now_nanos(), Ordering::Release);
+        }
+        WriterWaitGuard(self)
+    }
+
+    /// `write_prio` 로 기다리기 시작한 writer 의 우선순위를 남긴다.
+    ///
+    /// 기다리는 writer 가 모두 빠질 때까지 가장 높은 값이 유지되므로 근사치이다.
+    pub(crate) fn note_waiting_priority(&self, priority: u8) {
+        self.waiting_priority
+            .fetch_max(u16::from(priority) + 1, Ordering::AcqRel);
+    }
+
+    /// 기다리는 writer 의 가장 높은 우선순위
+    pub(crate) fn waiting_priority(&self) -> Option<u8> {
+        match self.waiting_priority.load(Ordering::Acquire) {
+            0 => None,
+            priority => u8::try_from(priority - 1).ok(),
+        }
+    }
+
+    /// writer 가 기다리고 있다면 지금까지 기다린 시간
+    pub(crate) fn writer_waited(&self) -> Option<Duration> {
+        if self.waiting_writers.load(Ordering::Acquire) == 0 {
+            return None;
+        }
+        match self.writer_wait_start.load(Ordering::Acquire) {
+            0 => None,
+            start => Some(Duration::from_nanos(now_nanos().saturating_sub(start))),
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> LockStatsSnapshot {
+        let windows = self.windows.load(Ordering::Relaxed);
+        let window_reads = self.window_reads.load(Ordering::Relaxed);
+        let hold_nanos = self.hold_nanos();
+        let releases = self.releases.load(Ordering::Relaxed);
+        LockStatsSnapshot {
+            reads: self.reads.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            avg_reads_per_write: (windows > 0).then(|| window_reads as f64 / windows as f64),
+            total_hold: duration_from_nanos(hold_nanos),
+            avg_hold: (releases > 0)
+                .then(|| duration_from_nanos(hold_nanos / u128::from(releases))),
+            max_hold: Duration::from_nanos(self.max_hold_nanos.load(Ordering::Relaxed)),
+            waiters: self.waiters.load(Ordering::Relaxed),
+            readers: self.readers.load(Ordering::Relaxed),
+            peak_readers: self.peak_readers.load(Ordering::Relaxed),
+            read_permits_in_use: self.read_permits.load(Ordering::Relaxed),
+            downgrades: self.downgrades.load(Ordering::Relaxed),
+            sample_every: match self.sample_every.load(Ordering::Relaxed) {
+                0 => None,
+                every => Some(every),
+            },
+            first_contended: instant_from_nanos(self.first_contended.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+// 스레드마다 따로 도는 xorshift64*. 통계 샘플링에만 쓰므로 품질보다 경합이 없는 것이 중요하다.
+fn next_random() -> u64 {
+    thread_local! {
+        static STATE: Cell<u64> = Cell::new(seed());
+    }
+    STATE.with(|state| {
+        let mut x = state.get();
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        state.set(x);
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    })
+}
+
+// splitmix64 로 스레드마다 다른 0 이 아닌 시드를 만든다.
+fn seed() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let mut z = NEXT
+        .fetch_add(0x9E37_79B9_7F4A_7C15, Ordering::Relaxed)
+        .wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (z ^ (z >> 31)) | 1
+}
+
+// `Duration` 이 담을 수 있는 범위(약 5840억 년)를 넘으면 `Duration::MAX` 로 포화한다.
+fn duration_from_nanos(nanos: u128) -> Duration {
+    const NANOS_PER_SEC: u128 = 1_000_000_000;
+    match u64::try_from(nanos / NANOS_PER_SEC) {
+        Ok(secs) => Duration::new(secs, (nanos % NANOS_PER_SEC) as u32),
+        Err(_) => Duration::MAX,
+    }
+}
+
+// 0 을 "없음" 으로 쓰기 위해 1 부터 센다.
+fn now_nanos() -> u64 {
+    let elapsed = base().elapsed();
+    u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX - 1) + 1
+}
+
+fn base() -> Instant {
+    static BASE: OnceLock<Instant> = OnceLock::new();
+    *BASE.get_or_init(Instant::now)
+}
+
+// `now_nanos` 값을 다시 `Instant` 로. 0 은 `None`.
+fn instant_from_nanos(nanos: u64) -> Option<Instant> {
+    nanos
+        .checked_sub(1)
+        .map(|elapsed| base() + Duration::from_nanos(elapsed))
+}
+
+pub(crate) struct WaitGuard<'a>(&'a LockStats);
+
+impl Drop for WaitGuard<'_> {
+    fn drop(&mut self) {
+        self.0.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// 가드가 쥐고 있는 `with_max_readers` 허가. owned 가드도 쥘 수 있도록 빌리지 않는다.
+pub(crate) struct ReadPermit {
+    stats: Arc<LockStats>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Drop for ReadPermit {
+    fn drop(&mut self) {
+        self.stats.read_permits.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) struct WriterWaitGuard<'a>(&'a LockStats);
+
+impl Drop for WriterWaitGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.waiting_writers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.writer_wait_start.store(0, Ordering::Release);
+            self.0.waiting_priority.store(0, Ordering::Release);
+        }
+    }
+}
+
+/// `TokioRwLockTrace::stats` 가 반환하는 통계 스냅샷.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LockStatsSnapshot {
+    /// 읽기 락 획득 횟수
+    pub reads: u64,
+    /// 쓰기 락 획득 횟수
+    pub writes: u64,
+    /// 연속된 두 쓰기 사이에 일어난 읽기 횟수의 평균. 쓰기가 두 번 이상 있어야 계산된다.
+    ///
+    /// 값이 작으면 쓰기가 대부분이라는 뜻이므로 `RwLock` 대신 `Mutex` 가 더 단순하고 빠를 수 있다.
+    pub avg_reads_per_write: Option<f64>,
+    /// 해제된 가드들의 누적 점유 시간.
+    ///
+    /// 내부적으로 128비트 나노초로 쌓으므로 넘치거나 패닉하지 않는다. `Duration` 의 한계를
+    /// 넘으면 `Duration::MAX` 에 머문다.
+    pub total_hold: Duration,
+    /// 해제된 가드들의 평균 점유 시간. 해제가 한 번도 없으면 `None`.
+    pub avg_hold: Option<Duration>,
+    /// 가장 길었던 점유 시간
+    pub max_hold: Duration,
+    /// 현재 획득을 기다리는 태스크 수
+    pub waiters: u64,
+    /// 현재 살아 있는 읽기 가드 수
+    pub readers: u64,
+    /// 동시에 살아 있던 읽기 가드의 최대 수
+    pub peak_readers: u64,
+    /// `with_max_readers` 로 제한한 락에서 사용 중인 읽기 허가 수. 제한이 없으면 0.
+    pub read_permits_in_use: u64,
+    /// 쓰기 가드를 `downgrade` 로 읽기 가드로 바꾼 횟수
+    pub downgrades: u64,
+    /// `LockConfig::sample_stats` 로 샘플링 중이면 그 비율. `Some` 이면 획득 수와 점유 시간,
+    /// 평균은 표본을 늘려 잡은 추정치다.
+    pub sample_every: Option<u32>,
+    /// 처음으로 기다려야 했던 획득이 락을 얻은 시각. 한 번도 경합이 없었으면 `None`.
+    ///
+    /// 부하가 걸릴 때만 경합하는 락이라면 이 시각을 배포나 트래픽 변화와 맞춰 볼 수 있다.
+    pub first_contended: Option<Instant>,
+}
+
+impl LockStatsSnapshot {
+    /// `earlier` 이후 이 스냅샷까지 늘어난 양. 주기적으로 샘플링해서 구간별 비율을 낼 때 쓴다.
+    ///
+    /// 카운터가 한 바퀴 돌았다면 wrapping 뺄셈으로 구간 값을 그대로 얻는다.
+    /// 점유 시간은 거꾸로 갈 수 없으므로 순서가 바뀐 스냅샷이면 0 이 된다.
+    ///
+    /// ```
+    /// use tracing_lock::TokioRwLockTrace;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let lock = TokioRwLockTrace::new(0);
+    /// let before = lock.stats();
+    /// *lock.write().await += 1;
+    /// let delta = lock.stats().since(&before);
+    /// assert_eq!((delta.reads, delta.writes), (0, 1));
+    /// # }
+    /// ```
+    pub fn since(&self, earlier: &LockStatsSnapshot) -> LockStatsDelta {
+        LockStatsDelta {
+            reads: self.reads.wrapping_sub(earlier.reads),
+            writes: self.writes.wrapping_sub(earlier.writes),
+            hold: self.total_hold.saturating_sub(earlier.total_hold),
+            downgrades: self.downgrades.wrapping_sub(earlier.downgrades),
+            new_peak_readers: (self.peak_readers > earlier.peak_readers)
+                .then_some(self.peak_readers),
+        }
+    }
+}
+
+/// 두 스냅샷 사이의 차이. [`LockStatsSnapshot::since`] 가 반환한다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockStatsDelta {
+    /// 구간 동안의 읽기 락 획득 횟수
+    pub reads: u64,
+    /// 구간 동안의 쓰기 락 획득 횟수
+    pub writes: u64,
+    /// 구간 동안 해제된 가드들의 점유 시간 합
+    pub hold: Duration,
+    /// 구간 동안의 `downgrade` 횟수
+    pub downgrades: u64,
+    /// 구간 동안 동시 읽기 가드 최대 수가 갱신됐다면 새 최대값
+    pub new_peak_readers: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn avg_reads_per_write_window() {
+        let stats = LockStats::default();
+        // 첫 쓰기 이전의 읽기는 제외: R W | R R R | W | R | W
+        stats.record_read();
+        stats.record_write();
+        assert_eq!(stats.snapshot().avg_reads_per_write, None);
+
+        for _ in 0..3 {
+            stats.record_read();
+        }
+        stats.record_write();
+        stats.record_read();
+        stats.record_write();
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.reads, 5);
+        assert_eq!(snapshot.writes, 3);
+        assert_eq!(snapshot.avg_reads_per_write, Some(2.0));
+    }
+
+    #[test]
+    fn tracks_current_and_peak_readers() {
+        let stats = LockStats::default();
+        stats.record_read();
+        stats.record_read();
+        stats.record_release(AccessKind::Read, Duration::ZERO);
+        stats.record_read();
+        stats.record_write();
+        stats.record_release(AccessKind::Write, Duration::ZERO);
+        let snapshot = stats.snapshot();
+        assert_eq!((snapshot.readers, snapshot.peak_readers), (2, 2));
+    }
+
+    #[test]
+    fn since_reports_interval_deltas() {
+        let stats = LockStats::default();
+        stats.record_read();
+        stats.record_release(AccessKind::Read, Duration::from_millis(5));
+        let before = stats.snapshot();
+
+        stats.record_read();
+        stats.record_read();
+        stats.record_write();
+        stats.record_release(AccessKind::Read, Duration::from_millis(1));
+        stats.record_release(AccessKind::Read, Duration::from_millis(2));
+        stats.record_release(AccessKind::Write, Duration::from_millis(3));
+        let after = stats.snapshot();
+
+        assert_eq!(
+            after.since(&before),
+            LockStatsDelta {
+                reads: 2,
+                writes: 1,
+                hold: Duration::from_millis(6),
+                downgrades: 0,
+                new_peak_readers: Some(2),
+            }
+        );
+        // 변화가 없으면 최대값도 갱신되지 않는다.
+        assert_eq!(after.since(&after).new_peak_readers, None);
+    }
+
+    #[test]
+    fn since_survives_wraparound_and_reordering() {
+        let mut earlier = LockStats::default().snapshot();
+        earlier.reads = u64::MAX - 1;
+        earlier.total_hold = Duration::from_secs(1);
+        let mut later = earlier.clone();
+        later.reads = 3;
+        later.total_hold = Duration::ZERO;
+
+        let delta = later.since(&earlier);
+        assert_eq!(delta.reads, 5);
+        assert_eq!(delta.hold, Duration::ZERO);
+    }
+
+    #[test]
+    fn sampled_counts_stay_near_true_count() {
+        let stats = LockStats::default();
+        stats.set_sample_every(Some(10));
+        for _ in 0..20_000 {
+            stats.record_read();
+            stats.record_release(AccessKind::Read, Duration::from_micros(1));
+        }
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.sample_every, Some(10));
+        // 1/10 로 뽑은 20000 번의 표준편차는 약 424 이므로 10% 면 넉넉하다.
+        assert!(
+            (18_000..=22_000).contains(&snapshot.reads),
+            "{}",
+            snapshot.reads
+        );
+        let hold = snapshot.total_hold.as_micros();
+        assert!((18_000..=22_000).contains(&hold), "{hold}");
+        assert_eq!(snapshot.avg_hold, Some(Duration::from_micros(1)));
+        // 게이지는 샘플링과 상관없이 정확하다.
+        assert_eq!((snapshot.readers, snapshot.peak_readers), (0, 1));
+    }
+
+    #[test]
+    fn hold_time_carries_past_u64_nanos() {
+        let stats = LockStats::default();
+        stats.hold_nanos.store(u64::MAX - 10, Ordering::Relaxed);
+        stats.record_release(AccessKind::Write, Duration::from_millis(1));
+        stats.record_release(AccessKind::Write, Duration::from_millis(1));
+
+        let expected = u128::from(u64::MAX) - 10 + 2_000_000;
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.total_hold.as_nanos(), expected);
+        assert_eq!(snapshot.avg_hold.unwrap().as_nanos(), expected / 2);
+
+        // `Duration` 범위를 넘으면 포화한다.
+        stats.hold_wraps.store(u64::MAX, Ordering::Relaxed);
+        assert_eq!(stats.snapshot().total_hold, Duration::MAX);
+    }
+
+    #[test]
+    fn waiters_drop_back_on_cancel() {
+        let stats = LockStats::default();
+        let first = stats.start_wait();
+        let second = stats.start_wait();
+        assert_eq!(stats.snapshot().waiters, 2);
+        drop(first);
+        drop(second);
+        assert_eq!(stats.snapshot().waiters, 0);
+    }
+}
@@ -0,0 +1,279 @@
+//! 계측된 `std::sync::Mutex`/`RwLock`. (`std-sync` 기능)
+//!
+//! 스레드를 막는 락이므로 async 코드에서 `.await` 를 건너 쥐면 안 된다. 대기 시간은 곧바로 얻지
+//! 못했을 때만 잰다.
+
+// 반환형은 `std` 와 같아야 하므로, 오염된 가드를 담는 `Err` 가 큰 것은 그대로 둔다.
+#![allow(clippy::result_large_err)]
+
+use std::sync::{
+    LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    TryLockError, TryLockResult,
+};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+use crate::probe::{Probe, TracedGuard, Waiting};
+use crate::stats::LockStatsSnapshot;
+
+/// `TracedStdMutex::lock` 이 돌려주는 가드
+pub type TracedStdMutexGuard<'a, T> = TracedGuard<'a, MutexGuard<'a, T>>;
+/// `TracedStdRwLock::read` 가 돌려주는 가드
+pub type TracedStdReadGuard<'a, T> = TracedGuard<'a, RwLockReadGuard<'a, T>>;
+/// `TracedStdRwLock::write` 가 돌려주는 가드
+pub type TracedStdWriteGuard<'a, T> = TracedGuard<'a, RwLockWriteGuard<'a, T>>;
+
+// 먼저 기다리지 않고 시도해서 경합 여부를 가린다. 오염된 락도 가드를 감싸서 돌려준다.
+fn acquire<'a, G>(
+    probe: &'a Probe,
+    waiting: Waiting<'a>,
+    access: AccessKind,
+    attempt: TryLockResult<G>,
+    blocking: impl FnOnce() -> LockResult<G>,
+) -> LockResult<TracedGuard<'a, G>> {
+    let (result, contended) = match attempt {
+        Ok(guard) => (Ok(guard), false),
+        Err(TryLockError::Poisoned(poisoned)) => (Err(poisoned), false),
+        Err(TryLockError::WouldBlock) => {
+            let _blocked = probe.blocked(&waiting, access);
+            (blocking(), true)
+        }
+    };
+    match result {
+        Ok(guard) => Ok(probe.acquired(waiting, guard, access, contended)),
+        Err(poisoned) => Err(PoisonError::new(probe.acquired(
+            waiting,
+            poisoned.into_inner(),
+            access,
+            contended,
+        ))),
+    }
+}
+
+#[track_caller]
+fn try_acquire<'a, G>(
+    probe: &'a Probe,
+    access: AccessKind,
+    attempt: TryLockResult<G>,
+) -> TryLockResult<TracedGuard<'a, G>> {
+    match attempt {
+        Ok(guard) => Ok(probe.acquired(probe.wait(access), guard, access, false)),
+        Err(TryLockError::Poisoned(poisoned)) => Err(TryLockError::Poisoned(PoisonError::new(
+            probe.acquired(probe.wait(access), poisoned.into_inner(), access, false),
+        ))),
+        Err(TryLockError::WouldBlock) => {
+            probe.busy(access);
+            Err(TryLockError::WouldBlock)
+        }
+    }
+}
+
+/// 획득/해제 시점과 점유 시간을 기록하는 `std::sync::Mutex`.
+///
+/// ```
+/// use tracing_lock::TracedStdMutex;
+///
+/// let hits = TracedStdMutex::new(0);
+/// *hits.lock().unwrap() += 1;
+/// assert_eq!(hits.stats().writes, 1);
+/// ```
+pub struct TracedStdMutex<T> {
+    probe: Probe,
+    inner: Mutex<T>,
+}
+
+impl<T> TracedStdMutex<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        TracedStdMutex {
+            probe: Probe::new(),
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    #[track_caller]
+    pub fn lock(&self) -> LockResult<TracedStdMutexGuard<'_, T>> {
+        let waiting = self.probe.wait(AccessKind::Write);
+        acquire(
+            &self.probe,
+            waiting,
+            AccessKind::Write,
+            self.inner.try_lock(),
+            || self.inner.lock(),
+        )
+    }
+
+    /// 기다리지 않고 시도한다. 이미 잡혀 있으면 `Busy` 이벤트를 남기고 실패한다.
+    #[track_caller]
+    pub fn try_lock(&self) -> TryLockResult<TracedStdMutexGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Write, self.inner.try_lock())
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+/// 획득/해제 시점과 점유 시간을 기록하는 `std::sync::RwLock`.
+///
+/// ```
+/// use tracing_lock::TracedStdRwLock;
+///
+/// let config = TracedStdRwLock::new("v1");
+/// *config.write().unwrap() = "v2";
+/// assert_eq!(*config.read().unwrap(), "v2");
+/// ```
+pub struct TracedStdRwLock<T> {
+    probe: Probe,
+    inner: RwLock<T>,
+}
+
+impl<T> TracedStdRwLock<T> {
+    #[track_caller]
+    pub fn new(value: T) -> Self {
+        TracedStdRwLock {
+            probe: Probe::new(),
+            inner: RwLock::new(value),
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        self.probe.configure(config);
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.probe.name()
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.probe.stats()
+    }
+
+    #[track_caller]
+    pub fn read(&self) -> LockResult<TracedStdReadGuard<'_, T>> {
+        let waiting = self.probe.wait(AccessKind::Read);
+        acquire(
+            &self.probe,
+            waiting,
+            AccessKind::Read,
+            self.inner.try_read(),
+            || self.inner.read(),
+        )
+    }
+
+    #[track_caller]
+    pub fn write(&self) -> LockResult<TracedStdWriteGuard<'_, T>> {
+        let waiting = self.probe.wait(AccessKind::Write);
+        acquire(
+            &self.probe,
+            waiting,
+            AccessKind::Write,
+            self.inner.try_write(),
+            || self.inner.write(),
+        )
+    }
+
+    #[track_caller]
+    pub fn try_read(&self) -> TryLockResult<TracedStdReadGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Read, self.inner.try_read())
+    }
+
+    #[track_caller]
+    pub fn try_write(&self) -> TryLockResult<TracedStdWriteGuard<'_, T>> {
+        try_acquire(&self.probe, AccessKind::Write, self.inner.try_write())
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        self.inner.get_mut()
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::EventKind;
+
+    #[test]
+    fn blocked_thread_is_counted_as_contended() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TracedStdMutex::new(0).with_config(LockConfig::new().silent().observer(events.clone())),
+        );
+        let held = lock.lock().unwrap();
+        assert!(matches!(lock.try_lock(), Err(TryLockError::WouldBlock)));
+        let waiter = {
+            let lock = lock.clone();
+            std::thread::spawn(move || *lock.lock().unwrap() += 1)
+        };
+        while lock.stats().waiters < 1 {
+            std::thread::yield_now();
+        }
+        std::thread::sleep(Duration::from_millis(5));
+        drop(held);
+        waiter.join().unwrap();
+
+        let stats = lock.stats();
+        assert_eq!(stats.writes, 2);
+        assert!(stats.first_contended.is_some());
+        let events = events.events();
+        let waited: Vec<_> = events
+            .iter()
+            .filter(|e| e.kind == EventKind::Acquire)
+            .map(|e| e.waited.unwrap())
+            .collect();
+        assert_eq!(waited[0], Duration::ZERO);
+        assert!(waited[1] >= Duration::from_millis(5), "{waited:?}");
+    }
+
+    #[test]
+    fn poisoned_lock_still_hands_out_traced_guards() {
+        let lock = Arc::new(TracedStdRwLock::new(0).with_config(LockConfig::new().silent()));
+        let poisoner = lock.clone();
+        std::thread::spawn(move || {
+            let _guard = poisoner.write().unwrap();
+            panic!("poison");
+        })
+        .join()
+        .unwrap_err();
+
+        let Err(poisoned) = lock.read() else {
+            panic!("lock should be poisoned");
+        };
+        let guard = poisoned.into_inner();
+        assert_eq!(*guard, 0);
+        drop(guard);
+        assert!(lock.try_write().is_err());
+        let stats = lock.stats();
+        assert_eq!((stats.reads, stats.writes, stats.readers), (1, 2, 0));
+    }
+}
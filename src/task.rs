@@ -0,0 +1,203 @@
+//! 태스크별로 보유 중인 락 스택.
+//!
+//! tokio 태스크 안에서는 태스크 id 로, 밖에서는 스레드 id 로 구분한다.
+
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
+    thread::{Thread, ThreadId},
+};
+
+type ThreadNameFallback = Arc<dyn Fn(&Thread) -> String + Send + Sync>;
+
+static NESTING: AtomicBool = AtomicBool::new(false);
+static HELD: Mutex<Option<HashMap<TaskKey, Vec<u64>>>> = Mutex::new(None);
+static THREAD_NAME_FALLBACK: RwLock<Option<ThreadNameFallback>> = RwLock::new(None);
+
+/// 해제 이벤트에 태스크가 아직 보유한 가드 수(`depth`)를 기록할지 켜고 끈다. 기본값은 꺼짐.
+///
+/// 켜져 있는 동안 획득한 가드만 센다.
+pub fn set_nesting_depth(enabled: bool) {
+    NESTING.store(enabled, Ordering::Relaxed);
+}
+
+/// 이름 없는 스레드(tokio 워커 등)에서 이벤트의 `thread` 로 쓸 값을 정한다.
+///
+/// 설정하지 않으면 `ThreadId(7)` 처럼 스레드 id 를 쓴다.
+///
+/// ```
+/// tracing_lock::set_thread_name_fallback(|thread| {
+///     let task = tokio::task::try_id().map(|id| format!(" task {id}"));
+///     format!("{:?}{}", thread.id(), task.unwrap_or_default())
+/// });
+/// ```
+pub fn set_thread_name_fallback(resolver: impl Fn(&Thread) -> String + Send + Sync + 'static) {
+    *THREAD_NAME_FALLBACK
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(resolver));
+}
+
+tokio::task_local! {
+    static OPERATION_ID: Arc<str>;
+}
+
+/// `fut` 가 도는 동안 만들어지는 모든 락 이벤트의 `operation_id` 에 `id` 를 붙인다.
+///
+/// 업무 단위 하나(요청, 작업 등)의 락 활동을 호출마다 컨텍스트를 넘기지 않고 묶어 볼 때 쓴다.
+/// `tokio::task_local!` 이므로 `fut` 안에서 `tokio::spawn` 한 태스크에는 이어지지 않는다.
+/// 중첩하면 안쪽 id 가 이긴다.
+///
+/// ```
+/// use tracing_lock::{EventKind, LockConfig, LockEvent, TokioRwLockTrace};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().observer(|e: &LockEvent| {
+///     if e.kind == EventKind::Acquire {
+///         assert_eq!(e.operation_id.as_deref(), Some("order-17"));
+///     }
+/// }));
+/// tracing_lock::with_operation_id("order-17", async {
+///     *lock.write().await += 1;
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn with_operation_id<F: Future>(id: impl fmt::Display, fut: F) -> F::Output {
+    OPERATION_ID.scope(Arc::from(id.to_string()), fut).await
+}
+
+/// 현재 태스크의 `with_operation_id` id
+pub(crate) fn operation_id() -> Option<String> {
+    OPERATION_ID.try_with(|id| id.to_string()).ok()
+}
+
+/// 현재 스레드의 이름. 이름이 없으면 등록된 대체값을 쓴다.
+pub(crate) fn thread_name() -> String {
+    let thread = std::thread::current();
+    if let Some(name) = thread.name() {
+        return name.to_string();
+    }
+    let fallback = THREAD_NAME_FALLBACK
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    match fallback {
+        Some(resolve) => resolve(&thread),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+thread_local! {
+    static FRAMES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// 이 스레드에서 살아 있는 `StackFrame` 수를 하나 늘린다. (`LockConfig::record_stack_depth`)
+///
+/// 재귀하거나 깊이 파고드는 함수 첫머리에서 불러 두면, 그 안에서 잡은 락의 획득 이벤트
+/// `stack_depth` 에 표시한 호출 깊이가 남는다. 백트레이스를 뜨지 않으므로 표시하지 않은 함수는
+/// 세지 않는다.
+///
+/// ```
+/// use tracing_lock::TokioRwLockTrace;
+///
+/// fn walk(lock: &TokioRwLockTrace<u32>, depth: u32) {
+///     let _frame = tracing_lock::enter_frame();
+///     if depth > 0 {
+///         walk(lock, depth - 1);
+///     } else {
+///         *lock.try_write().unwrap() += 1;
+///     }
+/// }
+/// ```
+pub fn enter_frame() -> StackFrame {
+    FRAMES.with(|frames| frames.set(frames.get() + 1));
+    StackFrame {
+        _thread: PhantomData,
+    }
+}
+
+/// `enter_frame` 이 돌려주는 표시. 드롭하면 깊이를 되돌린다.
+///
+/// 스레드에 묶인 값이라 `Send` 가 아니다. 그래서 `.await` 너머로 들고 갈 수 없고, 태스크가
+/// 다른 워커로 옮겨 가도 깊이가 섞이지 않는다.
+#[must_use = "the frame is counted only while this value is alive"]
+#[derive(Debug)]
+pub struct StackFrame {
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for StackFrame {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| frames.set(frames.get() - 1));
+    }
+}
+
+/// 현재 스레드에서 `enter_frame` 으로 표시한 호출 깊이
+pub(crate) fn stack_depth() -> usize {
+    FRAMES.with(Cell::get)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum TaskKey {
+    Task(tokio::task::Id),
+    Thread(ThreadId),
+}
+
+impl TaskKey {
+    pub(crate) fn current() -> Self {
+        match tokio::task::try_id() {
+            Some(id) => TaskKey::Task(id),
+            None => TaskKey::Thread(std::thread::current().id()),
+        }
+    }
+}
+
+/// 스택에 올라간 가드 하나
+#[derive(Debug)]
+pub(crate) struct Held {
+    key: TaskKey,
+    lock_id: u64,
+}
+
+/// 현재 태스크의 스택에 `lock_id` 를 올리고, 올린 뒤의 깊이를 돌려준다.
+pub(crate) fn push(lock_id: u64) -> Option<(Held, usize)> {
+    if !NESTING.load(Ordering::Relaxed) {
+        return None;
+    }
+    let key = TaskKey::current();
+    let mut held = HELD.lock().unwrap_or_else(PoisonError::into_inner);
+    let stack = held
+        .get_or_insert_with(HashMap::new)
+        .entry(key)
+        .or_default();
+    stack.push(lock_id);
+    Some((Held { key, lock_id }, stack.len()))
+}
+
+/// 스택에서 가드를 내리고, 태스크가 아직 보유한 가드 수를 돌려준다.
+pub(crate) fn pop(entry: &Held) -> usize {
+    let mut held = HELD.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(map) = held.as_mut() else {
+        return 0;
+    };
+    let Some(stack) = map.get_mut(&entry.key) else {
+        return 0;
+    };
+    // 스코프 순서가 아니게 드롭될 수도 있으므로 가장 최근의 같은 락을 찾는다.
+    if let Some(index) = stack.iter().rposition(|id| *id == entry.lock_id) {
+        stack.remove(index);
+    }
+    let depth = stack.len();
+    if depth == 0 {
+        map.remove(&entry.key);
+    }
+    depth
+}
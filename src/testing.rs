@@ -0,0 +1,183 @@
+//! 내보낸 이벤트를 모아서 단언하는 테스트 도구. (`test-util` 기능)
+//!
+//! ```
+//! use std::time::Duration;
+//! use tracing_lock::testing::CapturingObserver;
+//! use tracing_lock::{AccessKind, LockConfig, TokioRwLockTrace};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let capture = CapturingObserver::new();
+//! let lock = TokioRwLockTrace::new(0)
+//!     .with_config(LockConfig::new().name("jobs").silent().observer(capture.clone()));
+//! *lock.write().await += 1;
+//!
+//! capture.assert_acquired("jobs", AccessKind::Write);
+//! capture.assert_hold_at_least(Duration::ZERO);
+//! # }
+//! ```
+
+use std::{
+    fmt::Write,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use crate::event::{AccessKind, EventKind, LockEvent, LockObserver};
+
+/// 받은 이벤트를 모두 공유 `Vec` 에 쌓는 관찰자.
+///
+/// 복제본끼리 같은 기록을 공유하므로 하나는 `LockConfig::observer` 에 넘기고 하나로 단언한다.
+#[derive(Debug, Clone, Default)]
+pub struct CapturingObserver {
+    events: Arc<Mutex<Vec<LockEvent>>>,
+}
+
+impl CapturingObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 지금까지 받은 이벤트의 복사본 (받은 순서)
+    pub fn events(&self) -> Vec<LockEvent> {
+        self.lock().clone()
+    }
+
+    /// 기록을 비운다.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// 지금까지 받은 이벤트를 꺼내고 기록을 비운다.
+    pub fn take(&self) -> Vec<LockEvent> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    /// `name` 락을 `access` 로 얻은 획득 이벤트가 있어야 한다.
+    #[track_caller]
+    pub fn assert_acquired(&self, name: &str, access: AccessKind) {
+        // 패닉하기 전에 뮤텍스를 놓는다.
+        let events = self.events();
+        let found = events.iter().any(|event| {
+            event.kind == EventKind::Acquire
+                && event.access == access
+                && event.name.as_deref() == Some(name)
+        });
+        assert!(
+            found,
+            "expected {access:?} lock '{name}' to be acquired; captured events:{}",
+            render(&events)
+        );
+    }
+
+    /// 점유 시간이 `min` 이상인 해제 이벤트가 있어야 한다.
+    #[track_caller]
+    pub fn assert_hold_at_least(&self, min: Duration) {
+        let events = self.events();
+        let longest = events
+            .iter()
+            .filter(|event| event.kind == EventKind::Release)
+            .filter_map(|event| event.held)
+            .max();
+        assert!(
+            longest.is_some_and(|held| held >= min),
+            "expected a hold of at least {min:?}, longest was {longest:?}; captured events:{}",
+            render(&events)
+        );
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<LockEvent>> {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl LockObserver for CapturingObserver {
+    fn on_event(&self, event: &LockEvent) {
+        self.lock().push(event.clone());
+    }
+}
+
+fn render(events: &[LockEvent]) -> String {
+    if events.is_empty() {
+        return " (none)".to_string();
+    }
+    let mut out = String::new();
+    for event in events {
+        let _ = write!(out, "\n  {event}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LockConfig, TokioRwLockTrace};
+
+    fn capture_lock(name: &str) -> (CapturingObserver, TokioRwLockTrace<i32>) {
+        let capture = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .name(name)
+                .silent()
+                .observer(capture.clone()),
+        );
+        (capture, lock)
+    }
+
+    #[tokio::test]
+    async fn captures_events_in_order() {
+        let (capture, lock) = capture_lock("capture");
+        drop(lock.read().await);
+
+        let kinds: Vec<_> = capture.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [EventKind::Created, EventKind::Acquire, EventKind::Release]
+        );
+        capture.clear();
+        assert!(capture.events().is_empty());
+    }
+
+    #[tokio::test]
+    async fn assert_acquired_matches_name_and_access() {
+        let (capture, lock) = capture_lock("matched");
+        drop(lock.read().await);
+
+        capture.assert_acquired("matched", AccessKind::Read);
+        let wrong_access = std::panic::catch_unwind(|| {
+            capture.assert_acquired("matched", AccessKind::Write);
+        });
+        assert!(wrong_access.is_err());
+        let wrong_name = std::panic::catch_unwind(|| {
+            capture.assert_acquired("other", AccessKind::Read);
+        });
+        assert!(wrong_name.is_err());
+    }
+
+    #[tokio::test]
+    async fn assert_hold_at_least_uses_longest_release() {
+        let (capture, lock) = capture_lock("held");
+        {
+            let _guard = lock.write().await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        drop(lock.read().await);
+
+        capture.assert_hold_at_least(Duration::from_millis(20));
+        let too_long = std::panic::catch_unwind(|| {
+            capture.assert_hold_at_least(Duration::from_secs(60));
+        });
+        let message = too_long.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.contains("Write lock 'held' released"), "{message}");
+    }
+
+    #[test]
+    fn empty_capture_fails_hold_assertion() {
+        let capture = CapturingObserver::new();
+        let result = std::panic::catch_unwind(|| capture.assert_hold_at_least(Duration::ZERO));
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(message.ends_with("(none)"), "{message}");
+    }
+}
@@ -0,0 +1,565 @@
+//! 획득 대기, 획득, 해제를 하나의 시간축에 모아 두었다가 내보내는 기록. (`start`, `stop`)
+//!
+//! 기록하는 동안 모든 계측된 락의 획득마다 기다리기 시작함, 얻음, 놓음(또는 기다리다 취소됨)을 그때의
+//! 태스크/스레드와 호출 위치와 함께 메모리에 쌓는다. `stop` 으로 받은 `Timeline` 은 Chrome 추적 형식
+//! (`chrome://tracing`, Perfetto 에서 열린다)이나 한 줄에 하나인 JSON 으로 쓸 수 있다. 부하 테스트
+//! 뒤에 reader 가 어느 writer 뒤에 줄을 섰는지 눈으로 보는 데 쓴다.
+//!
+//! ```
+//! use tracing_lock::{timeline, TokioRwLockTrace};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let lock = TokioRwLockTrace::new(0);
+//! timeline::start(10_000);
+//! *lock.write().await += 1;
+//! let timeline = timeline::stop();
+//!
+//! let mut trace = Vec::new();
+//! timeline.write_chrome_trace(&mut trace).unwrap();
+//! assert!(String::from_utf8(trace).unwrap().contains("\"cat\":\"hold\""));
+//! # }
+//! ```
+
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    num::NonZeroU64,
+    panic::Location,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::config::LockConfig;
+use crate::event::AccessKind;
+
+static RECORDING: AtomicBool = AtomicBool::new(false);
+static BUFFER: Mutex<Option<Buffer>> = Mutex::new(None);
+// 한 획득의 기록들을 잇는 번호
+static NEXT_ACQUISITION: AtomicU64 = AtomicU64::new(1);
+
+struct Buffer {
+    started: Instant,
+    capacity: usize,
+    records: VecDeque<TimelineRecord>,
+    dropped: u64,
+}
+
+/// 기록 하나가 나타내는 순간
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// 획득을 시작함. 곧바로 얻었다면 `Acquired` 와 같은 시각이다.
+    WaitStart,
+    /// 가드를 얻음
+    Acquired,
+    /// 가드를 놓음. 해제 없이 접근 종류가 바뀌면(`downgrade`) 이전 종류로 놓고 새 종류로 다시
+    /// 얻은 것으로 남는다.
+    Released,
+    /// 기다리던 future 가 얻기 전에 드롭됨
+    Cancelled,
+}
+
+/// 시간축의 기록 하나
+#[derive(Debug, Clone)]
+pub struct TimelineRecord {
+    pub phase: Phase,
+    /// 같은 획득의 기록은 같은 번호를 가진다.
+    pub acquisition: u64,
+    pub lock_id: u64,
+    pub lock: Option<String>,
+    pub access: AccessKind,
+    /// 기록한 tokio 태스크. 런타임 밖이면 없다.
+    pub task: Option<tokio::task::Id>,
+    pub thread: String,
+    /// 획득을 부른 위치. 해제 기록도 가드를 얻은 위치를 가리킨다.
+    pub location: &'static Location<'static>,
+    /// `start` 부터 지난 시간
+    pub at: Duration,
+}
+
+/// `stop` 이 돌려주는 기록 전체
+#[derive(Debug, Clone, Default)]
+pub struct Timeline {
+    /// 시각 순이다.
+    pub records: Vec<TimelineRecord>,
+    /// 버퍼가 차서 버린 오래된 기록 수
+    pub dropped: u64,
+    /// `start` 부터 `stop` 까지의 시간
+    pub duration: Duration,
+}
+
+/// 버퍼를 비우고 기록을 시작한다. 가장 최근 `capacity` 개의 기록을 남긴다.
+///
+/// 기록 중에는 획득과 해제마다 전역 뮤텍스 하나를 거친다.
+pub fn start(capacity: usize) {
+    *BUFFER.lock().unwrap_or_else(PoisonError::into_inner) = Some(Buffer {
+        started: Instant::now(),
+        capacity,
+        records: VecDeque::with_capacity(capacity.min(4096)),
+        dropped: 0,
+    });
+    RECORDING.store(true, Ordering::Relaxed);
+}
+
+/// 기록을 멈추고 지금까지 쌓인 기록을 돌려준다. 기록 중이 아니었으면 비어 있다.
+pub fn stop() -> Timeline {
+    RECORDING.store(false, Ordering::Relaxed);
+    let Some(buffer) = BUFFER.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+        return Timeline::default();
+    };
+    Timeline {
+        records: buffer.records.into(),
+        dropped: buffer.dropped,
+        duration: buffer.started.elapsed(),
+    }
+}
+
+fn record(
+    phase: Phase,
+    acquisition: NonZeroU64,
+    lock_id: u64,
+    lock: Option<String>,
+    access: AccessKind,
+    location: &'static Location<'static>,
+) {
+    let task = tokio::task::try_id();
+    let thread = crate::task::thread_name();
+    let mut buffer = BUFFER.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+    if buffer.capacity == 0 {
+        buffer.dropped += 1;
+        return;
+    }
+    if buffer.records.len() == buffer.capacity {
+        buffer.records.pop_front();
+        buffer.dropped += 1;
+    }
+    buffer.records.push_back(TimelineRecord {
+        phase,
+        acquisition: acquisition.get(),
+        lock_id,
+        lock,
+        access,
+        task,
+        thread,
+        location,
+        at: buffer.started.elapsed(),
+    });
+}
+
+fn next_acquisition() -> NonZeroU64 {
+    NonZeroU64::new(NEXT_ACQUISITION.fetch_add(1, Ordering::Relaxed))
+        .expect("acquisition counter overflowed")
+}
+
+/// 기다리기 시작한 획득 하나. 얻기 전에 드롭되면 취소로 남는다.
+pub(crate) struct Pending {
+    acquisition: NonZeroU64,
+    lock_id: u64,
+    lock: Option<String>,
+    access: AccessKind,
+    location: &'static Location<'static>,
+    done: bool,
+}
+
+/// 기록 중이면 획득을 시작했다고 남긴다.
+pub(crate) fn begin(
+    config: &LockConfig,
+    lock_id: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+) -> Option<Pending> {
+    if !RECORDING.load(Ordering::Relaxed) {
+        return None;
+    }
+    let acquisition = next_acquisition();
+    record(
+        Phase::WaitStart,
+        acquisition,
+        lock_id,
+        config.name.clone(),
+        access,
+        location,
+    );
+    Some(Pending {
+        acquisition,
+        lock_id,
+        lock: config.name.clone(),
+        access,
+        location,
+        done: false,
+    })
+}
+
+impl Pending {
+    /// 기다리던 획득이 가드를 얻었다고 남긴다. 돌려준 번호는 해제할 때 쓴다.
+    pub(crate) fn acquired(mut self) -> NonZeroU64 {
+        self.done = true;
+        record(
+            Phase::Acquired,
+            self.acquisition,
+            self.lock_id,
+            self.lock.take(),
+            self.access,
+            self.location,
+        );
+        self.acquisition
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if !self.done {
+            record(
+                Phase::Cancelled,
+                self.acquisition,
+                self.lock_id,
+                self.lock.take(),
+                self.access,
+                self.location,
+            );
+        }
+    }
+}
+
+/// 기록 중이면 기다리지 않고 얻은 가드를 남긴다. (`try_read` 등)
+pub(crate) fn acquired(
+    config: &LockConfig,
+    lock_id: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+) -> Option<NonZeroU64> {
+    RECORDING.load(Ordering::Relaxed).then(|| {
+        let acquisition = next_acquisition();
+        record(
+            Phase::Acquired,
+            acquisition,
+            lock_id,
+            config.name.clone(),
+            access,
+            location,
+        );
+        acquisition
+    })
+}
+
+/// `acquisition` 으로 남긴 가드를 놓았다고 남긴다.
+pub(crate) fn released(
+    config: &LockConfig,
+    lock_id: u64,
+    access: AccessKind,
+    location: &'static Location<'static>,
+    acquisition: NonZeroU64,
+) {
+    if RECORDING.load(Ordering::Relaxed) {
+        record(
+            Phase::Released,
+            acquisition,
+            lock_id,
+            config.name.clone(),
+            access,
+            location,
+        );
+    }
+}
+
+impl Phase {
+    fn label(self) -> &'static str {
+        match self {
+            Phase::WaitStart => "wait_start",
+            Phase::Acquired => "acquired",
+            Phase::Released => "released",
+            Phase::Cancelled => "cancelled",
+        }
+    }
+}
+
+fn access_label(access: AccessKind) -> &'static str {
+    match access {
+        AccessKind::Read => "read",
+        AccessKind::Write => "write",
+    }
+}
+
+// JSON 문자열 하나를 따옴표까지 쓴다.
+fn json_string(out: &mut impl io::Write, value: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    out.write_all(b"\"")
+}
+
+fn json_optional(out: &mut impl io::Write, value: Option<&str>) -> io::Result<()> {
+    match value {
+        Some(value) => json_string(out, value),
+        None => out.write_all(b"null"),
+    }
+}
+
+fn micros(at: Duration) -> String {
+    format!("{:.3}", at.as_secs_f64() * 1e6)
+}
+
+fn site(location: &Location<'_>) -> String {
+    format!("{}:{}", location.file(), location.line())
+}
+
+// Chrome 추적의 한 줄(tid). 태스크가 있으면 태스크마다, 없으면 스레드마다 하나다.
+fn lane_name(record: &TimelineRecord) -> String {
+    match record.task {
+        Some(task) => format!("task {}", task),
+        None => record.thread.clone(),
+    }
+}
+
+impl Timeline {
+    /// 기록마다 JSON 객체 한 줄씩 쓴다.
+    ///
+    /// `{"phase":"acquired","acquisition":3,"lock_id":1,"lock":"jobs","access":"write",
+    /// "task":"7","thread":"main","site":"src/jobs.rs:10","at_us":12.500}`
+    pub fn write_ndjson(&self, out: &mut impl io::Write) -> io::Result<()> {
+        for record in &self.records {
+            write!(
+                out,
+                "{{\"phase\":\"{}\",\"acquisition\":{},\"lock_id\":{},\"lock\":",
+                record.phase.label(),
+                record.acquisition,
+                record.lock_id
+            )?;
+            json_optional(out, record.lock.as_deref())?;
+            write!(
+                out,
+                ",\"access\":\"{}\",\"task\":",
+                access_label(record.access)
+            )?;
+            json_optional(out, record.task.map(|task| task.to_string()).as_deref())?;
+            out.write_all(b",\"thread\":")?;
+            json_string(out, &record.thread)?;
+            out.write_all(b",\"site\":")?;
+            json_string(out, &site(record.location))?;
+            writeln!(out, ",\"at_us\":{}}}", micros(record.at))?;
+        }
+        Ok(())
+    }
+
+    /// Chrome 추적 형식(JSON 객체 하나)으로 쓴다.
+    ///
+    /// 획득마다 기다린 구간(`cat: "wait"`)과 쥔 구간(`cat: "hold"`)이 획득한 태스크의 줄에 놓인다.
+    /// 곧바로 얻은 획득은 쥔 구간만 있다. `stop` 때까지 끝나지 않은 구간은 `stop` 에서 끝나고
+    /// `args.finished` 가 `false` 다.
+    pub fn write_chrome_trace(&self, out: &mut impl io::Write) -> io::Result<()> {
+        let mut lanes: HashMap<String, usize> = HashMap::new();
+        let mut lane = |record: &TimelineRecord| {
+            let next = lanes.len() + 1;
+            *lanes.entry(lane_name(record)).or_insert(next)
+        };
+        // 획득 번호마다 시작한 기록과 얻은 기록
+        let mut open: HashMap<u64, (Option<&TimelineRecord>, Option<&TimelineRecord>)> =
+            HashMap::new();
+        let mut spans = Vec::new();
+        for record in &self.records {
+            let slot = open.entry(record.acquisition).or_default();
+            match record.phase {
+                Phase::WaitStart => slot.0 = Some(record),
+                Phase::Acquired => {
+                    if let Some(start) = slot.0.take() {
+                        spans.push(("wait", start, record.at, Some("acquired")));
+                    }
+                    slot.1 = Some(record);
+                }
+                Phase::Cancelled => {
+                    if let Some(start) = slot.0.take() {
+                        spans.push(("wait", start, record.at, Some("cancelled")));
+                    }
+                    open.remove(&record.acquisition);
+                }
+                Phase::Released => {
+                    if let Some(acquired) = slot.1.take() {
+                        spans.push(("hold", acquired, record.at, None));
+                    }
+                    open.remove(&record.acquisition);
+                }
+            }
+        }
+        let mut unfinished: Vec<_> = open
+            .into_values()
+            .flat_map(|(start, acquired)| {
+                let wait = start.map(|start| ("wait", start, self.duration, Some("unfinished")));
+                let hold = acquired.map(|acquired| ("hold", acquired, self.duration, None));
+                wait.into_iter().chain(hold)
+            })
+            .collect();
+        unfinished.sort_by_key(|(_, start, _, _)| start.at);
+        let finished = spans.len();
+        spans.extend(unfinished);
+
+        out.write_all(b"{\"displayTimeUnit\":\"ms\",\"traceEvents\":[")?;
+        let mut first = true;
+        let mut separator = |out: &mut dyn io::Write| -> io::Result<()> {
+            if !std::mem::take(&mut first) {
+                out.write_all(b",")?;
+            }
+            out.write_all(b"\n")
+        };
+        for (index, (category, start, end, outcome)) in spans.into_iter().enumerate() {
+            // 곧바로 얻은 획득의 빈 대기 구간은 남기지 않는다.
+            if category == "wait" && end <= start.at && outcome == Some("acquired") {
+                continue;
+            }
+            separator(out)?;
+            let mut name = match category {
+                "wait" => format!("wait {}", access_label(start.access)),
+                _ => access_label(start.access).to_string(),
+            };
+            match &start.lock {
+                Some(lock) => name.push_str(&format!(" '{}'", lock)),
+                None => name.push_str(&format!(" #{}", start.lock_id)),
+            }
+            out.write_all(b"{\"name\":")?;
+            json_string(out, &name)?;
+            write!(
+                out,
+                ",\"cat\":\"{}\",\"ph\":\"X\",\"ts\":{},\"dur\":{},\"pid\":1,\"tid\":{},\"args\":{{\"lock_id\":{},\"site\":",
+                category,
+                micros(start.at),
+                micros(end.saturating_sub(start.at)),
+                lane(start),
+                start.lock_id
+            )?;
+            json_string(out, &site(start.location))?;
+            write!(out, ",\"finished\":{}", index < finished)?;
+            if let Some(outcome) = outcome {
+                write!(out, ",\"outcome\":\"{}\"", outcome)?;
+            }
+            out.write_all(b"}}")?;
+        }
+        let mut lanes: Vec<_> = lanes.into_iter().collect();
+        lanes.sort_by_key(|(_, tid)| *tid);
+        for (name, tid) in lanes {
+            separator(out)?;
+            write!(
+                out,
+                "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":1,\"tid\":{},\"args\":{{\"name\":",
+                tid
+            )?;
+            json_string(out, &name)?;
+            out.write_all(b"}}")?;
+        }
+        out.write_all(b"\n]}\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        phase: Phase,
+        acquisition: u64,
+        access: AccessKind,
+        thread: &str,
+        at_us: u64,
+    ) -> TimelineRecord {
+        TimelineRecord {
+            phase,
+            acquisition,
+            lock_id: 9,
+            lock: Some("ja\"bs".into()),
+            access,
+            task: None,
+            thread: thread.into(),
+            location: Location::caller(),
+            at: Duration::from_micros(at_us),
+        }
+    }
+
+    fn timeline() -> Timeline {
+        use AccessKind::{Read, Write};
+        Timeline {
+            records: vec![
+                record(Phase::WaitStart, 1, Write, "writer", 0),
+                record(Phase::Acquired, 1, Write, "writer", 0),
+                record(Phase::WaitStart, 2, Read, "reader", 10),
+                record(Phase::Released, 1, Write, "writer", 50),
+                record(Phase::Acquired, 2, Read, "reader", 50),
+                record(Phase::WaitStart, 3, Write, "writer", 60),
+                record(Phase::Cancelled, 3, Write, "writer", 70),
+            ],
+            dropped: 0,
+            duration: Duration::from_micros(100),
+        }
+    }
+
+    #[test]
+    fn chrome_trace_pairs_waits_and_holds() {
+        let mut out = Vec::new();
+        timeline().write_chrome_trace(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let events: Vec<_> = out
+            .lines()
+            .filter(|line| line.starts_with(r#"{"name""#))
+            .collect();
+        // 곧바로 얻은 쓰기에는 대기 구간이 없다.
+        assert_eq!(events.len(), 6, "{out}");
+        assert!(events[0].starts_with(
+            r#"{"name":"write 'ja\"bs'","cat":"hold","ph":"X","ts":0.000,"dur":50.000,"pid":1,"tid":1,"#
+        ));
+        assert!(events[1].starts_with(
+            r#"{"name":"wait read 'ja\"bs'","cat":"wait","ph":"X","ts":10.000,"dur":40.000,"pid":1,"tid":2,"#
+        ));
+        assert!(events[1].ends_with(r#""finished":true,"outcome":"acquired"}},"#));
+        assert!(events[2].contains(r#""outcome":"cancelled""#));
+        // 아직 쥔 읽기는 `stop` 에서 끝난다.
+        assert!(events[3].contains(r#""ts":50.000,"dur":50.000"#));
+        assert!(events[3].contains(r#""finished":false"#));
+        assert_eq!(
+            events[4],
+            r#"{"name":"thread_name","ph":"M","pid":1,"tid":1,"args":{"name":"writer"}},"#
+        );
+        assert!(out.ends_with("\n]}\n"));
+    }
+
+    #[test]
+    fn ndjson_writes_one_escaped_object_per_record() {
+        let mut out = Vec::new();
+        timeline().write_ndjson(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = out.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert!(lines[2].starts_with(
+            r#"{"phase":"wait_start","acquisition":2,"lock_id":9,"lock":"ja\"bs","access":"read","task":null,"thread":"reader","site":"src/timeline.rs:"#
+        ));
+        assert!(lines[2].ends_with(r#","at_us":10.000}"#));
+    }
+
+    #[test]
+    fn a_full_buffer_keeps_the_newest_records() {
+        start(2);
+        for _ in 0..3 {
+            if let Some(pending) =
+                begin(&LockConfig::new(), 0, AccessKind::Read, Location::caller())
+            {
+                pending.acquired();
+            }
+        }
+        let timeline = stop();
+        // 다른 테스트의 획득도 같은 버퍼에 들어올 수 있다.
+        assert_eq!(timeline.records.len(), 2);
+        assert!(timeline.dropped >= 4);
+        assert!(stop().records.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+//! 이벤트를 `tracing` 이벤트로, 가드의 수명을 `tracing` span 으로 내보낸다. (`tracing-backend` 기능)
+//!
+//! 이벤트 메타데이터의 파일/줄은 이 모듈을 가리키므로, 실제로 `read()`/`write()` 를 부른 위치는
+//! `caller.file`/`caller.line` 필드에 따로 적는다. 해제 이벤트는 드롭이 크레이트 안에서 일어나므로
+//! 가드를 얻은 위치를 적는다.
+
+use std::panic::Location;
+use std::time::Duration;
+
+use tracing::{field, Level, Span};
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind, LockEvent, ReleaseReason};
+
+pub(crate) const TARGET: &str = "tracing_lock";
+
+pub(crate) fn level(kind: EventKind) -> Level {
+    match kind {
+        EventKind::Misuse | EventKind::Starvation | EventKind::Deadlock => Level::ERROR,
+        EventKind::Warning => Level::WARN,
+        EventKind::Created | EventKind::Destroyed => Level::TRACE,
+        _ => Level::DEBUG,
+    }
+}
+
+pub(crate) fn enabled(kind: EventKind) -> bool {
+    match level(kind) {
+        Level::ERROR => tracing::enabled!(target: TARGET, Level::ERROR),
+        Level::WARN => tracing::enabled!(target: TARGET, Level::WARN),
+        Level::TRACE => tracing::enabled!(target: TARGET, Level::TRACE),
+        _ => tracing::enabled!(target: TARGET, Level::DEBUG),
+    }
+}
+
+fn access(access: AccessKind) -> &'static str {
+    match access {
+        AccessKind::Read => "read",
+        AccessKind::Write => "write",
+    }
+}
+
+fn micros(duration: Option<Duration>) -> Option<u64> {
+    duration.map(|d| u64::try_from(d.as_micros()).unwrap_or(u64::MAX))
+}
+
+/// `LockEvent` 하나를 해당 레벨의 `tracing` 이벤트로 보낸다. 부모는 현재 span 이다.
+pub(crate) fn event(event: &LockEvent) {
+    let caller = event.acquired_at.unwrap_or(event.location);
+    let reason = event.reason.map(|reason| match reason {
+        ReleaseReason::Dropped => "dropped",
+        ReleaseReason::Explicit => "explicit",
+        ReleaseReason::Unwound => "unwound",
+        ReleaseReason::EarlyExit => "early_exit",
+    });
+    let previous = event.previous.map(access);
+    macro_rules! at_level {
+        ($level:expr) => {
+            tracing::event!(
+                target: TARGET,
+                $level,
+                seq = event.seq,
+                kind = ?event.kind,
+                access = access(event.access),
+                lock = event.name.as_deref(),
+                caller.file = caller.file(),
+                caller.line = caller.line(),
+                thread = event.thread.as_str(),
+                waited_us = micros(event.waited),
+                scheduler_delay_us = micros(event.scheduler_delay),
+                held_us = micros(event.held),
+                context = event.context.as_deref(),
+                reason,
+                previous,
+                depth = event.depth,
+                priority = event.priority,
+                accesses = event.accesses,
+                ticket = event.ticket,
+                stack_depth = event.stack_depth,
+                operation_id = event.operation_id.as_deref(),
+                "{}",
+                event.message.as_deref().unwrap_or(""),
+            )
+        };
+    }
+    match level(event.kind) {
+        Level::ERROR => at_level!(Level::ERROR),
+        Level::WARN => at_level!(Level::WARN),
+        Level::TRACE => at_level!(Level::TRACE),
+        _ => at_level!(Level::DEBUG),
+    }
+}
+
+/// 가드 하나의 수명을 덮는 `lock_guard` span. 획득/해제 이벤트는 이 span 안에서 나간다.
+///
+/// 현재 span 을 부모로 삼으므로 락을 잡은 요청의 span 아래에 보인다. `silent` 락이나
+/// `set_enabled(false)` 동안 얻은 가드는 만들지 않는다.
+pub(crate) fn guard_span(
+    config: &LockConfig,
+    kind: AccessKind,
+    location: &'static Location<'static>,
+    waited: Option<Duration>,
+) -> Span {
+    if config.silent || !crate::config::enabled() {
+        return Span::none();
+    }
+    tracing::debug_span!(
+        target: TARGET,
+        "lock_guard",
+        lock = config.name.as_deref(),
+        access = access(kind),
+        caller.file = location.file(),
+        caller.line = location.line(),
+        waited_us = micros(waited),
+        held_us = field::Empty,
+    )
+}
+
+/// 해제할 때 점유 시간을 span 에 적는다.
+pub(crate) fn record_held(span: &Span, held: Duration) {
+    span.record("held_us", micros(Some(held)));
+}
@@ -0,0 +1,241 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
+use tokio::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::time::Instant;
+
+use crate::config::LockConfig;
+use crate::event::{AccessKind, EventKind};
+use crate::registry::{self, Entry};
+use crate::stats::{LockStats, LockStatsSnapshot};
+
+/// `TracedLock` 이 사용할 실제 동기화 primitive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LockBacking {
+    #[default]
+    RwLock,
+    /// 읽기와 쓰기를 똑같이 배타적으로 처리한다.
+    Mutex,
+}
+
+enum Backing<T> {
+    RwLock(RwLock<T>),
+    Mutex(Mutex<T>),
+}
+
+/// `Mutex` 와 `RwLock` 중 어느 쪽이 워크로드에 맞는지 비교하기 위한 파사드.
+///
+/// 호출부는 그대로 두고 `LockBacking` 만 바꿔서 두 primitive 의 경합 통계를 비교할 수 있다.
+///
+/// ```
+/// use tracing_lock::{LockBacking, TracedLock};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let backing = if std::env::var_os("USE_MUTEX").is_some() {
+///     LockBacking::Mutex
+/// } else {
+///     LockBacking::RwLock
+/// };
+/// let lock = TracedLock::with_backing(0, backing);
+/// *lock.lock().await += 1;
+/// assert_eq!(*lock.read().await, 1);
+/// # }
+/// ```
+pub struct TracedLock<T> {
+    inner: Backing<T>,
+    config: LockConfig,
+    entry: Arc<Entry>,
+}
+
+impl<T> TracedLock<T> {
+    pub fn new(value: T) -> Self {
+        Self::with_backing(value, LockBacking::default())
+    }
+
+    pub fn with_backing(value: T, backing: LockBacking) -> Self {
+        let inner = match backing {
+            LockBacking::RwLock => Backing::RwLock(RwLock::new(value)),
+            LockBacking::Mutex => Backing::Mutex(Mutex::new(value)),
+        };
+        let config = LockConfig::inherited();
+        let entry = registry::register();
+        entry.configure(&config);
+        TracedLock {
+            inner,
+            config,
+            entry,
+        }
+    }
+
+    /// `config` 에서 빌더로 직접 정한 값만 바꾼다. 나머지는 물려받은 기본 설정이 남는다.
+    pub fn with_config(mut self, config: LockConfig) -> Self {
+        let config = config.overlay(&self.config);
+        self.entry.configure(&config);
+        self.config = config;
+        self
+    }
+
+    pub fn backing(&self) -> LockBacking {
+        match self.inner {
+            Backing::RwLock(_) => LockBacking::RwLock,
+            Backing::Mutex(_) => LockBacking::Mutex,
+        }
+    }
+
+    /// 현재까지의 통계 스냅샷
+    pub fn stats(&self) -> LockStatsSnapshot {
+        self.entry.stats.snapshot()
+    }
+
+    /// `write` 와 같다.
+    pub async fn lock(&self) -> TracedWriteGuard<'_, T> {
+        self.write().await
+    }
+
+    pub async fn read(&self) -> TracedReadGuard<'_, T> {
+        let wait = self.entry.stats.start_wait();
+        let guard = match &self.inner {
+            Backing::RwLock(lock) => ReadInner::RwLock(lock.read().await),
+            Backing::Mutex(lock) => ReadInner::Mutex(lock.lock().await),
+        };
+        drop(wait);
+        self.entry.stats.record_read();
+        log_call_info!(&self.config, EventKind::Acquire, AccessKind::Read);
+        TracedReadGuard {
+            guard,
+            config: &self.config,
+            stats: &self.entry.stats,
+            start_time: Instant::now(),
+        }
+    }
+
+    pub async fn write(&self) -> TracedWriteGuard<'_, T> {
+        let wait = self.entry.stats.start_wait();
+        let guard = match &self.inner {
+            Backing::RwLock(lock) => WriteInner::RwLock(lock.write().await),
+            Backing::Mutex(lock) => WriteInner::Mutex(lock.lock().await),
+        };
+        drop(wait);
+        self.entry.stats.record_write();
+        log_call_info!(&self.config, EventKind::Acquire, AccessKind::Write);
+        TracedWriteGuard {
+            guard,
+            config: &self.config,
+            stats: &self.entry.stats,
+            start_time: Instant::now(),
+        }
+    }
+}
+
+enum ReadInner<'a, T> {
+    RwLock(RwLockReadGuard<'a, T>),
+    Mutex(MutexGuard<'a, T>),
+}
+
+enum WriteInner<'a, T> {
+    RwLock(RwLockWriteGuard<'a, T>),
+    Mutex(MutexGuard<'a, T>),
+}
+
+/// `TracedLock::read` 가 반환하는 가드
+pub struct TracedReadGuard<'a, T> {
+    guard: ReadInner<'a, T>,
+    config: &'a LockConfig,
+    stats: &'a LockStats,
+    start_time: Instant,
+}
+
+impl<T> Deref for TracedReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.guard {
+            ReadInner::RwLock(guard) => guard,
+            ReadInner::Mutex(guard) => guard,
+        }
+    }
+}
+
+impl<T> Drop for TracedReadGuard<'_, T> {
+    fn drop(&mut self) {
+        let duration = self.start_time.elapsed();
+        self.stats.record_release(AccessKind::Read, duration);
+        log_call_info!(self.config, EventKind::Release, AccessKind::Read, held: Some(duration));
+    }
+}
+
+/// `TracedLock::write`/`lock` 가 반환하는 가드
+pub struct TracedWriteGuard<'a, T> {
+    guard: WriteInner<'a, T>,
+    config: &'a LockConfig,
+    stats: &'a LockStats,
+    start_time: Instant,
+}
+
+impl<T> Deref for TracedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        match &self.guard {
+            WriteInner::RwLock(guard) => guard,
+            WriteInner::Mutex(guard) => guard,
+        }
+    }
+}
+
+impl<T> DerefMut for TracedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.guard {
+            WriteInner::RwLock(guard) => guard,
+            WriteInner::Mutex(guard) => guard,
+        }
+    }
+}
+
+impl<T> Drop for TracedWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        let duration = self.start_time.elapsed();
+        self.stats.record_release(AccessKind::Write, duration);
+        log_call_info!(self.config, EventKind::Release, AccessKind::Write, held: Some(duration));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn workload(backing: LockBacking) -> LockStatsSnapshot {
+        let lock = Arc::new(TracedLock::with_backing(0u64, backing));
+        let tasks: Vec<_> = (0..4)
+            .map(|i| {
+                let lock = lock.clone();
+                tokio::spawn(async move {
+                    for _ in 0..10 {
+                        if i == 0 {
+                            *lock.write().await += 1;
+                        } else {
+                            let _ = *lock.read().await;
+                        }
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*lock.read().await, 10);
+        assert_eq!(lock.backing(), backing);
+        lock.stats()
+    }
+
+    #[tokio::test]
+    async fn same_workload_under_both_backings() {
+        let rwlock = workload(LockBacking::RwLock).await;
+        let mutex = workload(LockBacking::Mutex).await;
+        assert_eq!((rwlock.reads, rwlock.writes), (31, 10));
+        assert_eq!((mutex.reads, mutex.writes), (rwlock.reads, rwlock.writes));
+        assert_eq!((rwlock.waiters, mutex.waiters), (0, 0));
+    }
+}
@@ -0,0 +1,292 @@
+//! 너무 오래 기다리거나 쥔 가드를 그 자리에서 알리는 감시 스레드.
+//! (`LockConfig::warn_if_wait_exceeds`, `LockConfig::warn_if_held_exceeds`)
+//!
+//! 감시할 락은 설정될 때 여기에 약한 참조로 오른다. 스레드는 처음 감시할 락이 생길 때 하나만
+//! 띄우며, 대기자/보유자 중 가장 먼저 문턱을 넘을 시각까지 잠들었다가 살펴본다.
+
+use std::{
+    sync::{Arc, Condvar, Mutex, Once, PoisonError, Weak},
+    time::{Duration, Instant},
+};
+
+use crate::config::LockConfig;
+use crate::event::{self, AccessKind, EventKind};
+use crate::registry::{Entry, Overdue};
+
+/// 감시하는 락 하나의 설정
+#[derive(Debug)]
+pub(crate) struct Watch {
+    config: LockConfig,
+    wait: Option<Duration>,
+    held: Option<Duration>,
+}
+
+static WATCHED: Mutex<Vec<Weak<Entry>>> = Mutex::new(Vec::new());
+// 감시할 락이 새로 생기면 깨워서 잠들 시간을 다시 정하게 한다.
+static WAKE: Condvar = Condvar::new();
+static STARTED: Once = Once::new();
+
+/// 문턱이 있으면 `entry` 를 감시 목록에 올리고, 없으면 감시를 끈다.
+pub(crate) fn configure(entry: &Arc<Entry>, config: &LockConfig) {
+    let watch = (config.wait_watchdog.is_some() || config.held_watchdog.is_some()).then(|| {
+        Arc::new(Watch {
+            config: config.clone(),
+            wait: config.wait_watchdog,
+            held: config.held_watchdog,
+        })
+    });
+    let watching = watch.is_some();
+    *entry.watch.lock().unwrap_or_else(PoisonError::into_inner) = watch;
+    // 감시를 끈 락은 스레드가 다음에 살펴볼 때 목록에서 뺀다.
+    if !watching {
+        return;
+    }
+    {
+        let mut watched = WATCHED.lock().unwrap_or_else(PoisonError::into_inner);
+        if !watched
+            .iter()
+            .any(|weak| std::ptr::eq(weak.as_ptr(), Arc::as_ptr(entry)))
+        {
+            watched.push(Arc::downgrade(entry));
+        }
+    }
+    STARTED.call_once(|| {
+        std::thread::Builder::new()
+            .name("tracing-lock-watchdog".into())
+            .spawn(run)
+            .expect("failed to spawn the lock watchdog thread");
+    });
+    WAKE.notify_one();
+}
+
+fn run() {
+    let mut watched = WATCHED.lock().unwrap_or_else(PoisonError::into_inner);
+    loop {
+        watched.retain(|weak| {
+            weak.upgrade().is_some_and(|entry| {
+                entry
+                    .watch
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .is_some()
+            })
+        });
+        let entries: Vec<_> = watched.iter().filter_map(Weak::upgrade).collect();
+        // 알림 콜백이 락을 새로 설정해도 막히지 않도록 목록을 놓고 살펴본다.
+        drop(watched);
+        let wake_at = check(&entries, Instant::now());
+        drop(entries);
+        watched = WATCHED.lock().unwrap_or_else(PoisonError::into_inner);
+        watched = match wake_at {
+            Some(at) => {
+                let timeout = at.saturating_duration_since(Instant::now());
+                WAKE.wait_timeout(watched, timeout)
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .0
+            }
+            None => WAKE.wait(watched).unwrap_or_else(PoisonError::into_inner),
+        };
+    }
+}
+
+// 문턱을 넘긴 것들을 알리고 다음에 깨어날 시각을 돌려준다. 지금 목록에 없는 획득도 가장 짧은
+// 문턱보다 일찍 넘을 수는 없으므로 그때까지만 잔다.
+fn check(entries: &[Arc<Entry>], now: Instant) -> Option<Instant> {
+    let mut wake_at = None::<Instant>;
+    let mut wake_by = |at: Instant| wake_at = Some(wake_at.map_or(at, |wake| wake.min(at)));
+    for entry in entries {
+        let Some(watch) = entry
+            .watch
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+        else {
+            continue;
+        };
+        for (waiting, threshold) in [(true, watch.wait), (false, watch.held)] {
+            let Some(threshold) = threshold else {
+                continue;
+            };
+            wake_by(now + threshold);
+            let (overdue, next) = entry.take_overdue(waiting, threshold, now);
+            if let Some(next) = next {
+                wake_by(next);
+            }
+            for overdue in overdue {
+                alert(entry, &watch, waiting, threshold, overdue);
+            }
+        }
+    }
+    wake_at
+}
+
+fn alert(entry: &Entry, watch: &Watch, waiting: bool, threshold: Duration, overdue: Overdue) {
+    let task = overdue
+        .task
+        .map(|task| format!(" in task {}", task))
+        .unwrap_or_default();
+    let message = if waiting {
+        // 누가 쥐고 있는지 알면 덧붙인다.
+        let holders: Vec<_> = [AccessKind::Write, AccessKind::Read]
+            .into_iter()
+            .flat_map(|access| entry.holder_sites(access))
+            .map(|site| format!("{}:{}", site.file(), site.line()))
+            .collect();
+        let held_by = match holders.len() {
+            0 => String::new(),
+            _ => format!("; held from {}", holders.join(", ")),
+        };
+        format!(
+            "still waiting{} after {:?} (threshold {:?}){}",
+            task, overdue.elapsed, threshold, held_by
+        )
+    } else {
+        format!(
+            "held{} for {:?} (threshold {:?}) and not yet released",
+            task, overdue.elapsed, threshold
+        )
+    };
+    let config = &watch.config;
+    let Some(event) = lock_event!(
+        config,
+        EventKind::Warning,
+        overdue.access,
+        location: overdue.location,
+        thread: overdue.thread,
+        waited: waiting.then_some(overdue.elapsed),
+        held: (!waiting).then_some(overdue.elapsed),
+        acquired_at: (!waiting).then_some(overdue.location),
+        message: Some(message),
+    ) else {
+        return;
+    };
+    if let Some(observer) = &config.watchdog_observer {
+        observer.on_event(&event);
+    }
+    event::emit(config, event);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::CapturingObserver;
+    use crate::{LockEvent, TokioRwLockTrace};
+
+    // 감시 스레드가 알릴 때까지 기다린다.
+    async fn warnings(events: &CapturingObserver, n: usize) -> Vec<LockEvent> {
+        for _ in 0..500 {
+            let mut warnings = events.events();
+            warnings.retain(|e| e.kind == EventKind::Warning);
+            if warnings.len() >= n {
+                return warnings;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("watchdog did not report {n} warning(s)");
+    }
+
+    #[tokio::test]
+    async fn long_hold_is_reported_before_release() {
+        let events = CapturingObserver::new();
+        let lock = TokioRwLockTrace::new(0).with_config(
+            LockConfig::new()
+                .name("watched")
+                .silent()
+                .observer(events.clone())
+                .warn_if_held_exceeds(Duration::from_millis(20)),
+        );
+        drop(lock.read().await);
+        let line = line!() + 1;
+        let guard = lock.write().await;
+
+        let warning = warnings(&events, 1).await.remove(0);
+        assert_eq!(warning.access, AccessKind::Write);
+        assert_eq!(warning.acquired_at.map(|at| at.line()), Some(line));
+        assert!(warning.held.unwrap() >= Duration::from_millis(20));
+        assert!(warning
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("not yet released"));
+        assert_eq!(warning.thread, crate::task::thread_name());
+
+        // 한 가드에 한 번만 알린다.
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        drop(guard);
+        let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds.iter().filter(|k| **k == EventKind::Warning).count(),
+            1
+        );
+        assert_eq!(kinds.last(), Some(&EventKind::Release));
+    }
+
+    #[tokio::test]
+    async fn slow_acquire_reaches_the_callback_while_waiting() {
+        let events = CapturingObserver::new();
+        let lock = Arc::new(
+            TokioRwLockTrace::new(0).with_config(
+                LockConfig::new()
+                    .silent()
+                    .warn_if_wait_exceeds(Duration::from_millis(20))
+                    .on_watchdog(events.clone()),
+            ),
+        );
+        let writer = lock.write().await;
+        let line = line!() + 3;
+        let reader = tokio::spawn({
+            let lock = lock.clone();
+            async move { *lock.read().await }
+        });
+
+        let warning = warnings(&events, 1).await.remove(0);
+        assert!(!reader.is_finished());
+        assert_eq!(warning.access, AccessKind::Read);
+        assert_eq!(warning.location.line(), line);
+        assert!(warning.waited.unwrap() >= Duration::from_millis(20));
+        let message = warning.message.unwrap();
+        assert!(message.starts_with("still waiting"), "{message}");
+
+        drop(writer);
+        reader.await.unwrap();
+        // 곧바로 얻은 획득은 대기자로 오르지 않는다.
+        drop(lock.read().await);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(events.events().len(), 1);
+    }
+
+    // `trace` 를 끄면 `TracedMutex` 는 계측하지 않는다.
+    #[cfg(feature = "trace")]
+    #[tokio::test]
+    async fn primitives_share_the_watchdog() {
+        let events = CapturingObserver::new();
+        let mutex = crate::TracedMutex::new(0).with_config(
+            LockConfig::new()
+                .silent()
+                .observer(events.clone())
+                .warn_if_held_exceeds(Duration::from_millis(10))
+                .warn_if_wait_exceeds(Duration::from_millis(10)),
+        );
+        let held_line = line!() + 1;
+        let guard = mutex.lock().await;
+        let waiting = async {
+            let _ = mutex.lock().await;
+        };
+        tokio::select! {
+            _ = waiting => unreachable!("the mutex is held"),
+            _ = warnings(&events, 2) => {}
+        }
+        drop(guard);
+
+        let warnings = warnings(&events, 2).await;
+        let message = |e: &LockEvent| e.message.clone().unwrap();
+        let held = warnings.iter().find(|e| e.held.is_some()).unwrap();
+        assert_eq!(held.acquired_at.map(|at| at.line()), Some(held_line));
+        let waited = warnings.iter().find(|e| e.waited.is_some()).unwrap();
+        assert!(
+            message(waited).contains(&format!(":{held_line}")),
+            "{}",
+            message(waited)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! 락을 살려 두지 않는 약한 참조.
+
+use std::sync::{Arc, Weak};
+
+use tokio::sync::{RwLock, Semaphore};
+
+use crate::config::LockConfig;
+use crate::lock::TokioRwLockTrace;
+use crate::registry::Entry;
+
+/// `TokioRwLockTrace::downgrade` 로 만든 약한 참조. `Arc` 에 대한 `Weak` 와 같다.
+///
+/// 값과 레지스트리 항목을 살려 두지 않으므로, 마지막 강한 참조가 드롭되면 락은 레지스트리 조회
+/// 결과에서도 사라진다.
+///
+/// ```
+/// use tracing_lock::TokioRwLockTrace;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let lock = TokioRwLockTrace::new(1);
+/// let weak = lock.downgrade();
+/// assert_eq!(*weak.upgrade().unwrap().read().await, 1);
+/// drop(lock);
+/// assert!(weak.upgrade().is_none());
+/// # }
+/// ```
+pub struct WeakRwLockTrace<T> {
+    inner: Weak<RwLock<T>>,
+    config: Arc<LockConfig>,
+    entry: Weak<Entry>,
+    read_permits: Option<Weak<Semaphore>>,
+}
+
+impl<T> TokioRwLockTrace<T> {
+    /// 값과 통계를 살려 두지 않는 약한 참조를 만든다.
+    pub fn downgrade(&self) -> WeakRwLockTrace<T> {
+        WeakRwLockTrace {
+            inner: Arc::downgrade(&self.inner),
+            config: self.config.clone(),
+            entry: Arc::downgrade(&self.entry),
+            read_permits: self.read_permits.as_ref().map(Arc::downgrade),
+        }
+    }
+}
+
+impl<T> WeakRwLockTrace<T> {
+    /// 락이 아직 살아 있으면 같은 값, 설정, 통계를 공유하는 핸들을 돌려준다.
+    pub fn upgrade(&self) -> Option<TokioRwLockTrace<T>> {
+        let read_permits = match &self.read_permits {
+            Some(permits) => Some(permits.upgrade()?),
+            None => None,
+        };
+        let inner = self.inner.upgrade()?;
+        let entry = self.entry.upgrade()?;
+        // 마지막 핸들이 드롭되는 중이면 소멸 이벤트가 이미 나갔으므로 되살리지 않는다.
+        if !entry.add_handle() {
+            return None;
+        }
+        Some(TokioRwLockTrace {
+            inner,
+            config: self.config.clone(),
+            entry,
+            read_permits,
+            #[cfg(feature = "dry-run")]
+            dry_run: RwLock::new(()),
+        })
+    }
+}
+
+impl<T> Clone for WeakRwLockTrace<T> {
+    fn clone(&self) -> Self {
+        WeakRwLockTrace {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+            entry: self.entry.clone(),
+            read_permits: self.read_permits.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::registry;
+    use crate::{LockConfig, TokioRwLockTrace};
+
+    fn registered(id: u64) -> bool {
+        registry::top_contended(usize::MAX)
+            .iter()
+            .any(|info| info.id == id)
+    }
+
+    #[tokio::test]
+    async fn lock_leaves_registry_after_last_strong_reference() {
+        let lock = TokioRwLockTrace::new(0)
+            .with_config(LockConfig::new().name("weak-ageout").silent())
+            .with_max_readers(2);
+        let id = lock.entry.id;
+        let weak = lock.downgrade();
+        assert!(registered(id));
+
+        let upgraded = weak.upgrade().unwrap();
+        *upgraded.write().await += 1;
+        assert_eq!(upgraded.name(), Some("weak-ageout"));
+        let reader = upgraded.read().await;
+        assert_eq!(lock.stats().writes, 1);
+        assert_eq!(lock.stats().read_permits_in_use, 1);
+        drop(reader);
+        drop(upgraded);
+        assert!(registered(id));
+
+        drop(lock);
+        assert!(!registered(id));
+        assert!(weak.upgrade().is_none());
+        assert!(!registry::dump_state().contains("weak-ageout"));
+    }
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "access-count")]
+
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{EventKind, LockConfig, TokioRwLockTrace};
+
+fn recording_lock() -> (CapturingObserver, TokioRwLockTrace<Vec<u32>>) {
+    let events = CapturingObserver::new();
+    let lock = TokioRwLockTrace::new(Vec::new())
+        .with_config(LockConfig::new().silent().observer(events.clone()));
+    (events, lock)
+}
+
+fn released_accesses(events: &CapturingObserver) -> Vec<Option<u64>> {
+    events
+        .events()
+        .iter()
+        .filter(|e| e.kind == EventKind::Release)
+        .map(|e| e.accesses)
+        .collect()
+}
+
+#[tokio::test]
+async fn release_reports_deref_count() {
+    let (events, lock) = recording_lock();
+    {
+        let mut guard = lock.write().await;
+        for i in 0..3 {
+            guard.push(i); // deref_mut
+        }
+        assert_eq!(guard.len(), 3); // deref
+        guard.reborrow().clear();
+    }
+    {
+        let guard = lock.read().await;
+        let _ = (guard.is_empty(), guard.len());
+    }
+    drop(lock.read().await);
+
+    assert_eq!(released_accesses(&events), [Some(5), Some(2), Some(0)]);
+    let line = events.events()[2].to_string();
+    assert!(line.contains("(accessed 5 times)"), "{line}");
+}
+
+#[tokio::test]
+async fn downgrade_keeps_counting() {
+    let (events, lock) = recording_lock();
+    let mut guard = lock.write().await;
+    guard.push(1);
+    let reader = guard.downgrade();
+    assert_eq!(reader[0], 1);
+    drop(reader);
+
+    assert_eq!(released_accesses(&events), [Some(2)]);
+}
@@ -0,0 +1,57 @@
+//! 출력이 꺼진 락의 획득/해제가 메시지를 만들지 않는지 할당 수로 확인한다.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations_per_cycle(lock: &TokioRwLockTrace<u32>) -> usize {
+    const CYCLES: usize = 100;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // 첫 획득의 일회성 초기화는 세지 않는다.
+        drop(lock.read().await);
+        let before = ALLOCATIONS.with(Cell::get);
+        for _ in 0..CYCLES {
+            drop(lock.read().await);
+            *lock.write().await += 1;
+        }
+        (ALLOCATIONS.with(Cell::get) - before) / (2 * CYCLES)
+    })
+}
+
+#[test]
+fn silent_lock_only_allocates_the_acquire_future() {
+    let silent = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("quiet").silent());
+    let logged = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("loud"));
+
+    // 남는 할당은 상자에 담긴 획득 future 하나뿐이다.
+    assert_eq!(allocations_per_cycle(&silent), 1);
+    // `log-backend`/`tracing-backend` 에서는 로거나 구독자가 없으면 `loud` 도 꺼진 것으로 본다.
+    #[cfg(not(any(feature = "log-backend", feature = "tracing-backend")))]
+    assert!(allocations_per_cycle(&logged) > 1);
+    #[cfg(any(feature = "log-backend", feature = "tracing-backend"))]
+    assert_eq!(allocations_per_cycle(&logged), 1);
+}
@@ -0,0 +1,32 @@
+//! `benches/contention.rs` 의 하네스를 짧게 돌려서 깨지지 않았는지 확인한다.
+
+#[path = "../benches/contention.rs"]
+mod contention;
+
+use std::time::Duration;
+
+use contention::Workload;
+
+#[test]
+fn harness_produces_nonzero_stats() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    let rows: Vec<_> = Workload::ALL
+        .into_iter()
+        .map(|workload| contention::run(&runtime, workload, 4, Duration::from_millis(30)))
+        .collect();
+    contention::print_table(&rows);
+
+    for row in &rows {
+        assert!(row.stats_agree, "{row:?}");
+        assert!(row.per_sec() > 0.0, "{row:?}");
+        match row.workload {
+            Workload::Read => assert!(row.reads > 0 && row.writes == 0, "{row:?}"),
+            Workload::Write => assert!(row.writes > 0 && row.reads == 0, "{row:?}"),
+            Workload::Mixed => assert!(row.reads > row.writes && row.writes > 0, "{row:?}"),
+        }
+    }
+}
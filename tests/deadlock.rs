@@ -0,0 +1,191 @@
+use std::{
+    sync::{Arc, Mutex, Once},
+    time::Duration,
+};
+
+use tokio::sync::Barrier;
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{
+    deadlock::{self, DeadlockReport},
+    AccessKind, EventKind, LockConfig, TokioRwLockTrace,
+};
+
+// 교착 검사와 콜백은 전역이므로 다른 테스트와 프로세스를 나눈다. 이 파일의 테스트는 보고를 락
+// 이름으로 가려 낸다.
+static REPORTS: Mutex<Vec<DeadlockReport>> = Mutex::new(Vec::new());
+static ENABLE: Once = Once::new();
+
+fn enable() {
+    ENABLE.call_once(|| {
+        deadlock::set_enabled(true);
+        deadlock::on_deadlock(|report| REPORTS.lock().unwrap().push(report.clone()));
+    });
+}
+
+fn involving(name: &str) -> Vec<DeadlockReport> {
+    REPORTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|report| {
+            report
+                .edges
+                .iter()
+                .any(|edge| edge.lock.as_deref() == Some(name))
+        })
+        .cloned()
+        .collect()
+}
+
+async fn reported(name: &str) -> DeadlockReport {
+    for _ in 0..500 {
+        if let Some(report) = involving(name).pop() {
+            return report;
+        }
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    }
+    panic!("no deadlock reported for {name}");
+}
+
+#[tokio::test]
+async fn opposite_lock_orders_are_reported_with_their_call_sites() {
+    enable();
+    let events = CapturingObserver::new();
+    let config = |name: &str| {
+        LockConfig::new()
+            .name(name)
+            .silent()
+            .observer(events.clone())
+    };
+    let a = Arc::new(TokioRwLockTrace::new(0).with_config(config("order-a")));
+    let b = Arc::new(TokioRwLockTrace::new(0).with_config(config("order-b")));
+    let barrier = Arc::new(Barrier::new(2));
+
+    let a_held = line!() + 5;
+    let b_waits = a_held + 2;
+    let first = tokio::spawn({
+        let (a, b, barrier) = (a.clone(), b.clone(), barrier.clone());
+        async move {
+            let _a = a.write().await;
+            barrier.wait().await;
+            let _b = b.write().await;
+        }
+    });
+    let b_held = line!() + 5;
+    let a_waits = b_held + 2;
+    let second = tokio::spawn({
+        let (a, b, barrier) = (a.clone(), b.clone(), barrier.clone());
+        async move {
+            let _b = b.read().await;
+            barrier.wait().await;
+            let _a = a.write().await;
+        }
+    });
+
+    let report = reported("order-a").await;
+    first.abort();
+    second.abort();
+    assert_eq!(report.edges.len(), 2, "{report}");
+    let edge = |lock: &str| {
+        report
+            .edges
+            .iter()
+            .find(|edge| edge.lock.as_deref() == Some(lock))
+            .unwrap()
+    };
+    let (on_a, on_b) = (edge("order-a"), edge("order-b"));
+    assert_eq!(on_a.waiting_at.line(), a_waits);
+    assert_eq!(on_a.held_at.line(), a_held);
+    assert_eq!(on_b.waiting_at.line(), b_waits);
+    assert_eq!(
+        (on_b.held_at.line(), on_b.held_access),
+        (b_held, AccessKind::Read)
+    );
+    // 서로를 기다린다.
+    assert_eq!(on_a.waiter, on_b.holder);
+    assert_eq!(on_b.waiter, on_a.holder);
+    assert!(!on_a.queued && !on_b.queued);
+
+    let events = events.events();
+    let deadlock = events
+        .iter()
+        .find(|e| e.kind == EventKind::Deadlock)
+        .unwrap();
+    assert_eq!(
+        deadlock.message.as_deref(),
+        Some(report.to_string().as_str())
+    );
+    assert!(deadlock
+        .message
+        .as_deref()
+        .unwrap()
+        .contains(&format!("deadlock.rs:{b_held}")));
+}
+
+#[tokio::test]
+async fn reading_again_behind_a_queued_writer_is_reported() {
+    enable();
+    let lock =
+        Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().name("reread").silent()));
+    let reader = lock.read().await;
+    let writer = tokio::spawn({
+        let lock = lock.clone();
+        async move { drop(lock.write().await) }
+    });
+    while lock.stats().waiters == 0 {
+        tokio::task::yield_now().await;
+    }
+
+    let reread = tokio::time::timeout(Duration::from_millis(20), lock.read()).await;
+    assert!(reread.is_err());
+    let report = reported("reread").await;
+    assert_eq!(report.edges.len(), 2, "{report}");
+    assert!(report.edges[0].queued);
+    assert_eq!(report.edges[0].held_access, AccessKind::Write);
+    assert_eq!(report.edges[1].held_access, AccessKind::Read);
+
+    drop(reader);
+    writer.await.unwrap();
+}
+
+// `trace` 를 끄면 `TracedMutex` 는 계측하지 않는다.
+#[cfg(feature = "trace")]
+#[tokio::test]
+async fn relocking_a_held_mutex_is_a_cycle_of_one() {
+    enable();
+    let mutex =
+        tracing_lock::TracedMutex::new(0).with_config(LockConfig::new().name("relock").silent());
+    let held = line!() + 1;
+    let guard = mutex.lock().await;
+    // 기다리지 않는 시도는 교착이 아니다.
+    assert!(mutex.try_lock().is_err());
+    assert!(involving("relock").is_empty());
+
+    let relock = tokio::time::timeout(Duration::from_millis(5), mutex.lock()).await;
+    assert!(relock.is_err());
+    let report = reported("relock").await;
+    assert_eq!(report.edges.len(), 1);
+    assert_eq!(report.edges[0].waiter, report.edges[0].holder);
+    assert_eq!(report.edges[0].held_at.line(), held);
+    drop(guard);
+}
+
+#[tokio::test]
+async fn waiting_without_a_cycle_is_not_reported() {
+    enable();
+    let a =
+        Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().name("plain").silent()));
+    let mut guard = a.write().await;
+    let waiter = tokio::spawn({
+        let a = a.clone();
+        async move { *a.write().await += 1 }
+    });
+    while a.stats().waiters == 0 {
+        tokio::task::yield_now().await;
+    }
+    *guard += 1;
+    drop(guard);
+    waiter.await.unwrap();
+    assert_eq!(*a.read().await, 2);
+    assert!(involving("plain").is_empty());
+}
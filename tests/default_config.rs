@@ -0,0 +1,57 @@
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{EventKind, LockConfig, TokioRwLockTrace};
+
+// 전역 기본값을 바꾸므로 다른 테스트와 프로세스를 나눈다.
+#[tokio::test]
+async fn new_locks_inherit_a_snapshot_of_the_default() {
+    let events = CapturingObserver::new();
+    tracing_lock::set_default_config(
+        LockConfig::new()
+            .name("inherited")
+            .silent()
+            .observer(events.clone()),
+    );
+
+    let plain = TokioRwLockTrace::new(0);
+    assert_eq!(plain.name(), Some("inherited"));
+    // `with_config` 는 직접 정한 이름만 바꾸고 관찰자는 그대로 둔다.
+    let renamed = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("renamed"));
+    // 물려받은 관찰자는 뗄 수 있고, 이름을 정하지 않으면 이름도 물려받는다.
+    let detached = TokioRwLockTrace::new(0)
+        .with_config(LockConfig::new().name("detached").without_observers());
+    let kept = TokioRwLockTrace::new(0).with_config(LockConfig::new().silent());
+    assert_eq!(kept.name(), Some("inherited"));
+    let from_inherited =
+        TokioRwLockTrace::new(0).with_config(LockConfig::inherited().name("from_inherited"));
+
+    // 이미 만든 락은 기본값이 바뀌어도 따라가지 않는다.
+    tracing_lock::set_default_config(LockConfig::new().name("later"));
+    assert_eq!(plain.name(), Some("inherited"));
+    assert_eq!(TokioRwLockTrace::new(0).name(), Some("later"));
+
+    *plain.write().await += 1;
+    drop(renamed.read().await);
+    drop(detached.read().await);
+    drop(kept.read().await);
+    drop(from_inherited.read().await);
+
+    let events = events.events();
+    let acquired: Vec<_> = events
+        .iter()
+        .filter(|e| e.kind == EventKind::Acquire)
+        .map(|e| e.name.as_deref())
+        .collect();
+    assert_eq!(
+        acquired,
+        [
+            Some("inherited"),
+            Some("renamed"),
+            Some("inherited"),
+            Some("from_inherited")
+        ]
+    );
+    let snapshot = tracing_lock::registry::top_contended(usize::MAX);
+    assert!(snapshot
+        .iter()
+        .any(|info| info.name.as_deref() == Some("inherited")));
+}
@@ -0,0 +1,38 @@
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+use tracing_lock_derive::TracedLocks;
+
+#[derive(TracedLocks)]
+struct AppState {
+    users: TokioRwLockTrace<Vec<String>>,
+    sessions: TokioRwLockTrace<u32>,
+    config: tracing_lock::TokioRwLockTrace<&'static str>,
+    #[allow(dead_code)]
+    started: std::time::Instant,
+}
+
+#[tokio::test]
+async fn report_labels_each_lock_field() {
+    let mut state = AppState {
+        users: TokioRwLockTrace::new(Vec::new()),
+        sessions: TokioRwLockTrace::new(0).with_config(LockConfig::new().name("active-sessions")),
+        config: TokioRwLockTrace::new("v1"),
+        started: std::time::Instant::now(),
+    };
+    state.name_locks();
+    state.users.write().await.push("ann".to_string());
+    drop(state.users.read().await);
+    *state.sessions.write().await += 1;
+
+    let report = state.lock_report();
+    let counts: Vec<_> = report
+        .iter()
+        .map(|(field, stats)| (*field, stats.reads, stats.writes))
+        .collect();
+    assert_eq!(
+        counts,
+        [("users", 1, 1), ("sessions", 0, 1), ("config", 0, 0)]
+    );
+    assert_eq!(state.users.name(), Some("users"));
+    // 이미 이름이 있는 락은 그대로 둔다.
+    assert_eq!(state.sessions.name(), Some("active-sessions"));
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "trace")]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{EventKind, LockConfig, TracedMutex, TracedRwLock};
+
+// 만들어진 `lock_guard` span 을 센다.
+#[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+struct GuardSpans(Arc<AtomicUsize>);
+
+#[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for GuardSpans {
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        _: &tracing::span::Id,
+        _: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if attrs.metadata().name() == "lock_guard" {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// 계측 스위치가 전역이므로 다른 테스트와 프로세스를 나눈다.
+#[tokio::test]
+async fn instrumentation_can_be_switched_off_and_back_on_at_runtime() {
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    let spans = Arc::new(AtomicUsize::new(0));
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    let _subscriber = {
+        use tracing_subscriber::layer::SubscriberExt;
+        tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(GuardSpans(spans.clone())),
+        )
+    };
+    let reads = Arc::new(AtomicUsize::new(0));
+    let counter = reads.clone();
+    let events = CapturingObserver::new();
+    let config = LockConfig::new()
+        .clock(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            std::time::Instant::now()
+        })
+        .observer(events.clone());
+    let lock = TracedRwLock::new(0).with_config(config.clone());
+    let mutex = TracedMutex::new(0).with_config(config);
+
+    tracing_lock::set_enabled(false);
+    *lock.write().await += 1;
+    drop(lock.read().await);
+    *mutex.lock().await += 1;
+    // 꺼진 동안 얻은 가드는 시간을 재지 않았으므로 다시 켠 뒤에 놓아도 해제 이벤트가 없다.
+    let held = lock.read().await;
+    tracing_lock::set_enabled(true);
+    drop(held);
+    assert_eq!(reads.load(Ordering::Relaxed), 0);
+    assert!(events.events().is_empty());
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    assert_eq!(spans.load(Ordering::Relaxed), 0);
+    // 통계 카운터는 그대로 움직인다.
+    assert_eq!((lock.stats().reads, lock.stats().writes), (2, 1));
+    assert_eq!(mutex.stats().writes, 1);
+
+    *lock.write().await += 1;
+    assert!(reads.load(Ordering::Relaxed) > 0);
+    let kinds: Vec<_> = events.events().iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [EventKind::Created, EventKind::Acquire, EventKind::Release]
+    );
+    #[cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+    assert_eq!(spans.load(Ordering::Relaxed), 1);
+}
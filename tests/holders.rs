@@ -0,0 +1,52 @@
+#![cfg(feature = "trace")]
+
+use tracing_lock::{registry, LockConfig, TokioRwLockTrace};
+
+// 보유자 기록은 `install_panic_hook` 으로 한 번 켜면 끌 수 없는 전역 스위치이므로 다른 테스트와
+// 프로세스를 나눈다.
+#[tokio::test]
+async fn unnamed_lock_shows_creation_site() {
+    registry::install_panic_hook();
+    let line = line!() + 1;
+    let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().silent());
+    let _guard = lock.write().await;
+    let site = format!("created at {}:{}", file!(), line);
+
+    let info = registry::top_contended(usize::MAX)
+        .into_iter()
+        .find(|info| info.display_name() == format!("{}:{}", file!(), line))
+        .unwrap();
+    let dump = registry::dump_state();
+    assert!(
+        dump.contains(&format!("lock #{} {} (", info.id, site)),
+        "{dump}"
+    );
+    assert!(lock
+        .report()
+        .starts_with(&format!("lock #{} {}\n", info.id, site)));
+}
+
+#[tokio::test]
+async fn assert_read_uncontended_names_the_writer() {
+    registry::install_panic_hook();
+    let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("cfg").silent());
+    lock.assert_read_uncontended();
+    let reader = lock.read().await;
+    lock.assert_read_uncontended();
+    drop(reader);
+
+    let line = line!() + 1;
+    let writer = lock.write().await;
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        lock.assert_read_uncontended()
+    }))
+    .unwrap_err();
+    drop(writer);
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.contains("lock \"cfg\" would block"), "{message}");
+    assert!(
+        message.contains(&format!("write-held from {}:{}", file!(), line)),
+        "{message}"
+    );
+    lock.assert_read_uncontended();
+}
@@ -0,0 +1,29 @@
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{EventKind, LockConfig, TokioRwLockTrace};
+
+// 전역 필터를 바꾸므로 다른 테스트와 프로세스를 나눈다.
+#[tokio::test]
+async fn events_acquired_in_excluded_file_are_dropped() {
+    // `replace` 는 라이브러리의 `src/lock.rs` 안에서 쓰기 락을 잡는다.
+    tracing_lock::set_location_filter(|location| !location.file().ends_with("src/lock.rs"));
+
+    let events = CapturingObserver::new();
+    let lock =
+        TokioRwLockTrace::new(0).with_config(LockConfig::new().silent().observer(events.clone()));
+
+    assert_eq!(lock.replace(1).await, 0);
+    *lock.write().await += 1;
+    drop(lock.read().await);
+
+    let events = events.events();
+    let acquisitions: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e.kind, EventKind::Acquire | EventKind::Release))
+        .collect();
+    assert_eq!(acquisitions.len(), 4);
+    for event in &acquisitions {
+        let location = event.acquired_at.unwrap_or(event.location);
+        assert_eq!(location.file(), file!());
+    }
+    assert_eq!(lock.stats().writes, 2);
+}
@@ -0,0 +1,53 @@
+// `tracing-backend` 가 함께 켜지면 그쪽이 우선한다.
+#![cfg(feature = "log-backend")]
+
+use std::sync::Mutex;
+
+use log::{Level, Log, Metadata, Record};
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+struct TestLogger {
+    records: Mutex<Vec<(Level, String, String)>>,
+}
+
+impl Log for TestLogger {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.records.lock().unwrap().push((
+            record.level(),
+            record.target().to_string(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: TestLogger = TestLogger {
+    records: Mutex::new(Vec::new()),
+};
+
+#[tokio::test]
+async fn events_are_routed_through_log() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("logged"));
+    *lock.write().await += 1;
+
+    let records = LOGGER.records.lock().unwrap();
+    assert_eq!(records.len(), 3);
+    let (level, target, created) = &records[0];
+    assert_eq!((*level, target.as_str()), (Level::Trace, "tracing_lock"));
+    assert!(created.contains("lock 'logged' created"));
+    for (level, target, _) in &records[1..] {
+        assert_eq!((*level, target.as_str()), (Level::Debug, "tracing_lock"));
+    }
+    assert!(records[1].2.contains("Write lock 'logged' acquired"));
+    assert!(records[2]
+        .2
+        .contains("Write lock 'logged' released. Duration:"));
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::{Arc, Mutex};
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+// (이름, 레이블, 값). counter 는 더한 수, histogram 은 기록한 값이다.
+type Samples = Arc<Mutex<Vec<(String, Vec<(String, String)>, f64)>>>;
+
+struct Sample {
+    key: Key,
+    samples: Samples,
+}
+
+impl Sample {
+    fn push(&self, value: f64) {
+        let labels = self
+            .key
+            .labels()
+            .map(|label| (label.key().to_owned(), label.value().to_owned()))
+            .collect();
+        self.samples
+            .lock()
+            .unwrap()
+            .push((self.key.name().to_owned(), labels, value));
+    }
+}
+
+impl CounterFn for Sample {
+    fn increment(&self, value: u64) {
+        self.push(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.push(value as f64);
+    }
+}
+
+// gauge 는 설정한 값만 남긴다.
+impl GaugeFn for Sample {
+    fn increment(&self, _: f64) {}
+
+    fn decrement(&self, _: f64) {}
+
+    fn set(&self, value: f64) {
+        self.push(value);
+    }
+}
+
+impl HistogramFn for Sample {
+    fn record(&self, value: f64) {
+        self.push(value);
+    }
+}
+
+#[derive(Default)]
+struct Capture {
+    samples: Samples,
+}
+
+impl Capture {
+    fn sample(&self, key: &Key) -> Arc<Sample> {
+        Arc::new(Sample {
+            key: key.clone(),
+            samples: self.samples.clone(),
+        })
+    }
+}
+
+impl Recorder for Capture {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.sample(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.sample(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.sample(key))
+    }
+}
+
+fn labels(lock: &str, kind: &str) -> Vec<(String, String)> {
+    vec![
+        ("lock".to_owned(), lock.to_owned()),
+        ("kind".to_owned(), kind.to_owned()),
+    ]
+}
+
+#[test]
+fn acquisitions_waits_and_holds_are_recorded() {
+    let capture = Capture::default();
+    let samples = capture.samples.clone();
+    metrics::with_local_recorder(&capture, || {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let lock =
+                    TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs").silent());
+                *lock.write().await += 1;
+                let _ = *lock.read().await;
+                drop(lock.try_write().unwrap());
+            });
+    });
+
+    let samples = samples.lock().unwrap();
+    let count = |name: &str, kind: &str| {
+        samples
+            .iter()
+            .filter(|(n, l, _)| n == name && *l == labels("jobs", kind))
+            .count()
+    };
+    assert_eq!(count("tracing_lock_acquisitions_total", "write"), 2);
+    assert_eq!(count("tracing_lock_acquisitions_total", "read"), 1);
+    assert_eq!(count("tracing_lock_wait_seconds", "write"), 2);
+    assert_eq!(count("tracing_lock_wait_seconds", "read"), 1);
+    assert_eq!(count("tracing_lock_hold_seconds", "write"), 2);
+    assert_eq!(count("tracing_lock_hold_seconds", "read"), 1);
+    assert!(samples
+        .iter()
+        .filter(|(n, ..)| n == "tracing_lock_acquisitions_total")
+        .all(|(.., value)| *value == 1.0));
+    assert!(samples.iter().all(|(.., value)| *value >= 0.0));
+}
+
+#[test]
+fn tracked_sites_carry_a_site_label() {
+    let capture = Capture::default();
+    let samples = capture.samples.clone();
+    metrics::with_local_recorder(&capture, || {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let lock = TokioRwLockTrace::new(0)
+                    .with_config(LockConfig::new().name("sites").silent().track_sites());
+                for _ in 0..2 {
+                    let guard = lock.read().await;
+                    drop(guard);
+                }
+            });
+    });
+
+    let samples = samples.lock().unwrap();
+    let site = samples
+        .iter()
+        .find(|(n, ..)| n == "tracing_lock_site_acquisitions_total")
+        .and_then(|(_, l, _)| l.iter().find(|(k, _)| k == "site"))
+        .map(|(_, v)| v.clone())
+        .unwrap();
+    assert!(site.starts_with(&format!("{}:", file!())));
+    let values = |name: &str| -> Vec<f64> {
+        samples
+            .iter()
+            .filter(|(n, l, _)| {
+                n == name
+                    && l.iter().any(|(k, v)| k == "lock" && v == "sites")
+                    && l.iter().any(|(k, v)| k == "site" && *v == site)
+            })
+            .map(|(.., value)| *value)
+            .collect()
+    };
+    assert_eq!(values("tracing_lock_site_acquisitions_total"), [1.0, 1.0]);
+    assert_eq!(values("tracing_lock_site_holders"), [1.0, 0.0, 1.0, 0.0]);
+    assert_eq!(values("tracing_lock_site_wait_seconds").len(), 2);
+    assert_eq!(values("tracing_lock_site_hold_seconds").len(), 2);
+}
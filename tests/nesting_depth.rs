@@ -0,0 +1,38 @@
+use tracing_lock::testing::CapturingObserver;
+use tracing_lock::{EventKind, LockConfig, TokioRwLockTrace};
+
+// 깊이 기록 스위치가 전역이므로 다른 테스트와 프로세스를 나눈다.
+#[tokio::test]
+async fn release_depth_decreases_as_nested_guards_drop() {
+    tracing_lock::set_nesting_depth(true);
+    let events = CapturingObserver::new();
+    let named = |name: &'static str| {
+        TokioRwLockTrace::new(0).with_config(LockConfig::new().name(name).observer(events.clone()))
+    };
+    let (outer, middle, inner) = (named("outer"), named("middle"), named("inner"));
+    {
+        let _outer = outer.write().await;
+        let _middle = middle.read().await;
+        let _inner = inner.write().await;
+    }
+
+    let depths: Vec<_> = events
+        .events()
+        .iter()
+        .filter(|e| e.kind != EventKind::Created)
+        .map(|e| (e.kind, e.name.clone().unwrap(), e.depth))
+        .collect();
+    let released = |name: &str, depth| (EventKind::Release, name.to_string(), Some(depth));
+    let acquired = |name: &str, depth| (EventKind::Acquire, name.to_string(), Some(depth));
+    assert_eq!(
+        depths,
+        [
+            acquired("outer", 1),
+            acquired("middle", 2),
+            acquired("inner", 3),
+            released("inner", 2),
+            released("middle", 1),
+            released("outer", 0),
+        ]
+    );
+}
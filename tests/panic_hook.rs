@@ -0,0 +1,52 @@
+use std::sync::{Arc, Mutex};
+
+use tracing_lock::{registry, LockConfig, TokioRwLockTrace};
+
+// 패닉 훅은 프로세스 전역이므로 다른 테스트와 프로세스를 나누고, 끝나면 원래 훅으로 되돌린다.
+#[test]
+fn panic_hook_dumps_guards_held_at_panic() {
+    let dumps = Arc::new(Mutex::new(Vec::new()));
+    let sink = dumps.clone();
+    let original = std::panic::take_hook();
+    registry::install_panic_hook_with(move |dump| sink.lock().unwrap().push(dump.to_string()));
+
+    let lock = Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().name("dump-held")));
+    let worker = std::thread::Builder::new()
+        .name("dump-worker".to_string())
+        .spawn({
+            let lock = lock.clone();
+            move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                runtime.block_on(async {
+                    let _guard = lock.write().await;
+                    panic!("boom while holding dump-held");
+                })
+            }
+        })
+        .unwrap();
+    assert!(worker.join().is_err());
+
+    // 실패한 단언의 패닉도 훅을 거치므로 잠금을 쥔 채 단언하지 않는다.
+    let dumps = dumps.lock().unwrap().clone();
+    let dump = dumps
+        .iter()
+        .find(|dump| dump.contains("'dump-held'"))
+        .expect("dump mentions the held lock");
+    let line = dump
+        .lines()
+        .skip_while(|line| !line.contains("'dump-held'"))
+        .nth(1)
+        .unwrap();
+    assert!(line.contains("write held for"), "{line}");
+    assert!(line.contains("by thread dump-worker"), "{line}");
+    assert!(
+        line.contains(&format!("acquired at {}:", file!())),
+        "{line}"
+    );
+    // 패닉이 풀리면서 가드가 드롭되면 보유자에서도 빠진다.
+    assert!(!registry::dump_state().contains("'dump-held'"));
+    drop(std::panic::take_hook());
+    std::panic::set_hook(original);
+}
@@ -0,0 +1,110 @@
+// `trace` 를 끈 빌드에서도 계측 쪽과 같은 호출부가 컴파일되는지 본다.
+// (`cargo test --no-default-features`)
+#![cfg(not(feature = "trace"))]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use tracing_lock::{
+    AccessKind, Contended, LockConfig, SlowBy, TracedMutex, TracedMutexGuard, TracedRwLock,
+    TracedRwLockReadGuard, TracedRwLockWriteGuard, TracedSemaphore,
+};
+
+fn push(
+    mut guard: TracedRwLockWriteGuard<'_, Vec<u32>>,
+    item: u32,
+) -> TracedRwLockWriteGuard<'_, Vec<u32>> {
+    guard.push(item);
+    guard
+}
+
+fn len(guard: &TracedRwLockReadGuard<'_, Vec<u32>>) -> usize {
+    guard.reborrow().len()
+}
+
+#[tokio::test]
+async fn traced_api_compiles_without_instrumentation() {
+    let lock = TracedRwLock::new(Vec::new())
+        .with_config(LockConfig::new().name("jobs"))
+        .with_strict_fifo()
+        .with_group("queues")
+        .with_timing(false)
+        .with_min_logged_hold(Duration::from_millis(1))
+        .with_max_readers(8);
+    assert_eq!(lock.name(), None);
+
+    let mut guard = push(lock.write_prio(200).await, 1);
+    guard.complete();
+    let reader = guard.downgrade();
+    assert_eq!(len(&reader), 1);
+    reader.release();
+    lock.write_ctx("ctx").await.release();
+    lock.read_ctx("ctx").await.release_async().await;
+    assert_eq!(lock.peek(Vec::len).await, 1);
+    assert_eq!(
+        lock.with_write(|items| {
+            items.push(2);
+            items.len()
+        })
+        .await,
+        2
+    );
+    assert_eq!(lock.with_read(Vec::len).await, 2);
+    assert_eq!(lock.wait_while(Vec::is_empty).await.len(), 2);
+    assert_eq!(lock.read_after_next_write().await.len(), 2);
+    assert_eq!(
+        lock.read_fresh(Duration::from_secs(1), || async { vec![3] })
+            .await
+            .len(),
+        1
+    );
+    assert_eq!(lock.since_last_write(), None);
+    assert!(lock.write_backoff(2).await.is_ok());
+    let held = lock.read().await;
+    assert_eq!(
+        lock.write_backoff(2).await.err(),
+        Some(Contended { attempts: 2 })
+    );
+    assert!(lock.try_read_capped().is_ok());
+    drop(held);
+
+    // 진단 메서드는 빈 값을 돌려준다.
+    assert!(lock.report().is_empty());
+    assert!(lock.slowest().is_empty());
+    assert!(lock.slowest_by(SlowBy::Hold).is_empty());
+    assert!(lock.lock_span(AccessKind::Write).is_none());
+    assert_eq!(lock.contention_ratio(), 0.0);
+    let _ = lock.by_hold_time();
+    tracing::info_span!("request", lock = tracing::field::Empty).record("lock", lock.field());
+    lock.set_instrumented(false);
+    assert!(!lock.write_maybe().await.is_traced());
+    assert_eq!(lock.stats().writes, 0);
+
+    let weak = lock.downgrade();
+    let lock = Arc::new(weak.upgrade().unwrap());
+    let mut owned = lock.clone().write_owned().await;
+    owned.complete();
+    let owned = owned.downgrade();
+    assert!(Arc::ptr_eq(owned.rwlock(), &lock));
+    owned.release();
+    assert_eq!(lock.clone().read_owned().await.len(), 1);
+
+    let raw = RwLock::new(0);
+    let borrowed = TracedRwLock::borrow(&raw).with_config(LockConfig::new());
+    *borrowed.write().await += 1;
+    assert_eq!(*borrowed.read().await, 1);
+}
+
+#[tokio::test]
+async fn traced_mutex_and_semaphore_compile_without_instrumentation() {
+    let mutex = TracedMutex::new(0).with_config(LockConfig::new().name("hits"));
+    let mut guard: TracedMutexGuard<'_, u32> = mutex.lock().await;
+    *guard += 1;
+    assert_eq!(guard.acquired_at().file(), file!());
+    drop(guard);
+
+    let semaphore = TracedSemaphore::new(1);
+    let permit = semaphore.acquire().await.unwrap();
+    assert_eq!(permit.acquired_at().file(), file!());
+}
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tracing_lock::{LockConfig, LoggingRwLockReadGuard, LoggingRwLockWriteGuard, TokioRwLockTrace};
+
+#[tokio::test]
+async fn public_types_are_reachable() {
+    let lock: TokioRwLockTrace<u32> =
+        TokioRwLockTrace::from(Arc::new(RwLock::new(1))).with_config(LockConfig::new().name("api"));
+    assert_eq!(lock.name(), Some("api"));
+
+    {
+        let read: LoggingRwLockReadGuard<'_, u32> = lock.read().await;
+        assert_eq!(*read, 1);
+    }
+
+    let mut write: LoggingRwLockWriteGuard<'_, u32> = lock.write().await;
+    *write += 1;
+    assert_eq!(*write, 2);
+}
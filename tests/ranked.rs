@@ -0,0 +1,6 @@
+#[test]
+fn ranked_lock_ordering() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/ranked_in_order.rs");
+    t.compile_fail("tests/ui/ranked_out_of_order.rs");
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "trace")]
+
+use std::{sync::Arc, time::Duration};
+
+use tracing_lock::{
+    timeline::{self, Phase},
+    AccessKind, LockConfig, TokioRwLockTrace, TracedMutex,
+};
+
+// 기록 버퍼가 전역이므로 다른 테스트와 프로세스를 나눈다.
+#[tokio::test]
+async fn readers_queued_behind_a_writer_show_up_on_the_timeline() {
+    let lock =
+        Arc::new(TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs").silent()));
+    let mutex = TracedMutex::new(()).with_config(LockConfig::new().name("gate").silent());
+    timeline::start(1_000);
+
+    let mut writer = lock.write().await;
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            let lock = lock.clone();
+            tokio::spawn(async move { *lock.read().await })
+        })
+        .collect();
+    while lock.stats().waiters < 2 {
+        tokio::task::yield_now().await;
+    }
+    *writer += 1;
+    let reader = writer.downgrade();
+    for task in readers {
+        assert_eq!(task.await.unwrap(), 1);
+    }
+    drop(reader);
+
+    let held = mutex.lock().await;
+    let cancelled = tokio::time::timeout(Duration::from_millis(1), mutex.lock()).await;
+    assert!(cancelled.is_err());
+    drop(held);
+    let timeline = timeline::stop();
+    // 멈춘 뒤의 획득은 남지 않는다.
+    drop(lock.write().await);
+    assert!(timeline::stop().records.is_empty());
+
+    let jobs: Vec<_> = timeline
+        .records
+        .iter()
+        .filter(|record| record.lock.as_deref() == Some("jobs"))
+        .map(|record| (record.phase, record.access))
+        .collect();
+    use AccessKind::{Read, Write};
+    use Phase::{Acquired, Released, WaitStart};
+    assert_eq!(
+        jobs[..4],
+        [
+            (WaitStart, Write),
+            (Acquired, Write),
+            (WaitStart, Read),
+            (WaitStart, Read)
+        ]
+    );
+    // 내려 쓰면 쓰기를 놓고 같은 위치에서 읽기를 얻은 것으로 남는다.
+    assert_eq!(jobs[4..6], [(Released, Write), (Acquired, Read)]);
+    assert_eq!(jobs.len(), 11);
+    let readers: Vec<_> = timeline
+        .records
+        .iter()
+        .filter(|record| record.lock.as_deref() == Some("jobs") && record.phase == WaitStart)
+        .skip(1)
+        .collect();
+    assert!(readers.iter().all(|record| record.task.is_some()));
+    let gate: Vec<_> = timeline
+        .records
+        .iter()
+        .filter(|record| record.lock.as_deref() == Some("gate"))
+        .map(|record| record.phase)
+        .collect();
+    assert_eq!(
+        gate,
+        [WaitStart, Acquired, WaitStart, Phase::Cancelled, Released]
+    );
+
+    let mut trace = Vec::new();
+    timeline.write_chrome_trace(&mut trace).unwrap();
+    let trace = String::from_utf8(trace).unwrap();
+    let waits = trace
+        .lines()
+        .filter(|line| line.contains(r#""name":"wait read 'jobs'""#))
+        .count();
+    assert_eq!(waits, 2, "{trace}");
+    assert!(trace.contains(r#""outcome":"cancelled""#));
+    assert!(trace.contains(&format!(r#""name":"task {}""#, readers[0].task.unwrap())));
+
+    let mut lines = Vec::new();
+    timeline.write_ndjson(&mut lines).unwrap();
+    assert_eq!(
+        String::from_utf8(lines).unwrap().lines().count(),
+        timeline.records.len()
+    );
+}
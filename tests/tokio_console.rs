@@ -0,0 +1,131 @@
+#![cfg(feature = "tokio-console")]
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+type Fields = HashMap<String, String>;
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+#[derive(Default)]
+struct Recorded {
+    // span id -> (이름, target, 필드)
+    spans: HashMap<u64, (&'static str, &'static str, Fields)>,
+    // (target, 부모 span id, 필드)
+    events: Vec<(&'static str, Option<u64>, Fields)>,
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let meta = attrs.metadata();
+        self.recorded
+            .lock()
+            .unwrap()
+            .spans
+            .insert(id, (meta.name(), meta.target(), fields));
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        let mut recorded = self.recorded.lock().unwrap();
+        if let Some((_, _, fields)) = recorded.spans.get_mut(&id.into_u64()) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let parent = event.parent().map(span::Id::into_u64);
+        self.recorded
+            .lock()
+            .unwrap()
+            .events
+            .push((event.metadata().target(), parent, fields));
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[tokio::test]
+async fn lock_is_reported_as_console_resource() {
+    let recorder = Recorder::default();
+    let _default = tracing::subscriber::set_default(recorder.clone());
+
+    let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("jobs").silent());
+    drop(lock.read().await);
+    *lock.write().await += 1;
+
+    let recorded = recorder.recorded.lock().unwrap();
+    let (&resource, (_, target, fields)) = recorded
+        .spans
+        .iter()
+        .find(|(_, (name, _, _))| *name == "runtime.resource")
+        .expect("resource span");
+    assert_eq!(*target, "runtime::resource");
+    assert_eq!(fields["concrete_type"], "\"TokioRwLockTrace\"");
+    assert_eq!(fields["kind"], "\"Sync\"");
+    assert_eq!(fields["loc.file"], format!("{:?}", file!()));
+    assert!(fields.contains_key("loc.line") && fields.contains_key("loc.col"));
+    assert_eq!(fields["lock.name"], "\"jobs\"");
+
+    let updates: Vec<_> = recorded
+        .events
+        .iter()
+        .filter(|(target, parent, _)| {
+            *target == "runtime::resource::state_update" && *parent == Some(resource)
+        })
+        .map(|(_, _, fields)| {
+            let mut pairs: Vec<_> = fields.iter().map(|(k, v)| format!("{k}={v}")).collect();
+            pairs.sort();
+            pairs.join(" ")
+        })
+        .collect();
+    assert_eq!(
+        updates,
+        [
+            "write_locked=false",
+            "current_readers=0",
+            "current_readers.op=\"add\" current_readers=1",
+            "current_readers.op=\"sub\" current_readers=1",
+            "write_locked.op=\"override\" write_locked=true",
+            "write_locked.op=\"override\" write_locked=false",
+        ]
+    );
+}
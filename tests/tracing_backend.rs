@@ -0,0 +1,167 @@
+#![cfg(all(feature = "tracing-backend", not(feature = "log-backend")))]
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+use tracing_lock::{LockConfig, TokioRwLockTrace};
+
+type Fields = HashMap<String, String>;
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+#[derive(Default)]
+struct Recorded {
+    // span id -> (이름, 부모 span id, 필드)
+    spans: HashMap<u64, (&'static str, Option<u64>, Fields)>,
+    // (레벨, 부모 span id, 필드)
+    events: Vec<(Level, Option<u64>, Fields)>,
+    // 들어가 있는 span. 테스트는 스레드 하나에서만 돈다.
+    entered: Vec<u64>,
+}
+
+impl Recorded {
+    fn parent(&mut self, explicit: Option<&span::Id>, contextual: bool) -> Option<u64> {
+        match explicit {
+            Some(id) => Some(id.into_u64()),
+            None if contextual => self.entered.last().copied(),
+            None => None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    next_id: Arc<AtomicU64>,
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, meta: &Metadata<'_>) -> bool {
+        meta.target() == "tracing_lock" || meta.target() == module_path!()
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        let mut recorded = self.recorded.lock().unwrap();
+        let parent = recorded.parent(attrs.parent(), attrs.is_contextual());
+        recorded
+            .spans
+            .insert(id, (attrs.metadata().name(), parent, fields));
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        let mut recorded = self.recorded.lock().unwrap();
+        if let Some((_, _, fields)) = recorded.spans.get_mut(&id.into_u64()) {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let mut recorded = self.recorded.lock().unwrap();
+        let parent = recorded.parent(event.parent(), event.is_contextual());
+        recorded
+            .events
+            .push((*event.metadata().level(), parent, fields));
+    }
+
+    fn enter(&self, id: &span::Id) {
+        self.recorded.lock().unwrap().entered.push(id.into_u64());
+    }
+
+    fn exit(&self, id: &span::Id) {
+        let mut recorded = self.recorded.lock().unwrap();
+        let at = recorded.entered.iter().rposition(|&e| e == id.into_u64());
+        if let Some(at) = at {
+            recorded.entered.remove(at);
+        }
+    }
+}
+
+#[test]
+fn guard_lifetime_is_a_span_with_structured_events() {
+    let recorder = Recorder::default();
+    let line = tracing::subscriber::with_default(recorder.clone(), || {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async {
+                let lock = TokioRwLockTrace::new(0).with_config(LockConfig::new().name("traced"));
+                let request = tracing::info_span!("request");
+                let _entered = request.enter();
+                let line = line!() + 1;
+                let mut guard = lock.write().await;
+                *guard += 1;
+                drop(guard);
+                line
+            })
+    });
+
+    let recorded = recorder.recorded.lock().unwrap();
+    let (&request, _) = recorded
+        .spans
+        .iter()
+        .find(|(_, (name, ..))| *name == "request")
+        .expect("request span");
+    let (&guard, (_, parent, fields)) = recorded
+        .spans
+        .iter()
+        .find(|(_, (name, ..))| *name == "lock_guard")
+        .expect("guard span");
+    assert_eq!(*parent, Some(request));
+    assert_eq!(fields["lock"], "\"traced\"");
+    assert_eq!(fields["access"], "\"write\"");
+    assert_eq!(fields["caller.file"], format!("{:?}", file!()));
+    assert_eq!(fields["caller.line"], line.to_string());
+    assert!(fields.contains_key("waited_us"), "{fields:?}");
+    assert!(fields.contains_key("held_us"), "{fields:?}");
+
+    let events: Vec<_> = recorded
+        .events
+        .iter()
+        .filter(|(_, parent, _)| *parent == Some(guard))
+        .collect();
+    assert_eq!(events.len(), 2, "{:?}", recorded.events);
+    let (acquire, release) = (events[0], events[1]);
+    assert_eq!(acquire.0, Level::DEBUG);
+    assert_eq!(acquire.2["kind"], "Acquire");
+    assert_eq!(release.2["kind"], "Release");
+    assert_eq!(release.2["reason"], "\"dropped\"");
+    assert!(release.2.contains_key("held_us"), "{:?}", release.2);
+    for (_, _, fields) in [acquire, release] {
+        assert_eq!(fields["lock"], "\"traced\"");
+        assert_eq!(fields["caller.file"], format!("{:?}", file!()));
+    }
+    assert_eq!(acquire.2["caller.line"], line.to_string());
+
+    // 락 생성은 `trace` 레벨이며 가드 span 밖에서 나간다.
+    assert!(recorded
+        .events
+        .iter()
+        .any(|(level, parent, fields)| *level == Level::TRACE
+            && *parent == Some(request)
+            && fields["kind"] == "Created"));
+}
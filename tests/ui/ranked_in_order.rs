@@ -0,0 +1,15 @@
+use tracing_lock::ranked::{LockToken, RankedLock};
+
+async fn transfer(low: &RankedLock<u32, 1>, mid: &RankedLock<u32, 2>, high: &RankedLock<u32, 5>) {
+    let mut token = LockToken::new();
+    let (mut a, mut held) = low.write(&mut token).await;
+    let (b, mut held) = mid.read(&mut held).await;
+    let (mut c, _) = high.write(&mut held).await;
+    *a += *b;
+    *c += *b;
+}
+
+fn main() {
+    let (low, mid, high) = (RankedLock::new(1), RankedLock::new(2), RankedLock::new(3));
+    let _future = transfer(&low, &mid, &high);
+}
@@ -0,0 +1,12 @@
+use tracing_lock::ranked::{LockToken, RankedLock};
+
+async fn transfer(low: &RankedLock<u32, 1>, high: &RankedLock<u32, 2>) {
+    let mut token = LockToken::new();
+    let (_high, mut held) = high.write(&mut token).await;
+    let (_low, _) = low.write(&mut held).await;
+}
+
+fn main() {
+    let (low, high) = (RankedLock::new(1), RankedLock::new(2));
+    let _future = transfer(&low, &high);
+}
@@ -0,0 +1,74 @@
+//! `tracing-lock` 의 derive 매크로. `tracing-lock` 의 `derive` 기능으로 다시 내보낸다.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Type};
+
+/// 구조체의 `TokioRwLockTrace<_>` 필드들에 대한 메서드를 만든다.
+///
+/// - `lock_report(&self) -> Vec<(&'static str, LockStatsSnapshot)>`: 필드 이름과 통계를 선언 순서대로
+///   돌려준다.
+/// - `name_locks(&mut self)`: 이름이 없는 락에 필드 이름을 붙인다.
+///
+/// 타입 경로의 마지막 이름이 `TokioRwLockTrace` 가 아닌 필드는 건너뛴다.
+#[proc_macro_derive(TracedLocks)]
+pub fn derive_traced_locks(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "TracedLocks requires a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "TracedLocks can only be derived for structs",
+            ))
+        }
+    };
+    let locks: Vec<_> = fields
+        .iter()
+        .filter(|field| is_traced_lock(&field.ty))
+        .filter_map(|field| field.ident.as_ref())
+        .collect();
+    let labels = locks.iter().map(|ident| ident.to_string());
+    let names = labels.clone();
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// 락 필드마다 (필드 이름, 통계 스냅샷)을 선언 순서대로 돌려준다.
+            pub fn lock_report(&self) -> ::std::vec::Vec<(&'static str, ::tracing_lock::LockStatsSnapshot)> {
+                ::std::vec![#((#labels, self.#locks.stats()),)*]
+            }
+
+            /// 이름이 없는 락 필드에 필드 이름을 붙인다.
+            pub fn name_locks(&mut self) {
+                #(self.#locks.__name_if_unnamed(#names);)*
+            }
+        }
+    })
+}
+
+fn is_traced_lock(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "TokioRwLockTrace"),
+        _ => false,
+    }
+}